@@ -1,7 +1,7 @@
 use loco_rs::Result;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct Settings {
     pub allow_list: Option<Vec<String>>,
 }
@@ -11,8 +11,12 @@ use crate::{
     views::notes::PaginationResponse,
 };
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
 pub struct Params {
+    #[validate(
+        required(message = "title is required"),
+        length(min = 1, message = "title cannot be empty")
+    )]
     pub title: Option<String>,
     pub content: Option<String>,
 }
@@ -33,8 +37,7 @@ impl Params {
 }
 
 async fn load_item(ctx: &AppContext, id: i32) -> Result<Model> {
-    let item = Entity::find_by_id(id).one(&ctx.db).await?;
-    item.ok_or_else(|| Error::NotFound)
+    Entity::find_by_id_or_404(&ctx.db, id).await
 }
 
 pub async fn list(
@@ -44,6 +47,7 @@ pub async fn list(
     let pagination_query = query::PaginationQuery {
         page_size: params.pagination.page_size,
         page: params.pagination.page,
+        ..Default::default()
     };
 
     let paginated_notes = query::paginate(
@@ -55,10 +59,9 @@ pub async fn list(
     .await?;
 
     /*
-    if let Some(settings) = &ctx.config.settings {
-        let settings = common::settings::Settings::from_json(settings)?;
-        println!("allow list: {:?}", settings.allow_list);
-    }*/
+    let settings = ctx.config.get_settings::<common::settings::Settings>()?;
+    println!("allow list: {:?}", settings.allow_list);
+    */
 
     format::render()
         .cookies(&[
@@ -66,13 +69,13 @@ pub async fn list(
             cookie::Cookie::new("baz", "qux"),
         ])?
         .etag("foobar")?
-        .json(PaginationResponse::response(
-            paginated_notes,
-            &pagination_query,
-        ))
+        .json(PaginationResponse::response(paginated_notes))
 }
 
-pub async fn add(State(ctx): State<AppContext>, Json(params): Json<Params>) -> Result<Response> {
+pub async fn add(
+    State(ctx): State<AppContext>,
+    ValidJson(params): ValidJson<Params>,
+) -> Result<Response> {
     let mut item = ActiveModel {
         ..Default::default()
     };
@@ -84,7 +87,7 @@ pub async fn add(State(ctx): State<AppContext>, Json(params): Json<Params>) -> R
 pub async fn update(
     Path(id): Path<i32>,
     State(ctx): State<AppContext>,
-    Json(params): Json<Params>,
+    ValidJson(params): ValidJson<Params>,
 ) -> Result<Response> {
     let item = load_item(&ctx, id).await?;
     let mut item = item.into_active_model();
@@ -138,15 +141,12 @@ pub async fn get_one(
 impl ListQueryParams {
     #[must_use]
     pub fn into_query(&self) -> Condition {
-        let mut condition = query::condition();
-
-        if let Some(title) = &self.title {
-            condition = condition.like(Column::Title, title);
-        }
-        if let Some(content) = &self.content {
-            condition = condition.like(Column::Content, content);
-        }
-        condition.build()
+        query::condition()
+            .add_some(self.title.as_ref(), |c, title| c.like(Column::Title, title))
+            .add_some(self.content.as_ref(), |c, content| {
+                c.like(Column::Content, content)
+            })
+            .build()
     }
 }
 
@@ -1,6 +1,6 @@
 use loco_rs::{
     controller::views::pagination::{Pager, PagerMeta},
-    prelude::model::query::{PageResponse, PaginationQuery},
+    prelude::model::query::PageResponse,
 };
 use serde::{Deserialize, Serialize};
 
@@ -28,10 +28,7 @@ impl From<notes::Model> for ListResponse {
 
 impl PaginationResponse {
     #[must_use]
-    pub fn response(
-        data: PageResponse<notes::Model>,
-        pagination_query: &PaginationQuery,
-    ) -> Pager<Vec<ListResponse>> {
+    pub fn response(data: PageResponse<notes::Model>) -> Pager<Vec<ListResponse>> {
         Pager {
             results: data
                 .page
@@ -39,9 +36,10 @@ impl PaginationResponse {
                 .map(ListResponse::from)
                 .collect::<Vec<ListResponse>>(),
             info: PagerMeta {
-                page: pagination_query.page,
-                page_size: pagination_query.page_size,
+                page: data.current_page,
+                page_size: data.page_size,
                 total_pages: data.total_pages,
+                debug_sql: data.debug_sql,
             },
         }
     }
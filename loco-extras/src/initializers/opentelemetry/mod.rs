@@ -1,9 +1,107 @@
-use axum::{async_trait, Router as AxumRouter};
+use axum::{async_trait, http::HeaderMap, Router as AxumRouter};
 use axum_tracing_opentelemetry::middleware::{OtelAxumLayer, OtelInResponseLayer};
+use init_tracing_opentelemetry::{
+    init_propagator, otlp,
+    resource::DetectResource,
+    tracing_subscriber_ext::{build_logger_text, build_loglevel_filter_layer},
+};
 use loco_rs::{
     app::{AppContext, Initializer},
     Error, Result,
 };
+use opentelemetry_sdk::trace::{self as sdktrace, Sampler};
+use serde::Deserialize;
+use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Layer};
+
+/// The database query spans sea-orm records, at `trace` level.
+const DB_QUERY_SPANS: &str = "sea_orm::driver=trace";
+
+/// Settings read from `initializers.opentelemetry` in the app config.
+#[derive(Debug, Deserialize)]
+pub struct OpenTelemetryConfig {
+    /// Fraction of traces started by this app that are sampled, between `0.0`
+    /// and `1.0`. Requests carrying a `traceparent` follow the sampling
+    /// decision of the caller.
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: f64,
+}
+
+const fn default_sample_ratio() -> f64 {
+    1.0
+}
+
+impl Default for OpenTelemetryConfig {
+    fn default() -> Self {
+        Self {
+            sample_ratio: default_sample_ratio(),
+        }
+    }
+}
+
+impl OpenTelemetryConfig {
+    fn from_context(ctx: &AppContext) -> Result<Self> {
+        match ctx
+            .config
+            .initializers
+            .as_ref()
+            .and_then(|initializers| initializers.get("opentelemetry"))
+        {
+            Some(value) => Ok(serde_json::from_value(value.clone())?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Samples `ratio` of new traces, and follows the caller for propagated ones.
+#[must_use]
+pub fn sampler(ratio: f64) -> Sampler {
+    Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(ratio)))
+}
+
+/// Adds the trace context of the current span to the headers of an outgoing
+/// request, so the called service continues the same trace.
+pub fn inject_trace_context(headers: &mut HeaderMap) {
+    tracing_opentelemetry_instrumentation_sdk::http::inject_context(
+        &tracing_opentelemetry_instrumentation_sdk::find_current_context(),
+        headers,
+    );
+}
+
+/// Filters what is exported as traces: what is logged, and the database query
+/// spans, so queries run while handling a request nest under its span
+/// without logging every query. Like `build_loglevel_filter_layer`, which it
+/// calls, it leaves the log filter in `RUST_LOG`.
+fn build_otel_filter() -> EnvFilter {
+    build_loglevel_filter_layer().add_directive(
+        DB_QUERY_SPANS
+            .parse()
+            .expect("the database spans directive is valid"),
+    )
+}
+
+fn init_subscribers(config: &OpenTelemetryConfig) -> Result<()> {
+    let resource = DetectResource::default().build();
+    let tracer = otlp::init_tracer(resource.clone(), |pipeline| {
+        pipeline.with_trace_config(
+            sdktrace::config()
+                .with_resource(resource)
+                .with_sampler(sampler(config.sample_ratio)),
+        )
+    })
+    .map_err(|e| Error::Message(e.to_string()))?;
+    init_propagator().map_err(|e| Error::Message(e.to_string()))?;
+
+    let otel_filter = build_otel_filter();
+    let subscriber = tracing_subscriber::registry()
+        .with(
+            tracing_opentelemetry::layer()
+                .with_error_records_to_exceptions(true)
+                .with_tracer(tracer)
+                .with_filter(otel_filter),
+        )
+        .with(build_logger_text().with_filter(EnvFilter::from_default_env()));
+    tracing::subscriber::set_global_default(subscriber).map_err(|e| Error::Message(e.to_string()))
+}
 
 pub struct OpenTelemetryInitializer;
 
@@ -13,14 +111,12 @@ impl Initializer for OpenTelemetryInitializer {
         "opentelemetry".to_string()
     }
 
-    async fn before_run(&self, _app_context: &AppContext) -> Result<()> {
-        match init_tracing_opentelemetry::tracing_subscriber_ext::init_subscribers() {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                tracing::error!("Failed to initialize opentelemetry subscriber: {:?}", e);
-                Err(Error::Message(e.to_string()))
-            }
-        }
+    async fn before_run(&self, app_context: &AppContext) -> Result<()> {
+        let config = OpenTelemetryConfig::from_context(app_context)?;
+        init_subscribers(&config).map_err(|e| {
+            tracing::error!("Failed to initialize opentelemetry subscriber: {:?}", e);
+            e
+        })
     }
 
     async fn after_routes(&self, router: AxumRouter, _ctx: &AppContext) -> Result<AxumRouter> {
@@ -30,3 +126,134 @@ impl Initializer for OpenTelemetryInitializer {
         Ok(router)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{body::Body, extract::Request, routing::get, Router};
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::{
+        export::trace::{ExportResult, SpanData, SpanExporter},
+        propagation::TraceContextPropagator,
+        trace::TracerProvider,
+    };
+    use sea_orm::ConnectionTrait;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const TRACE_ID: &str = "0af7651916cd43dd8448eb211c80319c";
+    const PARENT_ID: &str = "b7ad6b7169203331";
+
+    #[derive(Debug, Clone, Default)]
+    struct Exported(Arc<Mutex<Vec<SpanData>>>);
+
+    impl SpanExporter for Exported {
+        fn export(
+            &mut self,
+            batch: Vec<SpanData>,
+        ) -> Pin<Box<dyn Future<Output = ExportResult> + Send + 'static>> {
+            self.0.lock().unwrap().extend(batch);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    /// Calls `/notes/:id`, which runs a database query, through the tracing
+    /// middleware, returning the exported spans and the headers the handler
+    /// propagated.
+    async fn call(sample_ratio: f64, traceparent: Option<&str>) -> (Vec<SpanData>, HeaderMap) {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let exported = Exported::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exported.clone())
+            .with_config(sdktrace::config().with_sampler(sampler(sample_ratio)))
+            .build();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_opentelemetry::layer()
+                .with_tracer(provider.tracer("test"))
+                .with_filter(build_otel_filter()),
+        );
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+        let outgoing = Arc::new(Mutex::new(HeaderMap::new()));
+        let handler_outgoing = outgoing.clone();
+        let app = Router::new()
+            .route(
+                "/notes/:id",
+                get(move || async move {
+                    db.execute_unprepared("SELECT 1").await.unwrap();
+                    inject_trace_context(&mut handler_outgoing.lock().unwrap());
+                }),
+            )
+            .layer(OtelAxumLayer::default());
+
+        let mut request = Request::builder().uri("/notes/42");
+        if let Some(traceparent) = traceparent {
+            request = request.header("traceparent", traceparent);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        provider.force_flush();
+
+        let spans = exported.0.lock().unwrap().clone();
+        let headers = outgoing.lock().unwrap().clone();
+        (spans, headers)
+    }
+
+    fn request_span(spans: &[SpanData]) -> &SpanData {
+        spans
+            .iter()
+            .find(|span| span.name == "GET /notes/:id")
+            .expect("request span")
+    }
+
+    #[tokio::test]
+    async fn exports_span_with_route_name() {
+        let (spans, _) = call(1.0, None).await;
+
+        assert_eq!(request_span(&spans).name, "GET /notes/:id");
+    }
+
+    #[tokio::test]
+    async fn nests_query_spans_under_request_span() {
+        let (spans, _) = call(1.0, None).await;
+        let request = request_span(&spans);
+
+        let query = spans
+            .iter()
+            .find(|span| span.name == "execute_unprepared")
+            .expect("query span");
+        assert_eq!(
+            query.span_context.trace_id(),
+            request.span_context.trace_id()
+        );
+        assert_eq!(query.parent_span_id, request.span_context.span_id());
+    }
+
+    #[tokio::test]
+    async fn honors_inbound_traceparent() {
+        let (spans, headers) = call(0.0, Some(&format!("00-{TRACE_ID}-{PARENT_ID}-01"))).await;
+
+        // sampled by the caller, despite the sample ratio
+        let request = request_span(&spans);
+        assert_eq!(request.span_context.trace_id().to_string(), TRACE_ID);
+        assert_eq!(request.parent_span_id.to_string(), PARENT_ID);
+
+        let outgoing = headers["traceparent"].to_str().unwrap();
+        assert!(outgoing.starts_with(&format!("00-{TRACE_ID}-")));
+        assert!(!outgoing.contains(PARENT_ID));
+    }
+
+    #[tokio::test]
+    async fn samples_by_ratio() {
+        let (spans, _) = call(0.0, None).await;
+        assert!(spans.is_empty());
+    }
+}
@@ -15,7 +15,7 @@ use axum::Router as AxumRouter;
 #[cfg(feature = "channels")]
 use crate::controller::channels::AppChannels;
 use crate::{
-    boot::{BootResult, ServeParams, StartMode},
+    boot::{self, BootResult, ServeParams, StartMode},
     cache::{self},
     config::{self, Config},
     controller::{
@@ -107,7 +107,7 @@ pub trait Hooks {
     async fn boot(mode: StartMode, environment: &Environment) -> Result<BootResult>;
 
     /// Start serving the Axum web application on the specified address and
-    /// port.
+    /// port, until [`boot::shutdown_signal`] completes.
     ///
     /// # Returns
     /// A Result indicating success () or an error if the server fails to start.
@@ -122,6 +122,7 @@ pub trait Hooks {
             listener,
             app.into_make_service_with_connect_info::<SocketAddr>(),
         )
+        .with_graceful_shutdown(boot::shutdown_signal())
         .await?;
 
         Ok(())
@@ -218,6 +219,13 @@ pub trait Initializer: Sync + Send {
     /// The initializer name or identifier
     fn name(&self) -> String;
 
+    /// The order of the initializer among the others, lower runs first.
+    /// Initializers of the same priority run in the order they are listed
+    /// in [`Hooks::initializers`].
+    fn priority(&self) -> i32 {
+        0
+    }
+
     /// Occurs after the app's `before_run`.
     /// Use this to for one-time initializations, load caches, perform web
     /// hooks, etc.
@@ -225,11 +233,30 @@ pub trait Initializer: Sync + Send {
         Ok(())
     }
 
+    /// Occurs after the app's `before_routes`, before the app routes are
+    /// added and the middlewares applied, so routes added here go through the
+    /// app middlewares too.
+    /// Use this to add routes or extensions, such as a health endpoint
+    async fn before_routes(
+        &self,
+        router: AxumRouter<AppContext>,
+        _ctx: &AppContext,
+    ) -> Result<AxumRouter<AppContext>> {
+        Ok(router)
+    }
+
     /// Occurs after the app's `after_routes`.
     /// Use this to compose additional functionality and wire it into an Axum
     /// Router
     async fn after_routes(&self, router: AxumRouter, _ctx: &AppContext) -> Result<AxumRouter> {
         Ok(router)
     }
+
+    /// Occurs when the app shuts down, after the server stopped on `SIGINT`
+    /// or `SIGTERM`, in the reverse order of the initializers.
+    /// Use this to flush buffers, close connections, etc.
+    async fn on_shutdown(&self, _ctx: &AppContext) -> Result<()> {
+        Ok(())
+    }
 }
 // </snip>
@@ -4,6 +4,7 @@
 use std::path::PathBuf;
 
 use axum::Router;
+use colored::Colorize;
 #[cfg(feature = "with-db")]
 use sea_orm_migration::MigratorTrait;
 use tracing::{info, trace, warn};
@@ -11,7 +12,7 @@ use tracing::{info, trace, warn};
 #[cfg(feature = "with-db")]
 use crate::db;
 use crate::{
-    app::{AppContext, Hooks},
+    app::{AppContext, Hooks, Initializer},
     banner::print_banner,
     cache,
     config::{self, Config},
@@ -26,7 +27,6 @@ use crate::{
     worker::{self, AppWorker, Pool, Processor, RedisConnectionManager},
     Result,
 };
-use colored::Colorize;
 
 /// Represents the application startup mode.
 pub enum StartMode {
@@ -45,6 +45,8 @@ pub struct BootResult {
     pub router: Option<Router>,
     /// worker processor
     pub processor: Option<Processor>,
+    /// The app initializers, shut down when the app stops
+    pub initializers: Vec<Box<dyn Initializer>>,
 }
 
 /// Configuration structure for serving an application.
@@ -71,27 +73,78 @@ pub async fn start<H: Hooks>(boot: BootResult, server_config: ServeParams) -> Re
     let BootResult {
         router,
         processor,
-        app_context: _,
+        app_context,
+        initializers,
     } = boot;
 
-    match (router, processor) {
+    let result = match (router, processor) {
         (Some(router), Some(processor)) => {
             tokio::spawn(async move {
                 if let Err(err) = process(processor).await {
                     tracing::error!("Error in processing: {:?}", err);
                 }
             });
-            H::serve(router, server_config).await?;
+            H::serve(router, server_config).await
         }
-        (Some(router), None) => {
-            H::serve(router, server_config).await?;
+        (Some(router), None) => H::serve(router, server_config).await,
+        // the processor runs until the process is stopped
+        (None, Some(processor)) => tokio::select! {
+            result = process(processor) => result,
+            () = shutdown_signal() => Ok(()),
+        },
+        _ => Ok(()),
+    };
+
+    // the hooks run even when serving failed, then its error is returned
+    run_shutdown_hooks(&initializers, &app_context).await;
+    result
+}
+
+/// Completes when the process receives `SIGINT` (Ctrl-C), or `SIGTERM` on
+/// Unix, to stop the server gracefully.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %err, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
         }
-        (None, Some(processor)) => {
-            process(processor).await?;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+    info!("shutting down");
+}
+
+/// Runs [`Initializer::on_shutdown`] in the reverse order of the
+/// initializers, so an initializer shuts down before the ones it may depend
+/// on. A failing hook is logged and does not stop the others.
+async fn run_shutdown_hooks(initializers: &[Box<dyn Initializer>], app_context: &AppContext) {
+    for initializer in initializers.iter().rev() {
+        if let Err(err) = initializer.on_shutdown(app_context).await {
+            tracing::error!(
+                initializer = initializer.name(),
+                error = %err,
+                "initializer shutdown failed"
+            );
         }
-        _ => {}
     }
-    Ok(())
 }
 
 async fn process(processor: Processor) -> Result<()> {
@@ -308,38 +361,30 @@ pub async fn create_app<H: Hooks>(
 /// When could not create the application
 pub async fn run_app<H: Hooks>(mode: &StartMode, app_context: AppContext) -> Result<BootResult> {
     H::before_run(&app_context).await?;
-    let initializers = H::initializers(&app_context).await?;
+    let mut initializers = H::initializers(&app_context).await?;
+    sort_by_priority(&mut initializers);
     info!(initializers = ?initializers.iter().map(|init| init.name()).collect::<Vec<_>>().join(","), "initializers loaded");
     for initializer in &initializers {
         initializer.before_run(&app_context).await?;
     }
     match mode {
         StartMode::ServerOnly => {
-            let app = H::before_routes(&app_context).await?;
-            let app = H::routes(&app_context).to_router::<H>(app_context.clone(), app)?;
-            let mut router = H::after_routes(app, &app_context).await?;
-            for initializer in &initializers {
-                router = initializer.after_routes(router, &app_context).await?;
-            }
-
+            let router = create_router::<H>(&app_context, &initializers).await?;
             Ok(BootResult {
                 app_context,
                 router: Some(router),
                 processor: None,
+                initializers,
             })
         }
         StartMode::ServerAndWorker => {
             let processor = create_processor::<H>(&app_context)?;
-            let app = H::before_routes(&app_context).await?;
-            let app = H::routes(&app_context).to_router::<H>(app_context.clone(), app)?;
-            let mut router = H::after_routes(app, &app_context).await?;
-            for initializer in &initializers {
-                router = initializer.after_routes(router, &app_context).await?;
-            }
+            let router = create_router::<H>(&app_context, &initializers).await?;
             Ok(BootResult {
                 app_context,
                 router: Some(router),
                 processor: Some(processor),
+                initializers,
             })
         }
         StartMode::WorkerOnly => {
@@ -348,10 +393,38 @@ pub async fn run_app<H: Hooks>(mode: &StartMode, app_context: AppContext) -> Res
                 app_context,
                 router: None,
                 processor: Some(processor),
+                initializers,
             })
         }
     }
 }
+
+/// Builds the app router, in this order: [`Hooks::before_routes`], the
+/// initializers [`Initializer::before_routes`], the app routes with their
+/// middlewares, [`Hooks::after_routes`], and the initializers
+/// [`Initializer::after_routes`].
+async fn create_router<H: Hooks>(
+    app_context: &AppContext,
+    initializers: &[Box<dyn Initializer>],
+) -> Result<Router> {
+    let mut app = H::before_routes(app_context).await?;
+    for initializer in initializers {
+        app = initializer.before_routes(app, app_context).await?;
+    }
+    let app = H::routes(app_context).to_router::<H>(app_context.clone(), app)?;
+    let mut router = H::after_routes(app, app_context).await?;
+    for initializer in initializers {
+        router = initializer.after_routes(router, app_context).await?;
+    }
+    Ok(router)
+}
+
+/// Sorts the initializers by [`Initializer::priority`], keeping the listed
+/// order of initializers of the same priority.
+fn sort_by_priority(initializers: &mut [Box<dyn Initializer>]) {
+    initializers.sort_by_key(|initializer| initializer.priority());
+}
+
 /// Creates and configures a [`Processor`] for handling worker tasks.
 fn create_processor<H: Hooks>(app_context: &AppContext) -> Result<Processor> {
     let queues = worker::get_queues(&app_context.config.workers.queues);
@@ -427,3 +500,153 @@ pub async fn connect_redis(config: &Config) -> Option<Pool<RedisConnectionManage
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::tests_cfg;
+
+    struct Recording {
+        name: &'static str,
+        priority: i32,
+        fails: bool,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Recording {
+        fn boxed(
+            name: &'static str,
+            priority: i32,
+            calls: &Arc<Mutex<Vec<&'static str>>>,
+        ) -> Box<dyn Initializer> {
+            Box::new(Self {
+                name,
+                priority,
+                fails: false,
+                calls: calls.clone(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Initializer for Recording {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        async fn before_run(&self, _ctx: &AppContext) -> Result<()> {
+            self.calls.lock().unwrap().push(self.name);
+            Ok(())
+        }
+
+        async fn on_shutdown(&self, _ctx: &AppContext) -> Result<()> {
+            self.calls.lock().unwrap().push(self.name);
+            if self.fails {
+                return Err(Error::string("shutdown failed"));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn shuts_down_initializers_in_reverse_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let initializers = ["db", "cache", "metrics"]
+            .into_iter()
+            .map(|name| {
+                Box::new(Recording {
+                    name,
+                    priority: 0,
+                    fails: name == "cache",
+                    calls: calls.clone(),
+                }) as Box<dyn Initializer>
+            })
+            .collect::<Vec<_>>();
+
+        run_shutdown_hooks(&initializers, &tests_cfg::app::get_app_context().await).await;
+
+        assert_eq!(*calls.lock().unwrap(), ["metrics", "cache", "db"]);
+    }
+
+    struct Version;
+
+    #[async_trait]
+    impl Initializer for Version {
+        fn name(&self) -> String {
+            "version".to_string()
+        }
+
+        async fn before_routes(
+            &self,
+            router: Router<AppContext>,
+            _ctx: &AppContext,
+        ) -> Result<Router<AppContext>> {
+            Ok(router.route("/_version", get(|| async { "ok" })))
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_routes_added_before_app_routes() {
+        let ctx = tests_cfg::app::get_app_context().await;
+        let initializers: Vec<Box<dyn Initializer>> = vec![Box::new(Version)];
+        let router = create_router::<tests_cfg::db::AppHook>(&ctx, &initializers)
+            .await
+            .unwrap();
+
+        let response = router
+            .oneshot(
+                axum::http::Request::get("/_version")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"ok");
+    }
+
+    #[tokio::test]
+    async fn runs_initializers_by_priority() {
+        let ctx = tests_cfg::app::get_app_context().await;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut initializers = vec![
+            Recording::boxed("view-engine", 10, &calls),
+            Recording::boxed("metrics", 0, &calls),
+            Recording::boxed("config", -10, &calls),
+            Recording::boxed("cache", 0, &calls),
+        ];
+
+        sort_by_priority(&mut initializers);
+        for initializer in &initializers {
+            initializer.before_run(&ctx).await.unwrap();
+        }
+        run_shutdown_hooks(&initializers, &ctx).await;
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "config",
+                "metrics",
+                "cache",
+                "view-engine",
+                "view-engine",
+                "cache",
+                "metrics",
+                "config"
+            ]
+        );
+    }
+}
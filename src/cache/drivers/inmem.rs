@@ -1,10 +1,13 @@
 //! # In-Memory Cache Driver
 //!
 //! This module implements a cache driver using an in-memory cache.
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use moka::sync::Cache;
+use moka::{sync::Cache, Expiry};
 
 use super::CacheDriver;
 use crate::cache::CacheResult;
@@ -24,6 +27,22 @@ pub fn new() -> Box<dyn CacheDriver> {
 /// Represents the in-memory cache driver.
 pub struct Inmem {
     cache: Cache<String, String>,
+    /// The pairs inserted with an expiry, along with their time to live
+    expiring: Cache<String, (String, Duration)>,
+}
+
+/// Expires the pairs of [`Inmem::expiring`] after their time to live.
+struct TimeToLive;
+
+impl Expiry<String, (String, Duration)> for TimeToLive {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        (_, ttl): &(String, Duration),
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(*ttl)
+    }
 }
 
 impl Inmem {
@@ -34,7 +53,14 @@ impl Inmem {
     /// A boxed [`CacheDriver`] instance.
     #[must_use]
     pub fn from(cache: Cache<String, String>) -> Box<dyn CacheDriver> {
-        Box::new(Self { cache })
+        let mut expiring = Cache::builder().expire_after(TimeToLive);
+        if let Some(max_capacity) = cache.policy().max_capacity() {
+            expiring = expiring.max_capacity(max_capacity);
+        }
+        Box::new(Self {
+            cache,
+            expiring: expiring.build(),
+        })
     }
 }
 
@@ -46,7 +72,7 @@ impl CacheDriver for Inmem {
     ///
     /// Returns a `CacheError` if there is an error during the operation.
     async fn contains_key(&self, key: &str) -> CacheResult<bool> {
-        Ok(self.cache.contains_key(key))
+        Ok(self.cache.contains_key(key) || self.expiring.contains_key(key))
     }
 
     /// Retrieves a value from the cache based on the provided key.
@@ -55,7 +81,10 @@ impl CacheDriver for Inmem {
     ///
     /// Returns a `CacheError` if there is an error during the operation.
     async fn get(&self, key: &str) -> CacheResult<Option<String>> {
-        Ok(self.cache.get(key))
+        Ok(self
+            .cache
+            .get(key)
+            .or_else(|| self.expiring.get(key).map(|(value, _)| value)))
     }
 
     /// Inserts a key-value pair into the cache.
@@ -64,11 +93,24 @@ impl CacheDriver for Inmem {
     ///
     /// Returns a `CacheError` if there is an error during the operation.
     async fn insert(&self, key: &str, value: &str) -> CacheResult<()> {
+        self.expiring.remove(key);
         self.cache
             .insert(key.to_string(), Arc::new(value).to_string());
         Ok(())
     }
 
+    /// Inserts a key-value pair into the cache, removed after `ttl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CacheError` if there is an error during the operation.
+    async fn insert_with_expiry(&self, key: &str, value: &str, ttl: Duration) -> CacheResult<()> {
+        self.cache.remove(key);
+        self.expiring
+            .insert(key.to_string(), (value.to_string(), ttl));
+        Ok(())
+    }
+
     /// Removes a key-value pair from the cache.
     ///
     /// # Errors
@@ -76,6 +118,7 @@ impl CacheDriver for Inmem {
     /// Returns a `CacheError` if there is an error during the operation.
     async fn remove(&self, key: &str) -> CacheResult<()> {
         self.cache.remove(key);
+        self.expiring.remove(key);
         Ok(())
     }
 
@@ -86,6 +129,7 @@ impl CacheDriver for Inmem {
     /// Returns a `CacheError` if there is an error during the operation.
     async fn clear(&self) -> CacheResult<()> {
         self.cache.invalidate_all();
+        self.expiring.invalidate_all();
         Ok(())
     }
 }
@@ -122,6 +166,27 @@ mod tests {
         assert!(!mem.contains_key("key").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn can_expire_key() {
+        let mem = new();
+        mem.insert_with_expiry("key", "loco", Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(mem.get("key").await.unwrap(), Some("loco".to_string()));
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(!mem.contains_key("key").await.unwrap());
+        assert_eq!(mem.get("key").await.unwrap(), None);
+
+        // a plain insert replaces the expiring pair
+        mem.insert_with_expiry("key", "loco", Duration::from_millis(20))
+            .await
+            .unwrap();
+        mem.insert("key", "kept").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(mem.get("key").await.unwrap(), Some("kept".to_string()));
+    }
+
     #[tokio::test]
     async fn can_clear() {
         let mem = new();
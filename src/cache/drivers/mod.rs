@@ -1,6 +1,8 @@
 //! # Cache Drivers Module
 //!
 //! This module defines traits and implementations for cache drivers.
+use std::time::Duration;
+
 use async_trait::async_trait;
 
 use super::CacheResult;
@@ -36,6 +38,19 @@ pub trait CacheDriver: Sync + Send {
     /// operation.
     async fn insert(&self, key: &str, value: &str) -> CacheResult<()>;
 
+    /// Inserts a key-value pair into the cache, removed after `ttl`.
+    ///
+    /// Drivers without expiry keep the pair like [`CacheDriver::insert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`super::CacheError`] if there is an error during the
+    /// operation.
+    async fn insert_with_expiry(&self, key: &str, value: &str, ttl: Duration) -> CacheResult<()> {
+        let _ = ttl;
+        self.insert(key, value).await
+    }
+
     /// Removes a key-value pair from the cache.
     ///
     /// # Errors
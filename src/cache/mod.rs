@@ -3,7 +3,7 @@
 //! This module provides a generic cache interface for various cache drivers.
 pub mod drivers;
 
-use std::future::Future;
+use std::{future::Future, time::Duration};
 
 use self::drivers::CacheDriver;
 use crate::Result as LocoResult;
@@ -87,6 +87,32 @@ impl Cache {
         self.driver.insert(key, value).await
     }
 
+    /// Inserts a key-value pair into the cache, removed after `ttl`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use loco_rs::cache::{self, CacheResult};
+    ///
+    /// pub async fn insert_with_expiry() -> CacheResult<()> {
+    ///     let cache = cache::Cache::new(cache::drivers::inmem::new());
+    ///     cache.insert_with_expiry("key", "value", Duration::from_secs(60)).await
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A [`CacheResult`] indicating the success of the operation.
+    pub async fn insert_with_expiry(
+        &self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> CacheResult<()> {
+        self.driver.insert_with_expiry(key, value, ttl).await
+    }
+
     /// Retrieves the value associated with the given key from the cache,
     /// or inserts it if it does not exist, using the provided closure to
     /// generate the value.
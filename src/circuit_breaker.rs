@@ -0,0 +1,376 @@
+//! # Circuit Breaker
+//!
+//! Lets handlers degrade gracefully when an optional dependency, such as a
+//! cache or a search service, is down: the call falls back (skip the cache,
+//! read from the database) instead of failing the request.
+//!
+//! After `failure_threshold` consecutive failures the circuit opens, and the
+//! dependency is not called at all for `reset_timeout`, so a dead service is
+//! not hammered with requests that are bound to fail. Once the timeout
+//! elapses a single trial call is let through again, while concurrent calls
+//! keep falling back; a success closes the circuit, a failure keeps it open
+//! for another `reset_timeout`. Allow more trial calls with
+//! [`CircuitBreaker::half_open_calls`].
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use loco_rs::{circuit_breaker::CircuitBreaker, prelude::*};
+//!
+//! static SEARCH: CircuitBreaker = CircuitBreaker::new("search", 5, Duration::from_secs(30));
+//!
+//! async fn search_service(term: &str) -> Result<Vec<String>> {
+//!     Ok(vec![term.to_string()])
+//! }
+//!
+//! async fn search_database(term: &str) -> Vec<String> {
+//!     vec![]
+//! }
+//!
+//! async fn search(term: &str) -> Vec<String> {
+//!     SEARCH
+//!         .optional(search_service(term))
+//!         .or_else(|| search_database(term))
+//!         .await
+//! }
+//! ```
+
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The state of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls are skipped until the reset timeout elapses.
+    Open,
+    /// The reset timeout elapsed, the next trial calls decide whether the
+    /// circuit closes again.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Inner {
+    failures: u32,
+    opened_at: Option<Instant>,
+    /// The trial calls in flight while half open.
+    trials: u32,
+}
+
+/// Tracks failures of an optional dependency and stops calling it while it
+/// keeps failing.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    half_open_calls: u32,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit, opening after `failure_threshold`
+    /// consecutive failures for `reset_timeout`. `name` identifies the
+    /// dependency in logs.
+    #[must_use]
+    pub const fn new(name: &'static str, failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold,
+            reset_timeout,
+            half_open_calls: 1,
+            inner: Mutex::new(Inner {
+                failures: 0,
+                opened_at: None,
+                trials: 0,
+            }),
+        }
+    }
+
+    /// Sets how many trial calls may run at once while the circuit is half
+    /// open, 1 by default.
+    #[must_use]
+    pub const fn half_open_calls(mut self, calls: u32) -> Self {
+        self.half_open_calls = calls;
+        self
+    }
+
+    /// Returns the current state of the circuit.
+    ///
+    /// # Panics
+    /// When the state lock is poisoned.
+    #[must_use]
+    pub fn state(&self) -> CircuitState {
+        self.state_of(&self.inner.lock().unwrap())
+    }
+
+    fn state_of(&self, inner: &Inner) -> CircuitState {
+        match inner.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.reset_timeout => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether a call may go through, taking a trial slot when half open.
+    fn permit(&self) -> Option<Trial<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        match self.state_of(&inner) {
+            CircuitState::Closed => Some(Trial(None)),
+            CircuitState::Open => None,
+            CircuitState::HalfOpen if inner.trials < self.half_open_calls => {
+                inner.trials += 1;
+                Some(Trial(Some(self)))
+            }
+            CircuitState::HalfOpen => None,
+        }
+    }
+
+    /// Wraps a call to the dependency. Complete it with
+    /// [`Optional::or_else`] to provide the fallback.
+    pub fn optional<F>(&self, call: F) -> Optional<'_, F> {
+        Optional {
+            breaker: self,
+            call,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.opened_at.is_some() {
+            tracing::info!(dependency = self.name, "circuit closed");
+        }
+        inner.failures = 0;
+        inner.opened_at = None;
+        inner.trials = 0;
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.failures = inner.failures.saturating_add(1);
+        if inner.failures >= self.failure_threshold {
+            if inner.opened_at.is_none() {
+                tracing::warn!(
+                    dependency = self.name,
+                    failures = inner.failures,
+                    "circuit opened"
+                );
+            }
+            inner.opened_at = Some(Instant::now());
+            inner.trials = 0;
+        }
+    }
+}
+
+/// A permit to call the dependency, holding a trial slot when the circuit is
+/// half open. The slot is given back if the call is dropped before it
+/// completes, so a cancelled trial does not keep the circuit half open.
+struct Trial<'a>(Option<&'a CircuitBreaker>);
+
+impl Trial<'_> {
+    /// The call completed, and its outcome was recorded.
+    fn complete(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for Trial<'_> {
+    fn drop(&mut self) {
+        if let Some(breaker) = self.0 {
+            let mut inner = breaker.inner.lock().unwrap();
+            inner.trials = inner.trials.saturating_sub(1);
+        }
+    }
+}
+
+/// A call to an optional dependency, see [`CircuitBreaker::optional`].
+#[must_use]
+pub struct Optional<'a, F> {
+    breaker: &'a CircuitBreaker,
+    call: F,
+}
+
+impl<'a, F> Optional<'a, F> {
+    /// Runs the call, or `fallback` when the call fails or the circuit is
+    /// open. The call is not polled at all while the circuit is open, or
+    /// while it is half open and its trial calls are all in flight.
+    ///
+    /// # Panics
+    /// When the state lock is poisoned.
+    pub async fn or_else<T, E, Fb, FbFut>(self, fallback: Fb) -> T
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Display,
+        Fb: FnOnce() -> FbFut,
+        FbFut: Future<Output = T>,
+    {
+        let Some(trial) = self.breaker.permit() else {
+            return fallback().await;
+        };
+        let result = self.call.await;
+        match result {
+            Ok(value) => {
+                self.breaker.record_success();
+                trial.complete();
+                value
+            }
+            Err(err) => {
+                tracing::warn!(
+                    dependency = self.breaker.name,
+                    error = %err,
+                    "optional dependency failed, falling back"
+                );
+                self.breaker.record_failure();
+                trial.complete();
+                fallback().await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    async fn call(breaker: &CircuitBreaker, calls: &AtomicU32, ok: bool) -> &'static str {
+        breaker
+            .optional(async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                if ok {
+                    Ok("service")
+                } else {
+                    Err("unavailable")
+                }
+            })
+            .or_else(|| async { "fallback" })
+            .await
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_failure() {
+        let breaker = CircuitBreaker::new("test", 3, Duration::from_secs(60));
+        let calls = AtomicU32::new(0);
+
+        assert_eq!(call(&breaker, &calls, true).await, "service");
+        assert_eq!(call(&breaker, &calls, false).await, "fallback");
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn success_resets_failures() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::from_secs(60));
+        let calls = AtomicU32::new(0);
+
+        call(&breaker, &calls, false).await;
+        call(&breaker, &calls, true).await;
+        call(&breaker, &calls, false).await;
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn trips_open_and_recovers() {
+        let breaker = CircuitBreaker::new("test", 3, Duration::from_millis(50));
+        let calls = AtomicU32::new(0);
+
+        for _ in 0..3 {
+            assert_eq!(call(&breaker, &calls, false).await, "fallback");
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // the dependency is no longer called while open
+        assert_eq!(call(&breaker, &calls, true).await, "fallback");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // a failing trial call opens the circuit again
+        assert_eq!(call(&breaker, &calls, false).await, "fallback");
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(call(&breaker, &calls, true).await, "service");
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    async fn trip(breaker: &CircuitBreaker) {
+        let calls = AtomicU32::new(0);
+        for _ in 0..breaker.failure_threshold {
+            call(breaker, &calls, false).await;
+        }
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+
+    #[tokio::test]
+    async fn half_open_lets_one_trial_call_through() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::from_millis(50));
+        trip(&breaker).await;
+        let calls = AtomicU32::new(0);
+
+        let (done, wait) = tokio::sync::oneshot::channel::<()>();
+        let trial = breaker
+            .optional(async {
+                wait.await.ok();
+                Ok::<_, &str>("service")
+            })
+            .or_else(|| async { "fallback" });
+        tokio::pin!(trial);
+        assert!(futures_util::poll!(&mut trial).is_pending());
+
+        // concurrent calls fall back while the trial call is in flight
+        assert_eq!(call(&breaker, &calls, true).await, "fallback");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        done.send(()).unwrap();
+        assert_eq!(trial.await, "service");
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(call(&breaker, &calls, true).await, "service");
+    }
+
+    #[tokio::test]
+    async fn half_open_calls_sets_the_trial_calls() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::from_millis(50)).half_open_calls(2);
+        trip(&breaker).await;
+        let calls = AtomicU32::new(0);
+
+        let trial = || {
+            breaker
+                .optional(std::future::pending::<Result<&str, &str>>())
+                .or_else(|| async { "fallback" })
+        };
+        let (first, second) = (trial(), trial());
+        tokio::pin!(first, second);
+        assert!(futures_util::poll!(&mut first).is_pending());
+        assert!(futures_util::poll!(&mut second).is_pending());
+
+        assert_eq!(call(&breaker, &calls, true).await, "fallback");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn cancelled_trial_gives_its_slot_back() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::from_millis(50));
+        trip(&breaker).await;
+
+        let pending = breaker
+            .optional(std::future::pending::<Result<&str, &str>>())
+            .or_else(|| async { "fallback" });
+        assert!(tokio::time::timeout(Duration::from_millis(10), pending)
+            .await
+            .is_err());
+
+        let calls = AtomicU32::new(0);
+        assert_eq!(call(&breaker, &calls, true).await, "service");
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
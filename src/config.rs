@@ -28,7 +28,7 @@ use std::{
 
 use fs_err as fs;
 use lazy_static::lazy_static;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
 use tracing::info;
 
@@ -64,8 +64,8 @@ pub struct Config {
     ///     - google.com
     ///     - apple.com
     /// ```
-    /// And then optionally deserialize it to your own `Settings` type by
-    /// accessing `ctx.config.settings`.
+    /// And then optionally deserialize it to your own `Settings` type with
+    /// [`Config::get_settings`], or by accessing `ctx.config.settings`.
     #[serde(default)]
     pub settings: Option<serde_json::Value>,
 
@@ -317,6 +317,10 @@ pub struct Server {
     pub host: String,
     /// Identify via the `Server` header
     pub ident: Option<String>,
+    /// Secret used to sign values handed out to clients, such as pagination
+    /// cursors and signed cookies.
+    #[serde(default)]
+    pub secret_key: Option<String>,
     /// Middleware configurations for the server, including payload limits,
     /// logging, and error handling.
     pub middlewares: middleware::Config,
@@ -331,6 +335,16 @@ impl Server {
     pub fn full_url(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// Returns the configured `secret_key`.
+    ///
+    /// # Errors
+    /// When no secret key is configured.
+    pub fn secret(&self) -> Result<&str> {
+        self.secret_key
+            .as_deref()
+            .ok_or_else(|| Error::Message("`server.secret_key` is not configured".to_string()))
+    }
 }
 /// Background worker configuration
 /// Example (development):
@@ -486,6 +500,36 @@ impl Config {
             .map_err(|err| Error::YAMLFile(err, selected_path.to_string_lossy().to_string()))
     }
 
+    /// Deserializes the custom `settings` into your own `Settings` type.
+    ///
+    /// # Errors
+    /// When there are no settings, or they do not deserialize into `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use loco_rs::tests_cfg;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Clone, Deserialize)]
+    /// struct Settings {
+    ///     allow_list: Vec<String>,
+    /// }
+    ///
+    /// let mut config = tests_cfg::config::test_config();
+    /// config.settings = Some(serde_json::json!({ "allow_list": ["google.com"] }));
+    ///
+    /// let settings = config.get_settings::<Settings>().unwrap();
+    /// assert_eq!(settings.allow_list, ["google.com"]);
+    /// ```
+    pub fn get_settings<T: DeserializeOwned>(&self) -> Result<T> {
+        let value = self
+            .settings
+            .as_ref()
+            .ok_or_else(|| Error::Message("no settings found in config".to_string()))?;
+        Ok(T::deserialize(value)?)
+    }
+
     /// Get a reference to the JWT configuration.
     ///
     /// # Errors
@@ -507,3 +551,67 @@ impl std::fmt::Display for Config {
         write!(f, "{content}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::tests_cfg;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+    struct Settings {
+        allow_list: Vec<String>,
+        retries: u32,
+        #[serde(default)]
+        verbose: bool,
+    }
+
+    #[test]
+    fn can_get_typed_settings() {
+        let mut config = tests_cfg::config::test_config();
+        config.settings = Some(json!({
+            "allow_list": ["google.com", "apple.com"],
+            "retries": 3,
+        }));
+
+        let settings = config.get_settings::<Settings>().unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                allow_list: vec!["google.com".to_string(), "apple.com".to_string()],
+                retries: 3,
+                verbose: false,
+            }
+        );
+
+        assert_eq!(config.get_settings::<Settings>().unwrap(), settings);
+    }
+
+    #[test]
+    fn get_settings_follows_changed_settings() {
+        let mut config = tests_cfg::config::test_config();
+        config.settings = Some(json!({ "allow_list": [], "retries": 3 }));
+        assert_eq!(config.get_settings::<Settings>().unwrap().retries, 3);
+
+        let mut clone = config.clone();
+        clone.settings = Some(json!({ "allow_list": [], "retries": 5 }));
+        assert_eq!(clone.get_settings::<Settings>().unwrap().retries, 5);
+        assert_eq!(config.get_settings::<Settings>().unwrap().retries, 3);
+
+        config.settings = Some(json!({ "allow_list": [], "retries": 7 }));
+        assert_eq!(config.get_settings::<Settings>().unwrap().retries, 7);
+
+        clone.settings = None;
+        assert!(clone.get_settings::<Settings>().is_err());
+    }
+
+    #[test]
+    fn get_settings_errors() {
+        let mut config = tests_cfg::config::test_config();
+        assert!(config.get_settings::<Settings>().is_err());
+
+        config.settings = Some(json!({ "allow_list": "google.com" }));
+        assert!(config.get_settings::<Settings>().is_err());
+    }
+}
@@ -2,24 +2,39 @@
 //! configuring routes in an Axum application. It allows you to define route
 //! prefixes, add routes, and configure middlewares for the application.
 
-use axum::Router as AXRouter;
+use std::{collections::BTreeMap, fmt};
+
+use axum::{Extension, Router as AXRouter};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::fmt;
 
 #[cfg(feature = "channels")]
 use super::channels::AppChannels;
-
 use crate::{
     app::{AppContext, Hooks},
-    controller::{middleware::MiddlewareLayer, routes::Routes},
-    Result,
+    controller::{
+        matched_route::MatchedRoute, middleware::MiddlewareLayer, route_table::RouteTable,
+        routes::Routes,
+    },
+    Error, Result,
 };
 
 lazy_static! {
     static ref NORMALIZE_URL: Regex = Regex::new(r"/+").unwrap();
 }
 
+/// Collapses repeated slashes and drops the trailing one, except for the root.
+pub(super) fn normalize_uri(uri: &str) -> String {
+    let normalized = NORMALIZE_URL.replace_all(uri, "/");
+    if normalized == "/" {
+        normalized.to_string()
+    } else {
+        normalized
+            .strip_suffix('/')
+            .map_or_else(|| normalized.to_string(), std::string::ToString::to_string)
+    }
+}
+
 /// Represents the routes of the application.
 #[derive(Clone)]
 pub struct AppRoutes {
@@ -33,6 +48,7 @@ pub struct ListRoutes {
     pub uri: String,
     pub actions: Vec<axum::http::Method>,
     pub method: axum::routing::MethodRouter<AppContext>,
+    pub name: Option<String>,
 }
 
 impl fmt::Display for ListRoutes {
@@ -82,28 +98,25 @@ impl AppRoutes {
             .iter()
             .flat_map(|controller| {
                 let mut uri_parts = vec![base_url_prefix.clone()];
+                if let Some(version) = controller.version_segment() {
+                    uri_parts.push(version);
+                }
                 if let Some(prefix) = controller.prefix.as_ref() {
                     uri_parts.push(prefix.to_string());
                 }
                 controller.handlers.iter().map(move |handler| {
-                    let mut parts = uri_parts.clone();
-                    parts.push(handler.uri.to_string());
-                    let joined_parts = parts.join("/");
-
-                    let normalized = NORMALIZE_URL.replace_all(&joined_parts, "/");
-                    let uri = if normalized == "/" {
-                        normalized.to_string()
+                    let handler = if controller.automatic_options {
+                        handler.with_automatic_options()
                     } else {
-                        normalized.strip_suffix('/').map_or_else(
-                            || normalized.to_string(),
-                            std::string::ToString::to_string,
-                        )
+                        handler.clone()
                     };
-
+                    let mut parts = uri_parts.clone();
+                    parts.push(handler.uri.to_string());
                     ListRoutes {
-                        uri,
-                        actions: handler.actions.clone(),
-                        method: handler.method.clone(),
+                        uri: normalize_uri(&parts.join("/")),
+                        actions: handler.actions,
+                        method: handler.method,
+                        name: handler.name,
                     }
                 })
             })
@@ -163,6 +176,28 @@ impl AppRoutes {
         self
     }
 
+    /// Returns the URI patterns of the named routes, see [`Routes::name`].
+    ///
+    /// # Errors
+    /// When the same name is given to routes of different URIs.
+    pub fn route_table(&self) -> Result<RouteTable> {
+        let mut routes = BTreeMap::new();
+        for route in self.collect() {
+            let Some(name) = route.name else {
+                continue;
+            };
+            // the methods of a URI may share its name
+            if let Some(uri) = routes.get(&name).filter(|uri| **uri != route.uri) {
+                return Err(Error::Message(format!(
+                    "route name `{name}` is given to both `{uri}` and `{}`",
+                    route.uri
+                )));
+            }
+            routes.insert(name, route.uri);
+        }
+        Ok(RouteTable::new(routes))
+    }
+
     #[must_use]
     pub fn middlewares<H: Hooks>(&self, ctx: &AppContext) -> Vec<Box<dyn MiddlewareLayer>> {
         H::middlewares(ctx)
@@ -202,8 +237,10 @@ impl AppRoutes {
         //
         for router in self.collect() {
             tracing::info!("{}", router.to_string());
-            app = app.route(&router.uri, router.method);
+            let matched = MatchedRoute::new(&router.uri);
+            app = app.route(&router.uri, router.method.layer(Extension(matched)));
         }
+        app = app.layer(Extension(self.route_table()?));
 
         #[cfg(feature = "channels")]
         if let Some(channels) = self.channels.as_ref() {
@@ -237,13 +274,13 @@ impl AppRoutes {
 #[cfg(test)]
 mod tests {
 
-    use super::*;
-    use crate::prelude::*;
-    use crate::tests_cfg;
     use insta::assert_debug_snapshot;
     use rstest::rstest;
     use tower::ServiceExt;
 
+    use super::*;
+    use crate::{controller::RouteInfo, prelude::*, tests_cfg};
+
     async fn action() -> Result<Response> {
         format::json("loco")
     }
@@ -309,6 +346,220 @@ mod tests {
         }
     }
 
+    #[rstest]
+    #[case(None, "1", "/v1/users/_ping")]
+    #[case(None, "/v2/", "/v2/users/_ping")]
+    #[case(None, "api/v3", "/api/v3/users/_ping")]
+    #[case(Some("api"), "1", "/api/v1/users/_ping")]
+    fn can_load_versioned_routes(
+        #[case] app_prefix: Option<&str>,
+        #[case] version: &str,
+        #[case] expected: &str,
+    ) {
+        let mut app_router = AppRoutes::empty();
+        if let Some(app_prefix) = app_prefix {
+            app_router = app_router.prefix(app_prefix);
+        }
+        let routes = Routes::at("users")
+            .version(version)
+            .add("/_ping", get(action));
+        let app_router = app_router
+            .add_route(routes)
+            .add_route(Routes::at("status").add("/_ping", get(action)));
+
+        let uris = app_router
+            .collect()
+            .into_iter()
+            .map(|route| route.uri)
+            .collect::<Vec<_>>();
+        let unversioned = app_prefix.map_or("/status/_ping".to_string(), |prefix| {
+            format!("/{prefix}/status/_ping")
+        });
+        assert_eq!(uris, [expected.to_string(), unversioned]);
+    }
+
+    #[test]
+    fn can_describe_routes() {
+        let routes = Routes::new()
+            .prefix("/notes/")
+            .add("/", get(action).post(action))
+            .add(":id", get(action).delete(action))
+            .name("notes.show");
+
+        assert_eq!(
+            routes.describe(),
+            vec![
+                RouteInfo {
+                    uri: "/notes".to_string(),
+                    actions: vec![axum::http::Method::GET, axum::http::Method::POST],
+                    order: 0,
+                    name: None,
+                },
+                RouteInfo {
+                    uri: "/notes/:id".to_string(),
+                    actions: vec![axum::http::Method::GET, axum::http::Method::DELETE],
+                    order: 1,
+                    name: Some("notes.show".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn can_answer_automatic_options() {
+        let routes = Routes::new()
+            .add("/notes", get(action).post(action))
+            .add("/custom", get(action).options(|| async { "custom" }))
+            .with_automatic_options();
+
+        let router = AppRoutes::empty()
+            .add_route(routes)
+            .to_router::<tests_cfg::db::AppHook>(
+                tests_cfg::app::get_app_context().await,
+                AXRouter::new(),
+            )
+            .unwrap();
+        let call = |method: axum::http::Method, uri: &str| {
+            router.clone().oneshot(
+                axum::http::Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = call(axum::http::Method::OPTIONS, "/notes").await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::NO_CONTENT);
+        assert_eq!(
+            response.headers()[axum::http::header::ALLOW],
+            "GET,HEAD,POST,OPTIONS"
+        );
+
+        let response = call(axum::http::Method::HEAD, "/notes").await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        // an explicit OPTIONS handler is kept
+        let response = call(axum::http::Method::OPTIONS, "/custom").await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        assert!(!response.headers().contains_key(axum::http::header::ALLOW));
+    }
+
+    #[cfg(feature = "ws")]
+    #[tokio::test]
+    async fn can_echo_over_websocket() {
+        use axum::extract::ws::{Message, WebSocketUpgrade};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        async fn echo(ws: WebSocketUpgrade, State(_ctx): State<AppContext>) -> Response {
+            ws.on_upgrade(|mut socket| async move {
+                while let Some(Ok(Message::Text(text))) = socket.recv().await {
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+            })
+        }
+
+        let routes = Routes::new().add_ws("/ws", echo);
+        assert_eq!(routes.describe()[0].actions, [axum::http::Method::GET]);
+
+        let router = AppRoutes::empty()
+            .add_route(routes)
+            .to_router::<tests_cfg::db::AppHook>(
+                tests_cfg::app::get_app_context().await,
+                AXRouter::new(),
+            )
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        // a bare-bones client: the opening handshake, then one masked text frame
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                b"GET /ws HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\n\
+                  Upgrade: websocket\r\nSec-WebSocket-Version: 13\r\n\
+                  Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let mut handshake = vec![];
+        while !handshake.ends_with(b"\r\n\r\n") {
+            handshake.push(stream.read_u8().await.unwrap());
+        }
+        assert!(handshake.starts_with(b"HTTP/1.1 101"));
+
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x81, 0x80 | 4];
+        frame.extend(mask);
+        frame.extend(b"loco".iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        stream.write_all(&frame).await.unwrap();
+
+        let mut reply = [0; 6];
+        stream.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply, [0x81, 4, b'l', b'o', b'c', b'o']);
+    }
+
+    #[tokio::test]
+    async fn handler_layer_applies_to_one_route() {
+        let routes = Routes::new()
+            .add("/layered", get(action))
+            .handler_layer(tower_http::set_header::SetResponseHeaderLayer::overriding(
+                axum::http::header::HeaderName::from_static("x-layered"),
+                axum::http::HeaderValue::from_static("yes"),
+            ))
+            .add("/plain", get(action));
+
+        let router = AppRoutes::empty()
+            .add_route(routes)
+            .to_router::<tests_cfg::db::AppHook>(
+                tests_cfg::app::get_app_context().await,
+                AXRouter::new(),
+            )
+            .unwrap();
+
+        for (uri, layered) in [("/layered", true), ("/plain", false)] {
+            let req = axum::http::Request::builder()
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .unwrap();
+            let response = router.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), axum::http::StatusCode::OK);
+            assert_eq!(
+                response.headers().contains_key("x-layered"),
+                layered,
+                "{uri}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(None, "status", "/_ping")]
+    #[case(None, "status/", "/_ping")]
+    #[case(None, "/status/", "_ping")]
+    #[case(None, "status", "_ping")]
+    #[case(Some("api/"), "/status", "/_ping")]
+    fn joins_prefix_and_uri_with_single_slash(
+        #[case] app_prefix: Option<&str>,
+        #[case] prefix: &str,
+        #[case] uri: &str,
+    ) {
+        let mut app_router = AppRoutes::empty();
+        if let Some(app_prefix) = app_prefix {
+            app_router = app_router.prefix(app_prefix);
+        }
+        let app_router = app_router.add_route(Routes::at(prefix).add(uri, get(action)));
+
+        let expected = app_prefix.map_or("/status/_ping", |_| "/api/status/_ping");
+        assert_eq!(app_router.collect()[0].uri, expected);
+    }
+
     #[rstest]
     #[case(axum::http::Method::GET, get(action))]
     #[case(axum::http::Method::POST, post(action))]
@@ -342,4 +593,31 @@ mod tests {
         let response = router.oneshot(req).await.unwrap();
         assert!(response.status().is_success());
     }
+
+    #[tokio::test]
+    async fn can_extract_matched_route() {
+        async fn matched(route: MatchedRoute) -> String {
+            route.to_string()
+        }
+
+        let app_router =
+            AppRoutes::empty().add_route(Routes::new().prefix("notes").add("/:id", get(matched)));
+
+        let ctx = tests_cfg::app::get_app_context().await;
+        let router = app_router
+            .to_router::<tests_cfg::db::AppHook>(ctx, axum::Router::new())
+            .unwrap();
+
+        let req = axum::http::Request::builder()
+            .uri("/notes/42")
+            .body(axum::body::Body::empty())
+            .unwrap();
+
+        let response = router.oneshot(req).await.unwrap();
+        assert!(response.status().is_success());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "/notes/:id");
+    }
 }
@@ -0,0 +1,442 @@
+//! JSON batch endpoint.
+//!
+//! Lets a client send several sub-requests in one HTTP call. Each operation
+//! is dispatched through the app router, in order, and its response is
+//! returned with its own status:
+//!
+//! ```json
+//! POST /_batch
+//! {
+//!   "operations": [
+//!     { "method": "POST", "path": "/notes", "body": { "title": "one" } },
+//!     { "method": "POST", "path": "/notes", "body": { "title": "two" } }
+//!   ],
+//!   "fail_fast": true
+//! }
+//! ```
+//!
+//! Sub-requests carry the headers of the batch request, such as
+//! `Authorization`. With `fail_fast`, the operations after the first one
+//! failing (`4xx` or `5xx`) are skipped and left out of the response.
+//!
+//! With `transaction`, the batch also opens a database transaction and hands
+//! it to every operation as a [`BatchTransaction`] request extension. It is
+//! committed when all the operations succeeded and rolled back otherwise, so
+//! handlers writing through it are all applied or not at all:
+//!
+//! ```rust
+//! use axum::Extension;
+//! use loco_rs::{controller::batch::BatchTransaction, prelude::*, tests_cfg::db::test_db};
+//!
+//! async fn add(
+//!     State(ctx): State<AppContext>,
+//!     tx: Option<Extension<BatchTransaction>>,
+//!     Json(name): Json<String>,
+//! ) -> Result<Response> {
+//!     let note = test_db::ActiveModel {
+//!         name: ActiveValue::Set(name),
+//!         ..Default::default()
+//!     };
+//!     let note = match tx {
+//!         Some(Extension(tx)) => note.insert(tx.as_ref()).await?,
+//!         None => note.insert(&ctx.db).await?,
+//!     };
+//!     format::json(note.id)
+//! }
+//! ```
+//!
+//! Mount the endpoint in `Hooks::after_routes`:
+//!
+//! ```rust,ignore
+//! async fn after_routes(router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
+//!     Ok(batch::mount(router, "/_batch", ctx))
+//! }
+//! ```
+//!
+//! A batch has at most [`DEFAULT_MAX_OPERATIONS`] operations, larger ones are
+//! rejected with `413 Payload Too Large`. Use [`mount_with_max_operations`]
+//! to change it.
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderMap, Method, StatusCode},
+    routing::post,
+    Json, Router as AXRouter,
+};
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
+
+use crate::{app::AppContext, controller::ErrorDetail, Error, Result};
+
+/// The maximum number of operations of a batch mounted with [`mount`].
+pub const DEFAULT_MAX_OPERATIONS: usize = 100;
+
+/// A sub-request of a batch.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Operation {
+    pub method: String,
+    pub path: String,
+    /// JSON body of the sub-request, if any.
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BatchRequest {
+    pub operations: Vec<Operation>,
+    /// Skip the remaining operations after the first failing one.
+    #[serde(default)]
+    pub fail_fast: bool,
+    /// Run the operations in a database transaction, see
+    /// [`BatchTransaction`]. Implies `fail_fast`.
+    #[serde(default)]
+    pub transaction: bool,
+}
+
+/// The response to an [`Operation`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationResponse {
+    pub status: u16,
+    /// The response body: parsed when it is JSON, a string otherwise.
+    pub body: serde_json::Value,
+}
+
+impl OperationResponse {
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.status < 400
+    }
+}
+
+/// The database transaction of a batch running in transaction mode, set as
+/// an extension of its sub-requests.
+#[derive(Clone)]
+pub struct BatchTransaction(Arc<DatabaseTransaction>);
+
+impl AsRef<DatabaseTransaction> for BatchTransaction {
+    fn as_ref(&self) -> &DatabaseTransaction {
+        &self.0
+    }
+}
+
+async fn call(
+    router: AXRouter,
+    operation: &Operation,
+    headers: &HeaderMap,
+    transaction: Option<&BatchTransaction>,
+) -> Result<OperationResponse> {
+    let method = Method::from_bytes(operation.method.to_uppercase().as_bytes())
+        .map_err(|_| Error::BadRequest(format!("invalid method `{}`", operation.method)))?;
+
+    let mut request = Request::builder().method(method).uri(&operation.path);
+    for (name, value) in headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            request = request.header(name, value);
+        }
+    }
+    let body = match &operation.body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(serde_json::to_vec(body)?)
+        }
+        None => Body::empty(),
+    };
+    if let Some(transaction) = transaction {
+        request = request.extension(transaction.clone());
+    }
+    let request = request
+        .body(body)
+        .map_err(|_| Error::BadRequest(format!("invalid path `{}`", operation.path)))?;
+
+    let response = router
+        .oneshot(request)
+        .await
+        .map_err(|err| Error::Message(err.to_string()))?;
+    let status = response.status().as_u16();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .map_err(|err| Error::Message(err.to_string()))?;
+    let body = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(&body).into_owned()));
+
+    Ok(OperationResponse { status, body })
+}
+
+/// Dispatches the operations of a batch through the router, in order, and
+/// returns their responses.
+///
+/// # Errors
+/// When the batch has more than `max_operations` operations, an operation
+/// has an invalid method or path, cannot be dispatched, or the transaction
+/// cannot be opened, committed or rolled back.
+pub async fn dispatch(
+    router: AXRouter,
+    db: &DatabaseConnection,
+    headers: &HeaderMap,
+    batch: &BatchRequest,
+    max_operations: usize,
+) -> Result<Vec<OperationResponse>> {
+    if batch.operations.len() > max_operations {
+        return Err(Error::CustomError(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorDetail::new(
+                "payload_too_large".to_string(),
+                format!("a batch has at most {max_operations} operations"),
+            ),
+        ));
+    }
+
+    let transaction = if batch.transaction {
+        Some(BatchTransaction(Arc::new(db.begin().await?)))
+    } else {
+        None
+    };
+    let fail_fast = batch.fail_fast || batch.transaction;
+
+    let mut responses = Vec::with_capacity(batch.operations.len());
+    for operation in &batch.operations {
+        let response = call(router.clone(), operation, headers, transaction.as_ref()).await?;
+        let failed = !response.is_success();
+        responses.push(response);
+        if failed && fail_fast {
+            break;
+        }
+    }
+
+    if let Some(BatchTransaction(transaction)) = transaction {
+        let transaction = Arc::try_unwrap(transaction).map_err(|_| {
+            Error::Message("the batch transaction is still held by a handler".to_string())
+        })?;
+        if responses.iter().all(OperationResponse::is_success) {
+            transaction.commit().await?;
+        } else {
+            transaction.rollback().await?;
+        }
+    }
+    Ok(responses)
+}
+
+/// Adds a `POST` batch endpoint at `path`, dispatching its operations through
+/// `router`.
+pub fn mount(router: AXRouter, path: &str, ctx: &AppContext) -> AXRouter {
+    mount_with_max_operations(router, path, ctx, DEFAULT_MAX_OPERATIONS)
+}
+
+/// Like [`mount`], accepting batches of up to `max_operations` operations.
+pub fn mount_with_max_operations(
+    router: AXRouter,
+    path: &str,
+    ctx: &AppContext,
+    max_operations: usize,
+) -> AXRouter {
+    let inner = router.clone();
+    let db = ctx.db.clone();
+    router.route(
+        path,
+        post(
+            move |headers: HeaderMap, Json(batch): Json<BatchRequest>| async move {
+                dispatch(inner, &db, &headers, &batch, max_operations)
+                    .await
+                    .map(Json)
+            },
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::State,
+        routing::{get, post},
+        Extension,
+    };
+    use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait};
+    use serde_json::json;
+
+    use super::*;
+    use crate::tests_cfg::db::{seeded_connection, test_db};
+
+    async fn create(
+        State(db): State<DatabaseConnection>,
+        tx: Option<Extension<BatchTransaction>>,
+        Json(note): Json<serde_json::Value>,
+    ) -> Result<(axum::http::StatusCode, Json<serde_json::Value>)> {
+        let Some(name) = note["name"].as_str() else {
+            return Err(Error::BadRequest("name is required".to_string()));
+        };
+        let note = test_db::active_model(name);
+        let note = match tx {
+            Some(Extension(tx)) => note.insert(tx.as_ref()).await?,
+            None => note.insert(&db).await?,
+        };
+        Ok((
+            axum::http::StatusCode::CREATED,
+            Json(json!({ "id": note.id, "name": note.name })),
+        ))
+    }
+
+    async fn setup() -> (AXRouter, DatabaseConnection) {
+        let db = seeded_connection(&[]).await;
+        let router = AXRouter::new()
+            .route("/notes", post(create))
+            .route("/ping", get(|| async { "pong" }))
+            .with_state(db.clone());
+        (router, db)
+    }
+
+    fn create_op(body: serde_json::Value) -> Operation {
+        Operation {
+            method: "post".to_string(),
+            path: "/notes".to_string(),
+            body: Some(body),
+        }
+    }
+
+    #[tokio::test]
+    async fn can_batch_creates() {
+        let (router, db) = setup().await;
+        let batch = BatchRequest {
+            operations: vec![
+                create_op(json!({ "name": "one" })),
+                create_op(json!({ "name": "two" })),
+                Operation {
+                    method: "GET".to_string(),
+                    path: "/ping".to_string(),
+                    body: None,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let responses = dispatch(
+            router,
+            &db,
+            &HeaderMap::new(),
+            &batch,
+            DEFAULT_MAX_OPERATIONS,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0].status, 201);
+        assert_eq!(responses[0].body, json!({ "id": 1, "name": "one" }));
+        assert_eq!(responses[1].status, 201);
+        assert_eq!(responses[1].body, json!({ "id": 2, "name": "two" }));
+        assert_eq!(responses[2].body, json!("pong"));
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn fail_fast_skips_remaining_operations() {
+        let (router, db) = setup().await;
+        let operations = vec![
+            create_op(json!({ "name": "one" })),
+            create_op(json!({})),
+            create_op(json!({ "name": "three" })),
+        ];
+
+        let batch = BatchRequest {
+            operations: operations.clone(),
+            ..Default::default()
+        };
+        let responses = dispatch(
+            router.clone(),
+            &db,
+            &HeaderMap::new(),
+            &batch,
+            DEFAULT_MAX_OPERATIONS,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            responses.iter().map(|r| r.status).collect::<Vec<_>>(),
+            [201, 400, 201]
+        );
+
+        let batch = BatchRequest {
+            operations,
+            fail_fast: true,
+            ..Default::default()
+        };
+        let responses = dispatch(
+            router,
+            &db,
+            &HeaderMap::new(),
+            &batch,
+            DEFAULT_MAX_OPERATIONS,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            responses.iter().map(|r| r.status).collect::<Vec<_>>(),
+            [201, 400]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_batches_over_max_operations() {
+        let (router, db) = setup().await;
+        let batch = BatchRequest {
+            operations: vec![
+                create_op(json!({ "name": "one" })),
+                create_op(json!({ "name": "two" })),
+            ],
+            ..Default::default()
+        };
+
+        let err = dispatch(router, &db, &HeaderMap::new(), &batch, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CustomError(StatusCode::PAYLOAD_TOO_LARGE, _)
+        ));
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn transaction_rolls_back_on_failure() {
+        let (router, db) = setup().await;
+
+        let batch = BatchRequest {
+            operations: vec![create_op(json!({ "name": "one" })), create_op(json!({}))],
+            transaction: true,
+            ..Default::default()
+        };
+        let responses = dispatch(
+            router.clone(),
+            &db,
+            &HeaderMap::new(),
+            &batch,
+            DEFAULT_MAX_OPERATIONS,
+        )
+        .await
+        .unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 0);
+
+        let batch = BatchRequest {
+            operations: vec![
+                create_op(json!({ "name": "one" })),
+                create_op(json!({ "name": "two" })),
+            ],
+            transaction: true,
+            ..Default::default()
+        };
+        dispatch(
+            router,
+            &db,
+            &HeaderMap::new(),
+            &batch,
+            DEFAULT_MAX_OPERATIONS,
+        )
+        .await
+        .unwrap();
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,962 @@
+//! Response body codecs.
+//!
+//! A [`Codec`] turns a serializable value into response bytes. JSON is the
+//! default; MessagePack and CBOR are built in for clients that ask for them
+//! through `Accept`, which is what
+//! [`crate::controller::format::negotiate_body`] does. Implement [`Codec`] to
+//! plug in another encoding and respond with
+//! [`crate::controller::format::encoded`].
+//!
+//! MessagePack and CBOR are encoded straight from the serde data model, so
+//! byte strings stay binary and maps keep their non-string keys. Structs are
+//! maps of their field names, and enum variants with data are maps of a
+//! single entry, as in JSON.
+
+pub use erased_serde;
+use hyper::{header::ACCEPT, HeaderMap};
+
+use crate::{Error, Result};
+
+/// Encodes response bodies of a given content type.
+pub trait Codec: Send + Sync {
+    /// The `Content-Type` of the encoded body.
+    fn content_type(&self) -> &'static str;
+
+    /// Encodes the value.
+    ///
+    /// # Errors
+    /// When the value cannot be encoded.
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>>;
+}
+
+/// `application/json`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(value)?)
+    }
+}
+
+/// `application/msgpack`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MessagePackCodec;
+
+impl Codec for MessagePackCodec {
+    fn content_type(&self) -> &'static str {
+        "application/msgpack"
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        encoder::encode::<msgpack::MessagePack, _>(value).map_err(Error::wrap)
+    }
+}
+
+/// `application/cbor`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CborCodec;
+
+impl Codec for CborCodec {
+    fn content_type(&self) -> &'static str {
+        "application/cbor"
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        encoder::encode::<cbor::Cbor, _>(value).map_err(Error::wrap)
+    }
+}
+
+/// The built-in codecs, with the media types they answer to.
+const BUILT_IN: [(&dyn Codec, &[&str]); 3] = [
+    (&JsonCodec, &["application/json"]),
+    (
+        &MessagePackCodec,
+        &[
+            "application/msgpack",
+            "application/x-msgpack",
+            "application/vnd.msgpack",
+        ],
+    ),
+    (&CborCodec, &["application/cbor"]),
+];
+
+/// Picks the built-in codec the request `Accept` header prefers, see
+/// [`preferred`], falling back to JSON.
+#[must_use]
+pub fn negotiate(headers: &HeaderMap) -> &'static dyn Codec {
+    let offered = BUILT_IN.map(|(_, media_types)| media_types);
+    preferred(headers, &offered).map_or(&JsonCodec, |index| BUILT_IN[index].0)
+}
+
+/// Picks, of the `offered` formats given by their media types, the index of
+/// the one the client prefers.
+///
+/// The quality values (`q=`) of the `Accept` header decide and, between equal
+/// ones, the most specific range then the order of `offered`. A format is
+/// rated by the most specific range matching it, so `q=0` makes it not
+/// acceptable even under a `*/*`. Returns `None` without an `Accept` header or
+/// when none of `offered` is acceptable.
+#[must_use]
+pub fn preferred(headers: &HeaderMap, offered: &[&[&str]]) -> Option<usize> {
+    let ranges = headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let media = params.next()?.trim().to_ascii_lowercase();
+            let quality = params
+                .filter_map(|param| {
+                    let (name, value) = param.split_once('=')?;
+                    name.trim().eq_ignore_ascii_case("q").then_some(value)
+                })
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!media.is_empty()).then_some((media, quality))
+        })
+        .collect::<Vec<_>>();
+
+    let mut best: Option<(usize, f32, u8)> = None;
+    for (index, media_types) in offered.iter().enumerate() {
+        let matched = ranges
+            .iter()
+            .filter_map(|(range, quality)| {
+                let specificity = media_types.iter().find_map(|media| {
+                    if range == media {
+                        Some(2)
+                    } else if range == "*/*" {
+                        Some(0)
+                    } else {
+                        range
+                            .strip_suffix("/*")
+                            .filter(|kind| media.split('/').next() == Some(kind))
+                            .map(|_| 1)
+                    }
+                })?;
+                Some((*quality, specificity))
+            })
+            .max_by_key(|(_, specificity)| *specificity);
+
+        if let Some((quality, specificity)) = matched {
+            let better = best.map_or(true, |(_, best_quality, best_specificity)| {
+                quality > best_quality
+                    || (quality == best_quality && specificity > best_specificity)
+            });
+            if quality > 0.0 && better {
+                best = Some((index, quality, specificity));
+            }
+        }
+    }
+    best.map(|(index, _, _)| index)
+}
+
+/// A serde serializer shared by the binary formats, which only differ in how
+/// they mark each type.
+mod encoder {
+    use std::{fmt, marker::PhantomData};
+
+    use serde::ser::{self, Serialize};
+
+    /// How a format writes each type of the data model.
+    pub trait Format {
+        fn null(buf: &mut Vec<u8>);
+        fn bool(buf: &mut Vec<u8>, value: bool);
+        fn uint(buf: &mut Vec<u8>, value: u64);
+        /// Writes a negative integer.
+        fn negative(buf: &mut Vec<u8>, value: i64);
+        fn f32(buf: &mut Vec<u8>, value: f32);
+        fn f64(buf: &mut Vec<u8>, value: f64);
+        fn str(buf: &mut Vec<u8>, value: &str);
+        fn bytes(buf: &mut Vec<u8>, value: &[u8]);
+        /// Writes the header of an array of `len` items.
+        fn array(buf: &mut Vec<u8>, len: usize);
+        /// Writes the header of a map of `len` entries.
+        fn map(buf: &mut Vec<u8>, len: usize);
+    }
+
+    #[derive(Debug)]
+    pub struct EncodeError(String);
+
+    impl fmt::Display for EncodeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for EncodeError {}
+
+    impl ser::Error for EncodeError {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            Self(msg.to_string())
+        }
+    }
+
+    type Result<T = ()> = std::result::Result<T, EncodeError>;
+
+    pub fn encode<F: Format, T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        value.serialize(Encoder::<F>::new(&mut buf))?;
+        Ok(buf)
+    }
+
+    pub struct Encoder<'a, F> {
+        buf: &'a mut Vec<u8>,
+        format: PhantomData<F>,
+    }
+
+    impl<'a, F: Format> Encoder<'a, F> {
+        fn new(buf: &'a mut Vec<u8>) -> Self {
+            Self {
+                buf,
+                format: PhantomData,
+            }
+        }
+
+        /// Starts the single entry map of a variant with data.
+        fn variant(&mut self, variant: &str) {
+            F::map(self.buf, 1);
+            F::str(self.buf, variant);
+        }
+
+        fn compound(self, map: bool) -> Compound<'a, F> {
+            Compound {
+                buf: self.buf,
+                items: Vec::new(),
+                len: 0,
+                map,
+                format: PhantomData,
+            }
+        }
+    }
+
+    /// An array or map, whose items are buffered until their count, which
+    /// serde may not know up front, is written.
+    pub struct Compound<'a, F> {
+        buf: &'a mut Vec<u8>,
+        items: Vec<u8>,
+        len: usize,
+        map: bool,
+        format: PhantomData<F>,
+    }
+
+    impl<F: Format> Compound<'_, F> {
+        fn item<T: Serialize + ?Sized>(&mut self, value: &T) -> Result {
+            value.serialize(Encoder::<F>::new(&mut self.items))
+        }
+
+        fn finish(self) -> Result {
+            if self.map {
+                F::map(self.buf, self.len);
+            } else {
+                F::array(self.buf, self.len);
+            }
+            self.buf.extend_from_slice(&self.items);
+            Ok(())
+        }
+    }
+
+    impl<'a, F: Format> ser::Serializer for Encoder<'a, F> {
+        type Ok = ();
+        type Error = EncodeError;
+        type SerializeSeq = Compound<'a, F>;
+        type SerializeTuple = Compound<'a, F>;
+        type SerializeTupleStruct = Compound<'a, F>;
+        type SerializeTupleVariant = Compound<'a, F>;
+        type SerializeMap = Compound<'a, F>;
+        type SerializeStruct = Compound<'a, F>;
+        type SerializeStructVariant = Compound<'a, F>;
+
+        fn is_human_readable(&self) -> bool {
+            false
+        }
+
+        fn serialize_bool(self, v: bool) -> Result {
+            F::bool(self.buf, v);
+            Ok(())
+        }
+
+        fn serialize_i8(self, v: i8) -> Result {
+            self.serialize_i64(v.into())
+        }
+
+        fn serialize_i16(self, v: i16) -> Result {
+            self.serialize_i64(v.into())
+        }
+
+        fn serialize_i32(self, v: i32) -> Result {
+            self.serialize_i64(v.into())
+        }
+
+        fn serialize_i64(self, v: i64) -> Result {
+            if v < 0 {
+                F::negative(self.buf, v);
+            } else {
+                F::uint(self.buf, v.unsigned_abs());
+            }
+            Ok(())
+        }
+
+        fn serialize_i128(self, v: i128) -> Result {
+            if let Ok(v) = u64::try_from(v) {
+                return self.serialize_u64(v);
+            }
+            let v = i64::try_from(v).map_err(|_| EncodeError(format!("{v} is out of range")))?;
+            self.serialize_i64(v)
+        }
+
+        fn serialize_u8(self, v: u8) -> Result {
+            self.serialize_u64(v.into())
+        }
+
+        fn serialize_u16(self, v: u16) -> Result {
+            self.serialize_u64(v.into())
+        }
+
+        fn serialize_u32(self, v: u32) -> Result {
+            self.serialize_u64(v.into())
+        }
+
+        fn serialize_u64(self, v: u64) -> Result {
+            F::uint(self.buf, v);
+            Ok(())
+        }
+
+        fn serialize_u128(self, v: u128) -> Result {
+            let v = u64::try_from(v).map_err(|_| EncodeError(format!("{v} is out of range")))?;
+            self.serialize_u64(v)
+        }
+
+        fn serialize_f32(self, v: f32) -> Result {
+            F::f32(self.buf, v);
+            Ok(())
+        }
+
+        fn serialize_f64(self, v: f64) -> Result {
+            F::f64(self.buf, v);
+            Ok(())
+        }
+
+        fn serialize_char(self, v: char) -> Result {
+            self.serialize_str(v.encode_utf8(&mut [0; 4]))
+        }
+
+        fn serialize_str(self, v: &str) -> Result {
+            F::str(self.buf, v);
+            Ok(())
+        }
+
+        fn serialize_bytes(self, v: &[u8]) -> Result {
+            F::bytes(self.buf, v);
+            Ok(())
+        }
+
+        fn serialize_none(self) -> Result {
+            self.serialize_unit()
+        }
+
+        fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result {
+            value.serialize(self)
+        }
+
+        fn serialize_unit(self) -> Result {
+            F::null(self.buf);
+            Ok(())
+        }
+
+        fn serialize_unit_struct(self, _name: &'static str) -> Result {
+            self.serialize_unit()
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _name: &'static str,
+            _index: u32,
+            variant: &'static str,
+        ) -> Result {
+            self.serialize_str(variant)
+        }
+
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(
+            self,
+            _name: &'static str,
+            value: &T,
+        ) -> Result {
+            value.serialize(self)
+        }
+
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            mut self,
+            _name: &'static str,
+            _index: u32,
+            variant: &'static str,
+            value: &T,
+        ) -> Result {
+            self.variant(variant);
+            value.serialize(self)
+        }
+
+        fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+            Ok(self.compound(false))
+        }
+
+        fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+            Ok(self.compound(false))
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleStruct> {
+            Ok(self.compound(false))
+        }
+
+        fn serialize_tuple_variant(
+            mut self,
+            _name: &'static str,
+            _index: u32,
+            variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleVariant> {
+            self.variant(variant);
+            Ok(self.compound(false))
+        }
+
+        fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+            Ok(self.compound(true))
+        }
+
+        fn serialize_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStruct> {
+            Ok(self.compound(true))
+        }
+
+        fn serialize_struct_variant(
+            mut self,
+            _name: &'static str,
+            _index: u32,
+            variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStructVariant> {
+            self.variant(variant);
+            Ok(self.compound(true))
+        }
+    }
+
+    impl<F: Format> ser::SerializeSeq for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result {
+            self.len += 1;
+            self.item(value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+
+    impl<F: Format> ser::SerializeTuple for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result {
+            ser::SerializeSeq::serialize_element(self, value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+
+    impl<F: Format> ser::SerializeTupleStruct for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result {
+            ser::SerializeSeq::serialize_element(self, value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+
+    impl<F: Format> ser::SerializeTupleVariant for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result {
+            ser::SerializeSeq::serialize_element(self, value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+
+    impl<F: Format> ser::SerializeMap for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result {
+            self.len += 1;
+            self.item(key)
+        }
+
+        fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result {
+            self.item(value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+
+    impl<F: Format> ser::SerializeStruct for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_field<T: Serialize + ?Sized>(
+            &mut self,
+            key: &'static str,
+            value: &T,
+        ) -> Result {
+            self.len += 1;
+            self.item(key)?;
+            self.item(value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+
+    impl<F: Format> ser::SerializeStructVariant for Compound<'_, F> {
+        type Ok = ();
+        type Error = EncodeError;
+
+        fn serialize_field<T: Serialize + ?Sized>(
+            &mut self,
+            key: &'static str,
+            value: &T,
+        ) -> Result {
+            ser::SerializeStruct::serialize_field(self, key, value)
+        }
+
+        fn end(self) -> Result {
+            self.finish()
+        }
+    }
+}
+
+/// <https://github.com/msgpack/msgpack/blob/master/spec.md>
+#[allow(clippy::cast_possible_truncation)]
+mod msgpack {
+    use super::encoder::Format;
+
+    pub struct MessagePack;
+
+    impl Format for MessagePack {
+        fn null(buf: &mut Vec<u8>) {
+            buf.push(0xc0);
+        }
+
+        fn bool(buf: &mut Vec<u8>, value: bool) {
+            buf.push(if value { 0xc3 } else { 0xc2 });
+        }
+
+        fn uint(buf: &mut Vec<u8>, n: u64) {
+            if n < 0x80 {
+                buf.push(n as u8);
+            } else if n <= u64::from(u8::MAX) {
+                buf.extend_from_slice(&[0xcc, n as u8]);
+            } else if n <= u64::from(u16::MAX) {
+                buf.push(0xcd);
+                buf.extend_from_slice(&(n as u16).to_be_bytes());
+            } else if n <= u64::from(u32::MAX) {
+                buf.push(0xce);
+                buf.extend_from_slice(&(n as u32).to_be_bytes());
+            } else {
+                buf.push(0xcf);
+                buf.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+
+        #[allow(clippy::cast_sign_loss)]
+        fn negative(buf: &mut Vec<u8>, n: i64) {
+            if n >= -32 {
+                buf.push(n as u8);
+            } else if n >= i64::from(i8::MIN) {
+                buf.extend_from_slice(&[0xd0, n as u8]);
+            } else if n >= i64::from(i16::MIN) {
+                buf.push(0xd1);
+                buf.extend_from_slice(&(n as i16).to_be_bytes());
+            } else if n >= i64::from(i32::MIN) {
+                buf.push(0xd2);
+                buf.extend_from_slice(&(n as i32).to_be_bytes());
+            } else {
+                buf.push(0xd3);
+                buf.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+
+        fn f32(buf: &mut Vec<u8>, value: f32) {
+            buf.push(0xca);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+
+        fn f64(buf: &mut Vec<u8>, value: f64) {
+            buf.push(0xcb);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+
+        /// `fixstr` below 32 bytes, then `str 8/16/32`.
+        fn str(buf: &mut Vec<u8>, value: &str) {
+            let len = value.len();
+            if len < 32 {
+                buf.push(0xa0 | len as u8);
+            } else if len <= usize::from(u8::MAX) {
+                buf.extend_from_slice(&[0xd9, len as u8]);
+            } else {
+                write_len(buf, len, 0xda, 0xdb);
+            }
+            buf.extend_from_slice(value.as_bytes());
+        }
+
+        /// `bin 8/16/32`.
+        fn bytes(buf: &mut Vec<u8>, value: &[u8]) {
+            let len = value.len();
+            if len <= usize::from(u8::MAX) {
+                buf.extend_from_slice(&[0xc4, len as u8]);
+            } else {
+                write_len(buf, len, 0xc5, 0xc6);
+            }
+            buf.extend_from_slice(value);
+        }
+
+        fn array(buf: &mut Vec<u8>, len: usize) {
+            write_container(buf, len, 0x90, 0xdc, 0xdd);
+        }
+
+        fn map(buf: &mut Vec<u8>, len: usize) {
+            write_container(buf, len, 0x80, 0xde, 0xdf);
+        }
+    }
+
+    /// The `fix` form below 16 entries, then the 16 or 32 bit forms.
+    fn write_container(buf: &mut Vec<u8>, len: usize, fix: u8, m16: u8, m32: u8) {
+        if len < 16 {
+            buf.push(fix | len as u8);
+        } else {
+            write_len(buf, len, m16, m32);
+        }
+    }
+
+    fn write_len(buf: &mut Vec<u8>, len: usize, m16: u8, m32: u8) {
+        if len <= usize::from(u16::MAX) {
+            buf.push(m16);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            buf.push(m32);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc8949>
+mod cbor {
+    use super::encoder::Format;
+
+    const UNSIGNED: u8 = 0;
+    const NEGATIVE: u8 = 1;
+    const BYTES: u8 = 2;
+    const TEXT: u8 = 3;
+    const ARRAY: u8 = 4;
+    const MAP: u8 = 5;
+
+    pub struct Cbor;
+
+    impl Format for Cbor {
+        fn null(buf: &mut Vec<u8>) {
+            buf.push(0xf6);
+        }
+
+        fn bool(buf: &mut Vec<u8>, value: bool) {
+            buf.push(if value { 0xf5 } else { 0xf4 });
+        }
+
+        fn uint(buf: &mut Vec<u8>, value: u64) {
+            write_head(buf, UNSIGNED, value);
+        }
+
+        fn negative(buf: &mut Vec<u8>, value: i64) {
+            // -1 - n, which is non-negative for a negative n
+            write_head(buf, NEGATIVE, (-1 - value).unsigned_abs());
+        }
+
+        fn f32(buf: &mut Vec<u8>, value: f32) {
+            buf.push(0xfa);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+
+        fn f64(buf: &mut Vec<u8>, value: f64) {
+            buf.push(0xfb);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+
+        fn str(buf: &mut Vec<u8>, value: &str) {
+            write_head(buf, TEXT, value.len() as u64);
+            buf.extend_from_slice(value.as_bytes());
+        }
+
+        fn bytes(buf: &mut Vec<u8>, value: &[u8]) {
+            write_head(buf, BYTES, value.len() as u64);
+            buf.extend_from_slice(value);
+        }
+
+        fn array(buf: &mut Vec<u8>, len: usize) {
+            write_head(buf, ARRAY, len as u64);
+        }
+
+        fn map(buf: &mut Vec<u8>, len: usize) {
+            write_head(buf, MAP, len as u64);
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_head(buf: &mut Vec<u8>, major: u8, n: u64) {
+        let major = major << 5;
+        if n < 24 {
+            buf.push(major | n as u8);
+        } else if n <= u64::from(u8::MAX) {
+            buf.extend_from_slice(&[major | 24, n as u8]);
+        } else if n <= u64::from(u16::MAX) {
+            buf.push(major | 25);
+            buf.extend_from_slice(&(n as u16).to_be_bytes());
+        } else if n <= u64::from(u32::MAX) {
+            buf.push(major | 26);
+            buf.extend_from_slice(&(n as u32).to_be_bytes());
+        } else {
+            buf.push(major | 27);
+            buf.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use hyper::header::HeaderValue;
+    use serde::{Serialize, Serializer};
+    use serde_json::json;
+
+    use super::*;
+
+    struct Raw(&'static [u8]);
+
+    impl Serialize for Raw {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Square(u8),
+        Rect(u8, u8),
+        Circle { r: u8 },
+    }
+
+    fn msgpack(value: &impl Serialize) -> Vec<u8> {
+        MessagePackCodec.encode(value).unwrap()
+    }
+
+    fn cbor(value: &impl Serialize) -> Vec<u8> {
+        CborCodec.encode(value).unwrap()
+    }
+
+    // the examples of the MessagePack spec
+    #[test]
+    fn can_encode_msgpack() {
+        assert_eq!(msgpack(&()), [0xc0]);
+        assert_eq!(msgpack(&None::<u8>), [0xc0]);
+        assert_eq!(msgpack(&false), [0xc2]);
+        assert_eq!(msgpack(&true), [0xc3]);
+
+        assert_eq!(msgpack(&127), [0x7f]);
+        assert_eq!(msgpack(&128), [0xcc, 0x80]);
+        assert_eq!(msgpack(&256), [0xcd, 0x01, 0x00]);
+        assert_eq!(msgpack(&65_536), [0xce, 0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(msgpack(&u64::MAX), [[0xcf].as_slice(), &[0xff; 8]].concat());
+        assert_eq!(msgpack(&-1), [0xff]);
+        assert_eq!(msgpack(&-32), [0xe0]);
+        assert_eq!(msgpack(&-33), [0xd0, 0xdf]);
+        assert_eq!(msgpack(&-129), [0xd1, 0xff, 0x7f]);
+        assert_eq!(msgpack(&-32_769), [0xd2, 0xff, 0xff, 0x7f, 0xff]);
+        assert_eq!(
+            msgpack(&i64::MIN),
+            [0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0].as_slice()
+        );
+        assert_eq!(msgpack(&1.5f32), [0xca, 0x3f, 0xc0, 0x00, 0x00]);
+        assert_eq!(
+            msgpack(&1.5f64),
+            [0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0].as_slice()
+        );
+
+        assert_eq!(msgpack(&"a"), [0xa1, b'a']);
+        assert_eq!(
+            msgpack(&"x".repeat(32)),
+            [[0xd9, 0x20].as_slice(), "x".repeat(32).as_bytes()].concat()
+        );
+        assert_eq!(msgpack(&"x".repeat(256))[..3], [0xda, 0x01, 0x00]);
+        assert_eq!(msgpack(&Raw(&[1, 2, 3])), [0xc4, 0x03, 0x01, 0x02, 0x03]);
+
+        assert_eq!(msgpack(&[1, 2]), [0x92, 0x01, 0x02]);
+        assert_eq!(msgpack(&vec![0; 16])[..3], [0xdc, 0x00, 0x10]);
+        assert_eq!(
+            msgpack(&json!({"a": [1, -1]})),
+            [0x81, 0xa1, b'a', 0x92, 0x01, 0xff]
+        );
+        assert_eq!(
+            msgpack(&BTreeMap::from([(1, "a")])),
+            [0x81, 0x01, 0xa1, b'a']
+        );
+    }
+
+    #[test]
+    fn can_encode_msgpack_variants() {
+        assert_eq!(
+            msgpack(&Shape::Empty),
+            [[0xa5].as_slice(), b"Empty"].concat()
+        );
+        assert_eq!(
+            msgpack(&Shape::Square(2)),
+            [[0x81, 0xa6].as_slice(), b"Square", &[0x02]].concat()
+        );
+        assert_eq!(
+            msgpack(&Shape::Rect(2, 3)),
+            [[0x81, 0xa4].as_slice(), b"Rect", &[0x92, 0x02, 0x03]].concat()
+        );
+        assert_eq!(
+            msgpack(&Shape::Circle { r: 1 }),
+            [
+                [0x81, 0xa6].as_slice(),
+                b"Circle",
+                &[0x81, 0xa1, b'r', 0x01]
+            ]
+            .concat()
+        );
+    }
+
+    // the examples of RFC 8949, appendix A
+    #[test]
+    fn can_encode_cbor() {
+        assert_eq!(cbor(&0), [0x00]);
+        assert_eq!(cbor(&23), [0x17]);
+        assert_eq!(cbor(&24), [0x18, 0x18]);
+        assert_eq!(cbor(&100), [0x18, 0x64]);
+        assert_eq!(cbor(&1000), [0x19, 0x03, 0xe8]);
+        assert_eq!(cbor(&1_000_000), [0x1a, 0x00, 0x0f, 0x42, 0x40]);
+        assert_eq!(cbor(&u64::MAX), [[0x1b].as_slice(), &[0xff; 8]].concat());
+        assert_eq!(cbor(&-1), [0x20]);
+        assert_eq!(cbor(&-10), [0x29]);
+        assert_eq!(cbor(&-100), [0x38, 0x63]);
+        assert_eq!(cbor(&-1000), [0x39, 0x03, 0xe7]);
+        assert_eq!(
+            cbor(&i64::MIN),
+            [[0x3b, 0x7f].as_slice(), &[0xff; 7]].concat()
+        );
+        assert_eq!(
+            cbor(&1.1f64),
+            [0xfb, 0x3f, 0xf1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a]
+        );
+        assert_eq!(cbor(&100_000.0f32), [0xfa, 0x47, 0xc3, 0x50, 0x00]);
+        assert_eq!(cbor(&false), [0xf4]);
+        assert_eq!(cbor(&true), [0xf5]);
+        assert_eq!(cbor(&()), [0xf6]);
+
+        assert_eq!(cbor(&Raw(&[1, 2, 3, 4])), [0x44, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(cbor(&"a"), [0x61, 0x61]);
+        assert_eq!(cbor(&"IETF"), [0x64, 0x49, 0x45, 0x54, 0x46]);
+        assert_eq!(cbor(&'\u{fc}'), [0x62, 0xc3, 0xbc]);
+
+        assert_eq!(cbor(&[1, 2, 3]), [0x83, 0x01, 0x02, 0x03]);
+        assert_eq!(
+            cbor(&(1..=25).collect::<Vec<_>>()),
+            [
+                [0x98, 0x19].as_slice(),
+                &(1..=23).collect::<Vec<u8>>(),
+                &[0x18, 0x18, 0x18, 0x19]
+            ]
+            .concat()
+        );
+        assert_eq!(
+            cbor(&BTreeMap::from([(1, 2), (3, 4)])),
+            [0xa2, 0x01, 0x02, 0x03, 0x04]
+        );
+        assert_eq!(
+            cbor(&json!({"a": 1, "b": [2, 3]})),
+            [0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x82, 0x02, 0x03]
+        );
+    }
+
+    #[test]
+    fn can_encode_cbor_variants() {
+        assert_eq!(cbor(&Shape::Empty), [[0x65].as_slice(), b"Empty"].concat());
+        assert_eq!(
+            cbor(&Shape::Circle { r: 1 }),
+            [
+                [0xa1, 0x66].as_slice(),
+                b"Circle",
+                &[0xa1, 0x61, b'r', 0x01]
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn can_negotiate() {
+        let negotiated = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_str(accept).unwrap());
+            negotiate(&headers).content_type()
+        };
+
+        assert_eq!(
+            negotiate(&HeaderMap::new()).content_type(),
+            "application/json"
+        );
+        assert_eq!(negotiated("application/msgpack"), "application/msgpack");
+        assert_eq!(
+            negotiated("text/html, application/cbor;q=0.9"),
+            "application/cbor"
+        );
+        assert_eq!(
+            negotiated("application/json;q=0.5, application/cbor"),
+            "application/cbor"
+        );
+        assert_eq!(
+            negotiated("application/json;q=0, */*"),
+            "application/msgpack"
+        );
+        assert_eq!(
+            negotiated("application/cbor;Q=0, application/*;q=0.1"),
+            "application/json"
+        );
+        assert_eq!(negotiated("*/*"), "application/json");
+        assert_eq!(negotiated("text/html"), "application/json");
+    }
+}
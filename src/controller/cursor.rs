@@ -0,0 +1,245 @@
+//! Signed pagination cursors.
+//!
+//! A cursor hands the sort key of the last row on a page to the client, which
+//! sends it back as `?cursor=` to fetch the rows after it. The key is signed
+//! with `server.secret_key`, so clients cannot forge a cursor, and carries an
+//! expiry, so stale links stop working.
+//!
+//! The [`Cursor`] extractor verifies and decodes the cursor of a request;
+//! without one it yields `None`, the first page.
+//!
+//! ```rust
+//! use loco_rs::{
+//!     controller::cursor::{self, Cursor},
+//!     prelude::*,
+//! };
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct After {
+//!     id: i32,
+//! }
+//!
+//! async fn list(
+//!     State(ctx): State<AppContext>,
+//!     Cursor(after): Cursor<After>,
+//! ) -> Result<Response> {
+//!     let last_id = after.map_or(0, |after| after.id) + 20;
+//!     let next = cursor::encode(&After { id: last_id }, ctx.config.server.secret()?, cursor::DEFAULT_TTL)?;
+//!     format::json(serde_json::json!({ "next": next }))
+//! }
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::{request::Parts, StatusCode},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::Mac;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{app::AppContext, controller::ErrorDetail, hash::hmac_sha256, Error, Result};
+
+/// Default time a cursor stays valid.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+#[derive(thiserror::Error, Debug)]
+pub enum CursorError {
+    #[error("malformed cursor")]
+    Malformed,
+
+    #[error("cursor signature does not match")]
+    InvalidSignature,
+
+    #[error("cursor has expired")]
+    Expired,
+
+    #[error(transparent)]
+    Payload(#[from] serde_json::Error),
+}
+
+impl From<CursorError> for Error {
+    fn from(err: CursorError) -> Self {
+        Self::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("bad_request".to_string(), err.to_string()),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Payload<T> {
+    key: T,
+    exp: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Encodes and signs the sort key of a row into a cursor valid for `ttl`.
+///
+/// # Errors
+/// When the key cannot be serialized.
+pub fn encode<T: Serialize>(key: &T, secret: &str, ttl: Duration) -> Result<String> {
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&Payload {
+        key,
+        exp: now().saturating_add(ttl.as_secs()),
+    })?);
+
+    let mut mac = hmac_sha256(secret.as_bytes());
+    mac.update(payload.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+    Ok(format!("{payload}.{signature}"))
+}
+
+/// Verifies a cursor and decodes its sort key.
+///
+/// # Errors
+/// When the cursor is malformed, its signature does not match, it has
+/// expired, or its key cannot be parsed into `T`.
+pub fn decode<T: DeserializeOwned>(cursor: &str, secret: &str) -> Result<T, CursorError> {
+    let (payload, signature) = cursor.split_once('.').ok_or(CursorError::Malformed)?;
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| CursorError::Malformed)?;
+
+    let mut mac = hmac_sha256(secret.as_bytes());
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&signature)
+        .map_err(|_| CursorError::InvalidSignature)?;
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| CursorError::Malformed)?;
+    let payload: Payload<T> = serde_json::from_slice(&payload)?;
+    if payload.exp < now() {
+        return Err(CursorError::Expired);
+    }
+    Ok(payload.key)
+}
+
+/// The decoded `?cursor=` of a request, `None` for the first page.
+///
+/// Rejects tampered, expired or malformed cursors with `400 Bad Request`.
+#[derive(Debug, Clone)]
+pub struct Cursor<T>(pub Option<T>);
+
+#[derive(Deserialize)]
+struct CursorParams {
+    cursor: Option<String>,
+}
+
+#[async_trait]
+impl<T> FromRequestParts<AppContext> for Cursor<T>
+where
+    T: DeserializeOwned + Send,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        ctx: &AppContext,
+    ) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<CursorParams>::try_from_uri(&parts.uri)
+            .map_err(|err| Error::BadRequest(err.to_string()))?;
+        match params.cursor.filter(|cursor| !cursor.is_empty()) {
+            Some(cursor) => Ok(Self(Some(decode(&cursor, ctx.config.server.secret()?)?))),
+            None => Ok(Self(None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+    use crate::tests_cfg;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct After {
+        id: i32,
+        title: String,
+    }
+
+    fn after() -> After {
+        After {
+            id: 42,
+            title: "loco".to_string(),
+        }
+    }
+
+    async fn extract(uri: &str) -> Result<Cursor<After>> {
+        let mut ctx = tests_cfg::app::get_app_context().await;
+        ctx.config.server.secret_key = Some("secret".to_string());
+        let (mut parts, ()) = Request::builder().uri(uri).body(()).unwrap().into_parts();
+        Cursor::from_request_parts(&mut parts, &ctx).await
+    }
+
+    #[tokio::test]
+    async fn can_extract_valid_cursor() {
+        let cursor = encode(&after(), "secret", DEFAULT_TTL).unwrap();
+
+        let Cursor(decoded) = extract(&format!("/notes?cursor={cursor}")).await.unwrap();
+        assert_eq!(decoded, Some(after()));
+    }
+
+    #[tokio::test]
+    async fn missing_cursor_is_first_page() {
+        let Cursor(decoded) = extract("/notes").await.unwrap();
+        assert_eq!(decoded, None);
+
+        let Cursor(decoded) = extract("/notes?cursor=").await.unwrap();
+        assert_eq!(decoded, None);
+    }
+
+    #[tokio::test]
+    async fn rejects_tampered_cursor() {
+        let cursor = encode(&after(), "secret", DEFAULT_TTL).unwrap();
+        let (_, signature) = cursor.split_once('.').unwrap();
+        let forged = format!(
+            "{}.{signature}",
+            URL_SAFE_NO_PAD.encode(br#"{"key":{"id":1,"title":"loco"},"exp":99999999999}"#)
+        );
+
+        assert!(matches!(
+            decode::<After>(&forged, "secret"),
+            Err(CursorError::InvalidSignature)
+        ));
+        assert!(matches!(
+            decode::<After>(&cursor, "other"),
+            Err(CursorError::InvalidSignature)
+        ));
+        assert!(matches!(
+            decode::<After>("garbage", "secret"),
+            Err(CursorError::Malformed)
+        ));
+        assert!(matches!(
+            extract(&format!("/notes?cursor={forged}")).await,
+            Err(Error::CustomError(StatusCode::BAD_REQUEST, _))
+        ));
+    }
+
+    #[test]
+    fn rejects_expired_cursor() {
+        let mut mac = hmac_sha256(b"secret");
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"key":{"id":1,"title":"loco"},"exp":1}"#);
+        mac.update(payload.as_bytes());
+        let cursor = format!(
+            "{payload}.{}",
+            URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+        );
+
+        assert!(matches!(
+            decode::<After>(&cursor, "secret"),
+            Err(CursorError::Expired)
+        ));
+    }
+}
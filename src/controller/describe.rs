@@ -8,7 +8,8 @@ lazy_static! {
     static ref DESCRIBE_METHOD_ACTION: Regex = Regex::new(r"\b(\w+):\s*BoxedHandler\b").unwrap();
 }
 
-/// Extract the allow list method actions from [`MethodRouter`].
+/// Extract the allow list method actions from [`MethodRouter`], in the order
+/// axum lists them.
 ///
 /// Currently axum not exposed the action type of the router. for hold extra
 /// information about routers we need to convert the `method` to string and
@@ -17,9 +18,9 @@ pub fn method_action(method: &MethodRouter<AppContext>) -> Vec<http::Method> {
     let method_str = format!("{method:?}");
 
     DESCRIBE_METHOD_ACTION
-        .captures(&method_str)
-        .and_then(|captures| captures.get(1).map(|m| m.as_str().to_lowercase()))
-        .and_then(|method_name| match method_name.as_str() {
+        .captures_iter(&method_str)
+        .filter_map(|captures| captures.get(1).map(|m| m.as_str().to_lowercase()))
+        .filter_map(|method_name| match method_name.as_str() {
             "get" => Some(http::Method::GET),
             "post" => Some(http::Method::POST),
             "put" => Some(http::Method::PUT),
@@ -34,6 +35,5 @@ pub fn method_action(method: &MethodRouter<AppContext>) -> Vec<http::Method> {
                 None
             }
         })
-        .into_iter()
         .collect::<Vec<_>>()
 }
@@ -21,23 +21,35 @@
 //! }
 //! ```
 
+use std::{io::SeekFrom, path::Path, time::Duration};
+
 use axum::{
     body::Body,
-    http::{response::Builder, HeaderName, HeaderValue},
-    response::{Html, IntoResponse, Redirect, Response},
+    http::{response::Builder, HeaderMap, HeaderName, HeaderValue},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Redirect, Response,
+    },
 };
 use axum_extra::extract::cookie::Cookie;
-use bytes::{BufMut, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
+use chrono::{DateTime, TimeZone, Utc};
+use futures_util::{Stream, StreamExt};
 use hyper::{header, StatusCode};
 use serde::Serialize;
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
 
 use crate::{
     controller::{
+        codec::{self, Codec},
+        signed_cookie,
         views::{self, ViewRenderer},
         Json,
     },
-    Result,
+    Error, Result,
 };
 
 /// Returns an empty response.
@@ -120,6 +132,59 @@ pub fn empty_json() -> Result<Response> {
     json(json!({}))
 }
 
+/// Returns a response encoded with the codec the request `Accept` header asks
+/// for: MessagePack (`application/msgpack`), CBOR (`application/cbor`), or
+/// JSON when neither is accepted, with a `Vary: Accept` header for caches.
+///
+/// # Example:
+///
+/// ```rust
+/// use axum::http::HeaderMap;
+/// use loco_rs::prelude::*;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// pub struct Health {
+///     pub ok: bool,
+/// }
+///
+/// async fn endpoint(headers: HeaderMap) -> Result<Response> {
+///    format::negotiate_body(&headers, Health { ok: true })
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if serde fails
+pub fn negotiate_body<T: Serialize>(headers: &HeaderMap, item: T) -> Result<Response> {
+    vary_accept(encoded(codec::negotiate(headers), item))
+}
+
+/// Adds `Vary: Accept` to a response picked from the `Accept` header, so
+/// caches keep one per format.
+fn vary_accept(response: Result<Response>) -> Result<Response> {
+    let mut response = response?;
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    Ok(response)
+}
+
+/// Returns a response encoded with the given [`Codec`].
+///
+/// # Errors
+///
+/// This function will return an error if serde or the codec fails
+pub fn encoded<T: Serialize>(codec: &dyn Codec, item: T) -> Result<Response> {
+    let body = codec.encode(&item)?;
+    Ok(Response::builder()
+        .header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(codec.content_type()),
+        )
+        .body(Body::from(body))?)
+}
+
 /// Returns an HTML response
 ///
 /// # Example:
@@ -160,6 +225,536 @@ pub fn redirect(to: &str) -> Result<Response> {
     Ok(Redirect::to(to).into_response())
 }
 
+/// A server-sent event, see [`sse`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    pub data: String,
+    /// The event type, `message` for clients when unset.
+    pub event: Option<String>,
+    pub id: Option<String>,
+}
+
+impl SseEvent {
+    /// An event carrying `data`, written as one `data:` line per line.
+    #[must_use]
+    pub fn data(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Self::default()
+        }
+    }
+
+    /// An event carrying `data` serialized to JSON.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if serde fails
+    pub fn json<T: Serialize>(data: &T) -> Result<Self> {
+        Ok(Self::data(serde_json::to_string(data)?))
+    }
+
+    #[must_use]
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    #[must_use]
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+}
+
+impl From<SseEvent> for Event {
+    fn from(sse_event: SseEvent) -> Self {
+        let mut event = Self::default();
+        if let Some(name) = sse_event.event {
+            event = event.event(name);
+        }
+        if let Some(id) = sse_event.id {
+            event = event.id(id);
+        }
+        event.data(sse_event.data)
+    }
+}
+
+/// Returns a `text/event-stream` response streaming the events, with
+/// keep-alive comments sent while no event is ready.
+///
+/// # Example:
+///
+/// ```rust
+/// use loco_rs::prelude::*;
+/// use futures_util::stream;
+///
+/// async fn notifications() -> Result<Response> {
+///     let events = stream::iter([
+///         Ok(format::SseEvent::data("welcome")),
+///         format::SseEvent::json(&serde_json::json!({ "unread": 3 })).map(|e| e.event("count")),
+///     ]);
+///     format::sse(events)
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Currently this function did't return any error. this is for feature
+/// functionality
+pub fn sse<S>(stream: S) -> Result<Response>
+where
+    S: Stream<Item = Result<SseEvent>> + Send + 'static,
+{
+    Ok(Sse::new(stream.map(|event| event.map(Event::from)))
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// Options of a [`csv_with`] response.
+#[derive(Debug, Clone, Default)]
+pub struct CsvOptions {
+    /// Serve the CSV as an attachment download with this file name.
+    pub filename: Option<String>,
+    /// Start with a UTF-8 byte order mark, so Excel detects the encoding.
+    pub bom: bool,
+}
+
+/// Writes a CSV field as RFC 4180 does, the same as the `Necessary` quote
+/// style of the `csv` crate: quoted, with its quotes doubled, when it holds a
+/// comma, a quote, a `\r` or a `\n`. Other characters, whitespace included,
+/// are written as they are.
+fn csv_field(buf: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        buf.push('"');
+        buf.push_str(&field.replace('"', "\"\""));
+        buf.push('"');
+    } else {
+        buf.push_str(field);
+    }
+}
+
+/// Writes a CSV record ending with `\r\n`. A record of a single empty field
+/// is written as `""`, so it is not read back as an empty line.
+fn csv_record<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    let mut record = String::new();
+    let mut count = 0;
+    for field in fields {
+        if count > 0 {
+            record.push(',');
+        }
+        csv_field(&mut record, field);
+        count += 1;
+    }
+    if count == 1 && record.is_empty() {
+        record.push_str("\"\"");
+    }
+    record.push_str("\r\n");
+    record
+}
+
+/// Serializes an item into the CSV record of the `headers` fields: strings as
+/// they are, numbers and booleans as text, `null` or missing fields as empty.
+/// A header with dots, such as `author.name`, is the path of a nested field,
+/// and the nested objects and arrays left are written as JSON.
+fn csv_row<T: Serialize>(headers: &[String], item: &T) -> Result<String> {
+    let value = serde_json::to_value(item)?;
+    let fields = headers
+        .iter()
+        .map(|header| {
+            match value.get(header).or_else(|| {
+                header
+                    .split('.')
+                    .try_fold(&value, |value, key| value.get(key))
+            }) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(field)) => field.clone(),
+                Some(field) => field.to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+    Ok(csv_record(fields.iter().map(String::as_str)))
+}
+
+/// Returns a `text/csv` response streaming a header row, then a row per
+/// item, without buffering the rows in memory. Each item is serialized and
+/// its `headers` fields are written out in order, a dotted header such as
+/// `author.name` reading a nested field.
+///
+/// # Example:
+///
+/// ```rust
+/// use loco_rs::prelude::*;
+/// use futures_util::stream;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Note {
+///     id: i32,
+///     title: String,
+/// }
+///
+/// async fn export() -> Result<Response> {
+///     let notes = stream::iter([Note { id: 1, title: "loco".to_string() }]);
+///     format::csv(&["id", "title"], notes)
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Currently this function did't return any error. this is for feature
+/// functionality
+pub fn csv<T, S>(headers: &[&str], stream: S) -> Result<Response>
+where
+    T: Serialize,
+    S: Stream<Item = T> + Send + 'static,
+{
+    csv_with(&CsvOptions::default(), headers, stream)
+}
+
+/// Like [`csv`], with a download file name or a byte order mark.
+///
+/// # Errors
+///
+/// This function will return an error if the file name is not a valid header
+/// value
+pub fn csv_with<T, S>(options: &CsvOptions, headers: &[&str], stream: S) -> Result<Response>
+where
+    T: Serialize,
+    S: Stream<Item = T> + Send + 'static,
+{
+    let headers = headers.iter().map(ToString::to_string).collect::<Vec<_>>();
+    let mut header_row = if options.bom {
+        "\u{feff}".to_string()
+    } else {
+        String::new()
+    };
+    header_row.push_str(&csv_record(headers.iter().map(String::as_str)));
+
+    let rows = stream.map(move |item| csv_row(&headers, &item));
+    let body = Body::from_stream(futures_util::stream::once(async { Ok(header_row) }).chain(rows));
+
+    let mut response = Builder::new().header(header::CONTENT_TYPE, "text/csv; charset=utf-8");
+    if let Some(filename) = &options.filename {
+        response = response.header(header::CONTENT_DISPOSITION, attachment(filename)?);
+    }
+    Ok(response.body(body)?)
+}
+
+/// A `Content-Disposition` value downloading the response as `filename`.
+fn attachment(filename: &str) -> Result<HeaderValue> {
+    Ok(HeaderValue::from_str(&format!(
+        "attachment; filename=\"{}\"",
+        filename.replace('"', "")
+    ))?)
+}
+
+/// Parses a single `bytes` range of the `Range` header into the inclusive
+/// `(start, end)` offsets of a `size` bytes body. Returns `None` without a
+/// usable header, and `Some(Err(()))` when the range is unsatisfiable.
+fn byte_range(headers: &HeaderMap, size: u64) -> Option<std::result::Result<(u64, u64), ()>> {
+    let range = headers
+        .get(header::RANGE)?
+        .to_str()
+        .ok()?
+        .trim()
+        .strip_prefix("bytes=")?;
+    if range.contains(',') {
+        // multiple ranges are not supported, serve the whole body instead
+        return None;
+    }
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            if suffix == 0 {
+                return Some(Err(()));
+            }
+            (size.saturating_sub(suffix), size.saturating_sub(1))
+        }
+        (start, "") => (start.parse().ok()?, size.saturating_sub(1)),
+        (start, end) => {
+            let end = end.parse::<u64>().ok()?;
+            (start.parse().ok()?, end.min(size.saturating_sub(1)))
+        }
+    };
+    if start >= size || start > end {
+        return Some(Err(()));
+    }
+    Some(Ok((start, end)))
+}
+
+/// Returns a file from disk as an attachment download, streamed rather than
+/// loaded in memory. The `Content-Type` is guessed from the file extension.
+///
+/// A single range `Range` header in `headers` is answered with a
+/// `206 Partial Content` holding just that range, and an unsatisfiable one
+/// with `416 Range Not Satisfiable`.
+///
+/// # Example:
+///
+/// ```rust
+/// use axum::http::HeaderMap;
+/// use loco_rs::prelude::*;
+///
+/// async fn download(headers: HeaderMap) -> Result<Response> {
+///     format::file(&headers, "uploads/report.pdf").await
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be opened or read
+pub async fn file(headers: &HeaderMap, path: impl AsRef<Path>) -> Result<Response> {
+    let path = path.as_ref();
+    let mut file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len();
+
+    let mut response = Builder::new()
+        .header(
+            header::CONTENT_TYPE,
+            mime_guess::from_path(path).first_or_octet_stream().as_ref(),
+        )
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Some(filename) = path.file_name() {
+        response = response.header(
+            header::CONTENT_DISPOSITION,
+            attachment(&filename.to_string_lossy())?,
+        );
+    }
+
+    match byte_range(headers, size) {
+        None => Ok(response
+            .header(header::CONTENT_LENGTH, size)
+            .body(Body::from_stream(ReaderStream::new(file)))?),
+        Some(Ok((start, end))) => {
+            file.seek(SeekFrom::Start(start)).await?;
+            let len = end - start + 1;
+            Ok(response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{size}"))
+                .header(header::CONTENT_LENGTH, len)
+                .body(Body::from_stream(ReaderStream::new(file.take(len))))?)
+        }
+        Some(Err(())) => Ok(response
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{size}"))
+            .body(Body::empty())?),
+    }
+}
+
+/// Returns a response streaming `reader`, such as a file or an object store
+/// download, with the given `Content-Type`.
+///
+/// # Errors
+///
+/// This function will return an error if the content type is not a valid
+/// header value
+pub fn stream<R>(reader: R, content_type: &str) -> Result<Response>
+where
+    R: AsyncRead + Send + 'static,
+{
+    Ok(Builder::new()
+        .header(header::CONTENT_TYPE, HeaderValue::from_str(content_type)?)
+        .body(Body::from_stream(ReaderStream::new(reader)))?)
+}
+
+/// Returns an `application/xml` response, with the root element named after
+/// the type of `data`. Requires the `xml` feature.
+///
+/// # Example:
+///
+/// ```rust
+/// use loco_rs::prelude::*;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Note {
+///     id: i32,
+///     title: String,
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let response = format::xml(Note { id: 1, title: "loco".to_string() }).unwrap();
+/// assert_eq!(response.headers()["content-type"], "application/xml");
+///
+/// let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+/// assert_eq!(body, "<Note><id>1</id><title>loco</title></Note>");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if `data` cannot be serialized to XML,
+/// for example a sequence, which needs [`xml_list`].
+#[cfg(feature = "xml")]
+pub fn xml<T: Serialize>(data: T) -> Result<Response> {
+    xml_response(quick_xml::se::to_string(&data))
+}
+
+/// Like [`xml`], with the root element named `root`.
+///
+/// # Errors
+///
+/// This function will return an error if `data` cannot be serialized to XML
+#[cfg(feature = "xml")]
+pub fn xml_named<T: Serialize>(root: &str, data: T) -> Result<Response> {
+    xml_response(quick_xml::se::to_string_with_root(root, &data))
+}
+
+/// Returns an `application/xml` response listing the items, each in an
+/// `item` element, wrapped in a `root` element such as
+/// `<notes><note>..</note><note>..</note></notes>`.
+///
+/// # Example:
+///
+/// ```rust
+/// use loco_rs::prelude::*;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Note {
+///     id: i32,
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let response = format::xml_list("notes", "note", &[Note { id: 1 }, Note { id: 2 }]).unwrap();
+///
+/// let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+/// assert_eq!(body, "<notes><note><id>1</id></note><note><id>2</id></note></notes>");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if the items cannot be serialized to
+/// XML
+#[cfg(feature = "xml")]
+pub fn xml_list<T: Serialize>(root: &str, item: &str, items: &[T]) -> Result<Response> {
+    let wrapper = std::collections::BTreeMap::from([(item, items)]);
+    xml_response(quick_xml::se::to_string_with_root(root, &wrapper))
+}
+
+#[cfg(feature = "xml")]
+fn xml_response(xml: std::result::Result<String, quick_xml::DeError>) -> Result<Response> {
+    Ok(Builder::new()
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(xml.map_err(crate::Error::wrap)?))?)
+}
+
+/// A response format picked from the request `Accept` header, see
+/// [`negotiated`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Negotiated {
+    Json,
+    Xml,
+    Html,
+}
+
+impl Negotiated {
+    /// The media types of the format.
+    #[must_use]
+    pub const fn media_types(self) -> &'static [&'static str] {
+        match self {
+            Self::Json => &["application/json"],
+            Self::Xml => &["application/xml", "text/xml"],
+            Self::Html => &["text/html", "application/xhtml+xml"],
+        }
+    }
+
+    /// Picks the format of `offered` the client prefers, as
+    /// [`codec::preferred`] does. Returns `default` without an `Accept`
+    /// header or when none of `offered` is acceptable.
+    #[must_use]
+    pub fn preferred(headers: &HeaderMap, offered: &[Self], default: Self) -> Self {
+        let media_types = offered
+            .iter()
+            .map(|format| format.media_types())
+            .collect::<Vec<_>>();
+        codec::preferred(headers, &media_types).map_or(default, |index| offered[index])
+    }
+}
+
+/// Returns `data` as JSON or, with the `xml` feature, XML, whichever the
+/// request `Accept` header prefers, and JSON when it accepts neither. The
+/// response has a `Vary: Accept` header for caches.
+///
+/// # Example:
+///
+/// ```rust
+/// use axum::http::HeaderMap;
+/// use loco_rs::prelude::*;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// pub struct Health {
+///     pub ok: bool,
+/// }
+///
+/// async fn endpoint(headers: HeaderMap) -> Result<Response> {
+///    format::negotiated(&headers, Health { ok: true })
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if serde fails
+pub fn negotiated<T: Serialize>(headers: &HeaderMap, data: T) -> Result<Response> {
+    negotiated_or(headers, Negotiated::Json, data)
+}
+
+/// Like [`negotiated`], falling back to `default` when the request accepts
+/// neither format.
+///
+/// # Errors
+///
+/// This function will return an error if serde fails
+pub fn negotiated_or<T: Serialize>(
+    headers: &HeaderMap,
+    default: Negotiated,
+    data: T,
+) -> Result<Response> {
+    let offered: &[Negotiated] = if cfg!(feature = "xml") {
+        &[Negotiated::Json, Negotiated::Xml]
+    } else {
+        &[Negotiated::Json]
+    };
+    vary_accept(match Negotiated::preferred(headers, offered, default) {
+        #[cfg(feature = "xml")]
+        Negotiated::Xml => xml(data),
+        _ => json(data),
+    })
+}
+
+/// Like [`negotiated_or`], also offering HTML, rendered from the template
+/// located by `key`.
+///
+/// # Errors
+///
+/// This function will return an error if serde or rendering fails
+pub fn negotiated_view<V, T>(
+    headers: &HeaderMap,
+    default: Negotiated,
+    v: &V,
+    key: &str,
+    data: T,
+) -> Result<Response>
+where
+    V: ViewRenderer,
+    T: Serialize,
+{
+    let offered: &[Negotiated] = if cfg!(feature = "xml") {
+        &[Negotiated::Json, Negotiated::Xml, Negotiated::Html]
+    } else {
+        &[Negotiated::Json, Negotiated::Html]
+    };
+    match Negotiated::preferred(headers, offered, default) {
+        Negotiated::Html => vary_accept(view(v, key, data)),
+        _ => negotiated_or(headers, default, data),
+    }
+}
+
 /// Render template located by `key`
 ///
 /// # Errors
@@ -174,6 +769,35 @@ where
     html(&res)
 }
 
+/// Render the `fragment` of the template located by `key`, see
+/// [`ViewRenderer::render_fragment`]
+///
+/// # Errors
+///
+/// This function will return an error if rendering fails
+pub fn view_fragment<V, S>(v: &V, key: &str, fragment: &str, data: S) -> Result<Response>
+where
+    V: ViewRenderer,
+    S: Serialize,
+{
+    let res = v.render_fragment(key, fragment, data)?;
+    html(&res)
+}
+
+/// Render template located by `key` with [`ViewRenderer::render_async`]
+///
+/// # Errors
+///
+/// This function will return an error if rendering fails
+pub async fn view_async<V, S>(v: &V, key: &str, data: S) -> Result<Response>
+where
+    V: ViewRenderer + Sync,
+    S: Serialize + Send,
+{
+    let res = v.render_async(key, data).await?;
+    html(&res)
+}
+
 /// Render template from string
 ///
 /// # Errors
@@ -186,8 +810,56 @@ where
     html(&views::template(template, data)?)
 }
 
+/// A `Cache-Control` header value, see [`RenderBuilder::cache_control`].
+///
+/// ```rust
+/// use std::time::Duration;
+/// use loco_rs::controller::format::CacheControl;
+///
+/// let directive = CacheControl {
+///     max_age: Some(Duration::from_secs(60)),
+///     private: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(directive.to_string(), "private, max-age=60");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// How long the response stays fresh, whole seconds.
+    pub max_age: Option<Duration>,
+    pub no_store: bool,
+    /// Only the client may cache the response, not shared caches.
+    pub private: bool,
+    pub must_revalidate: bool,
+}
+
+impl std::fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut directives = Vec::new();
+        if self.no_store {
+            directives.push("no-store".to_string());
+        }
+        if self.private {
+            directives.push("private".to_string());
+        }
+        if let Some(max_age) = self.max_age {
+            directives.push(format!("max-age={}", max_age.as_secs()));
+        }
+        if self.must_revalidate {
+            directives.push("must-revalidate".to_string());
+        }
+        f.write_str(&directives.join(", "))
+    }
+}
+
 pub struct RenderBuilder {
     response: Builder,
+    /// Set by [`RenderBuilder::etag_conditional`] when the client has the
+    /// current version already.
+    not_modified: bool,
+    /// Set by [`RenderBuilder::auto_etag`], with the `If-None-Match` values
+    /// of the request to check the computed etag against.
+    auto_etag: Option<Vec<HeaderValue>>,
 }
 
 impl RenderBuilder {
@@ -195,6 +867,8 @@ impl RenderBuilder {
     pub fn new() -> Self {
         Self {
             response: Builder::new().status(StatusCode::OK),
+            not_modified: false,
+            auto_etag: None,
         }
     }
 
@@ -213,6 +887,7 @@ impl RenderBuilder {
     {
         Self {
             response: self.response.status(status),
+            ..self
         }
     }
 
@@ -227,6 +902,7 @@ impl RenderBuilder {
     {
         Self {
             response: self.response.header(key, value),
+            ..self
         }
     }
 
@@ -241,9 +917,86 @@ impl RenderBuilder {
             response: self
                 .response
                 .header(header::ETAG, HeaderValue::from_str(etag)?),
+            ..self
+        })
+    }
+
+    /// Add an etag, and finalize with `304 Not Modified` and an empty body
+    /// instead when the request `If-None-Match` header matches it
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if provided etag value is illegal
+    /// (not visible ASCII)
+    pub fn etag_conditional(self, req_headers: &HeaderMap, etag: &str) -> Result<Self> {
+        let not_modified =
+            self.not_modified || if_none_match(req_headers.get_all(header::IF_NONE_MATCH), etag);
+        Ok(Self {
+            not_modified,
+            ..self.etag(etag)?
+        })
+    }
+
+    /// Add a weak etag computed from the response body, when finalizing with
+    /// a text, HTML or JSON body
+    #[must_use]
+    pub fn auto_etag(self) -> Self {
+        Self {
+            auto_etag: Some(vec![]),
+            ..self
+        }
+    }
+
+    /// Like [`RenderBuilder::auto_etag`], and finalize with `304 Not
+    /// Modified` and an empty body instead when the request `If-None-Match`
+    /// header matches the computed etag
+    #[must_use]
+    pub fn auto_etag_conditional(self, req_headers: &HeaderMap) -> Self {
+        Self {
+            auto_etag: Some(
+                req_headers
+                    .get_all(header::IF_NONE_MATCH)
+                    .iter()
+                    .cloned()
+                    .collect(),
+            ),
+            ..self
+        }
+    }
+
+    /// Add a `Cache-Control` header
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directive renders empty
+    pub fn cache_control(self, directive: CacheControl) -> Result<Self> {
+        let value = directive.to_string();
+        if value.is_empty() {
+            return Err(Error::Message(
+                "cache control needs at least one directive".to_string(),
+            ));
+        }
+        Ok(Self {
+            response: self
+                .response
+                .header(header::CACHE_CONTROL, HeaderValue::from_str(&value)?),
+            ..self
         })
     }
 
+    /// Add a `Last-Modified` header, formatted as an HTTP date
+    #[must_use]
+    pub fn last_modified<Tz: TimeZone>(self, at: &DateTime<Tz>) -> Self {
+        let value = at
+            .with_timezone(&Utc)
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        Self {
+            response: self.response.header(header::LAST_MODIFIED, value),
+            ..self
+        }
+    }
+
     /// Add a collection of cookies to the response
     ///
     /// # Errors
@@ -254,7 +1007,24 @@ impl RenderBuilder {
             let header_value = cookie.encoded().to_string().parse::<HeaderValue>()?;
             res = res.header(header::SET_COOKIE, header_value);
         }
-        Ok(Self { response: res })
+        Ok(Self {
+            response: res,
+            ..self
+        })
+    }
+
+    /// Add a collection of cookies to the response, with their values signed
+    /// with `secret` to read them back with
+    /// [`crate::controller::signed_cookie::SignedCookies`]
+    ///
+    /// # Errors
+    /// Returns error if cookie values are illegal
+    pub fn signed_cookies(self, cookies: &[Cookie<'_>], secret: &str) -> Result<Self> {
+        let signed = cookies
+            .iter()
+            .map(|cookie| signed_cookie::sign(cookie, secret))
+            .collect::<Vec<_>>();
+        self.cookies(&signed)
     }
 
     /// Finalize and return a text response
@@ -263,13 +1033,10 @@ impl RenderBuilder {
     ///
     /// This function will return an error if IO fails
     pub fn text(self, content: &str) -> Result<Response> {
-        Ok(self
-            .response
-            .header(
-                header::CONTENT_TYPE,
-                HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
-            )
-            .body(Body::from(content.to_string()))?)
+        self.finish(
+            mime::TEXT_PLAIN_UTF_8.as_ref(),
+            Bytes::from(content.to_string()),
+        )
     }
 
     /// Finalize and return an empty response
@@ -278,6 +1045,9 @@ impl RenderBuilder {
     ///
     /// This function will return an error if IO fails
     pub fn empty(self) -> Result<Response> {
+        if self.not_modified {
+            return self.finish_not_modified();
+        }
         Ok(self.response.body(Body::empty())?)
     }
 
@@ -295,6 +1065,20 @@ impl RenderBuilder {
         self.html(&content)
     }
 
+    /// Render template located by `key` with [`ViewRenderer::render_async`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if rendering fails
+    pub async fn view_async<V, S>(self, v: &V, key: &str, data: S) -> Result<Response>
+    where
+        V: ViewRenderer + Sync,
+        S: Serialize + Send,
+    {
+        let content = v.render_async(key, data).await?;
+        self.html(&content)
+    }
+
     /// Render template located by `key`
     ///
     /// # Errors
@@ -313,13 +1097,10 @@ impl RenderBuilder {
     ///
     /// This function will return an error if IO fails
     pub fn html(self, content: &str) -> Result<Response> {
-        Ok(self
-            .response
-            .header(
-                header::CONTENT_TYPE,
-                HeaderValue::from_static(mime::TEXT_HTML_UTF_8.as_ref()),
-            )
-            .body(Body::from(content.to_string()))?)
+        self.finish(
+            mime::TEXT_HTML_UTF_8.as_ref(),
+            Bytes::from(content.to_string()),
+        )
     }
 
     /// Finalize and return a JSON response
@@ -331,32 +1112,82 @@ impl RenderBuilder {
     where
         T: Serialize,
     {
+        if self.not_modified {
+            return self.finish_not_modified();
+        }
         let mut buf = BytesMut::with_capacity(128).writer();
         serde_json::to_writer(&mut buf, &item)?;
-        let body = Body::from(buf.into_inner().freeze());
+        self.finish(mime::APPLICATION_JSON.as_ref(), buf.into_inner().freeze())
+    }
+
+    /// Finalize and redirect request with `303 See Other`, keeping the
+    /// headers and cookies already set on the builder
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if IO fails
+    pub fn redirect(self, to: &str) -> Result<Response> {
+        self.redirect_with(StatusCode::SEE_OTHER, to)
+    }
+
+    /// Finalize and redirect request with `308 Permanent Redirect`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if IO fails
+    pub fn redirect_permanent(self, to: &str) -> Result<Response> {
+        self.redirect_with(StatusCode::PERMANENT_REDIRECT, to)
+    }
+
+    fn finish(mut self, content_type: &'static str, body: Bytes) -> Result<Response> {
+        if let Some(if_none_match_values) = self.auto_etag.take() {
+            let etag = body_etag(&body);
+            self.not_modified |= if_none_match(&if_none_match_values, &etag);
+            self = self.etag(&etag)?;
+        }
+        if self.not_modified {
+            return self.finish_not_modified();
+        }
         Ok(self
             .response
-            .header(
-                header::CONTENT_TYPE,
-                HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
-            )
-            .body(body)?)
+            .header(header::CONTENT_TYPE, HeaderValue::from_static(content_type))
+            .body(Body::from(body))?)
     }
 
-    /// Finalize and redirect request
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if IO fails
-    pub fn redirect(self, to: &str) -> Result<Response> {
+    fn finish_not_modified(self) -> Result<Response> {
         Ok(self
             .response
-            .status(StatusCode::SEE_OTHER)
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())?)
+    }
+
+    fn redirect_with(self, status: StatusCode, to: &str) -> Result<Response> {
+        Ok(self
+            .response
+            .status(status)
             .header(header::LOCATION, to)
             .body(Body::empty())?)
     }
 }
 
+/// Whether the `If-None-Match` header values of a request match `etag`, with
+/// the weak comparison of RFC 9110 since the body is not used for ranges.
+fn if_none_match<'a>(values: impl IntoIterator<Item = &'a HeaderValue>, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    values
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+/// A weak etag of a response body: the first 128 bits of its SHA-256, which
+/// is specified, so every server instance and version computes the same etag.
+fn body_etag(body: &[u8]) -> String {
+    format!("W/\"{}\"", hex::encode(&Sha256::digest(body)[..16]))
+}
+
 impl Default for RenderBuilder {
     fn default() -> Self {
         Self::new()
@@ -419,6 +1250,301 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn negotiated_response_format() {
+        let mut headers = HeaderMap::new();
+        let response = negotiate_body(&headers, serde_json::json!({"loco": "app"})).unwrap();
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("application/json".to_string())
+        );
+        assert_eq!(response_body_to_string(response).await, r#"{"loco":"app"}"#);
+
+        for content_type in ["application/msgpack", "application/cbor"] {
+            headers.insert(header::ACCEPT, HeaderValue::from_static(content_type));
+            let response = negotiate_body(&headers, serde_json::json!({"loco": "app"})).unwrap();
+            assert_eq!(
+                get_header_from_response(&response, "content-type"),
+                Some(content_type.to_string())
+            );
+            assert_eq!(
+                get_header_from_response(&response, "vary"),
+                Some("accept".to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn sse_response_format() {
+        let events = futures_util::stream::iter([
+            Ok(SseEvent::data("hello\nloco")),
+            Ok(SseEvent::data("{}").event("note").id("2")),
+        ]);
+        let response = sse(events).unwrap();
+
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("text/event-stream".to_string())
+        );
+        assert_eq!(
+            response_body_to_string(response).await,
+            "data: hello\ndata: loco\n\nevent: note\nid: 2\ndata: {}\n\n"
+        );
+    }
+
+    #[derive(Serialize)]
+    struct Row {
+        id: i32,
+        title: String,
+        tags: Vec<&'static str>,
+        note: Option<&'static str>,
+    }
+
+    #[tokio::test]
+    async fn csv_response_format() {
+        let rows = futures_util::stream::iter([
+            Row {
+                id: 1,
+                title: "loco".to_string(),
+                tags: vec!["rust"],
+                note: None,
+            },
+            Row {
+                id: 2,
+                title: "comma, \"quote\"\nand line".to_string(),
+                tags: vec![],
+                note: Some("ok"),
+            },
+        ]);
+        let response = csv(&["id", "title", "note", "tags"], rows).unwrap();
+
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("text/csv; charset=utf-8".to_string())
+        );
+        assert_eq!(
+            get_header_from_response(&response, "content-disposition"),
+            None
+        );
+        assert_eq!(
+            response_body_to_string(response).await,
+            "id,title,note,tags\r\n1,loco,,\"[\"\"rust\"\"]\"\r\n2,\"comma, \"\"quote\"\"\nand \
+             line\",ok,[]\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_record_quotes_when_necessary() {
+        let record = |fields: &[&str]| csv_record(fields.iter().copied());
+
+        assert_eq!(record(&["a", "b c", " d "]), "a,b c, d \r\n");
+        assert_eq!(record(&["a,b", "c"]), "\"a,b\",c\r\n");
+        assert_eq!(
+            record(&["say \"hi\"", "\""]),
+            "\"say \"\"hi\"\"\",\"\"\"\"\r\n"
+        );
+        assert_eq!(
+            record(&["a\nb", "c\rd", "e\r\nf"]),
+            "\"a\nb\",\"c\rd\",\"e\r\nf\"\r\n"
+        );
+        assert_eq!(record(&["", ""]), ",\r\n");
+        assert_eq!(record(&[""]), "\"\"\r\n");
+        assert_eq!(record(&["café", "日本"]), "café,日本\r\n");
+    }
+
+    #[test]
+    fn csv_row_reads_nested_fields() {
+        let item = json!({
+            "id": 1,
+            "author": {"name": "loco", "tags": ["a", "b"]},
+            "author.name": "flat",
+        });
+        let headers = [
+            "id",
+            "author.name",
+            "author.tags",
+            "author.missing",
+            "author",
+        ]
+        .map(ToString::to_string);
+
+        assert_eq!(
+            csv_row(&headers, &item).unwrap(),
+            concat!(
+                r#"1,flat,"[""a"",""b""]",,"{""name"":""loco"",""tags"":[""a"",""b""]}""#,
+                "\r\n"
+            )
+        );
+        assert_eq!(
+            csv_row(&headers[1..2], &json!({"author": {"name": "loco"}})).unwrap(),
+            "loco\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn csv_with_response_format() {
+        let options = CsvOptions {
+            filename: Some("notes.csv".to_string()),
+            bom: true,
+        };
+        let response = csv_with(
+            &options,
+            &["id"],
+            futures_util::stream::iter([json!({"id": 1})]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_header_from_response(&response, "content-disposition"),
+            Some("attachment; filename=\"notes.csv\"".to_string())
+        );
+        assert_eq!(
+            response_body_to_string(response).await,
+            "\u{feff}id\r\n1\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_response_format() {
+        let tree_res = tree_fs::from_yaml_str(
+            r"
+        files:
+        - path: report.txt
+          content: 0123456789
+        ",
+        )
+        .unwrap();
+        let path = tree_res.join("report.txt");
+
+        let response = file(&HeaderMap::new(), &path).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("text/plain".to_string())
+        );
+        assert_eq!(
+            get_header_from_response(&response, "content-disposition"),
+            Some("attachment; filename=\"report.txt\"".to_string())
+        );
+        assert_eq!(&response_body_to_string(response).await, "0123456789");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=2-5"));
+        let response = file(&headers, &path).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            get_header_from_response(&response, "content-range"),
+            Some("bytes 2-5/10".to_string())
+        );
+        assert_eq!(&response_body_to_string(response).await, "2345");
+
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=-3"));
+        let response = file(&headers, &path).await.unwrap();
+        assert_eq!(&response_body_to_string(response).await, "789");
+
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=10-"));
+        let response = file(&headers, &path).await.unwrap();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            get_header_from_response(&response, "content-range"),
+            Some("bytes */10".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_response_format() {
+        let response = stream(&b"loco"[..], "application/octet-stream").unwrap();
+
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("application/octet-stream".to_string())
+        );
+        assert_eq!(&response_body_to_string(response).await, "loco");
+    }
+
+    #[test]
+    fn can_pick_preferred_format() {
+        let preferred = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_str(accept).unwrap());
+            Negotiated::preferred(
+                &headers,
+                &[Negotiated::Json, Negotiated::Xml, Negotiated::Html],
+                Negotiated::Html,
+            )
+        };
+
+        assert_eq!(
+            Negotiated::preferred(&HeaderMap::new(), &[Negotiated::Json], Negotiated::Xml),
+            Negotiated::Xml
+        );
+        assert_eq!(preferred("application/xml"), Negotiated::Xml);
+        assert_eq!(preferred("*/*"), Negotiated::Json);
+        assert_eq!(preferred("image/png"), Negotiated::Html);
+        assert_eq!(
+            preferred("application/json;q=0.5, application/xml;q=0.9"),
+            Negotiated::Xml
+        );
+        assert_eq!(
+            preferred("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+            Negotiated::Html
+        );
+        assert_eq!(preferred("application/*;q=0.8, text/xml"), Negotiated::Xml);
+        assert_eq!(preferred("*/*, application/json;q=0"), Negotiated::Xml);
+        assert_eq!(
+            preferred("text/*;q=0, application/json;q=0, application/xml;q=0"),
+            Negotiated::Html
+        );
+    }
+
+    #[tokio::test]
+    async fn negotiated_view_response_format() {
+        let yaml_content = r"
+        files:
+        - path: template/test.html
+          content: |-
+            - {{foo}}
+        ";
+        let tree_res = tree_fs::from_yaml_str(yaml_content).unwrap();
+        let v = TeraView::from_custom_dir(&tree_res).unwrap();
+        let respond = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_str(accept).unwrap());
+            negotiated_view(
+                &headers,
+                Negotiated::Json,
+                &v,
+                "template/test.html",
+                serde_json::json!({"foo": "loco"}),
+            )
+            .unwrap()
+        };
+
+        let response = respond("text/html, application/json;q=0.9");
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("text/html; charset=utf-8".to_string())
+        );
+        assert_eq!(
+            get_header_from_response(&response, "vary"),
+            Some("accept".to_string())
+        );
+        assert_eq!(&response_body_to_string(response).await, "- loco");
+
+        let response = respond("application/json, text/html;q=0.9");
+        assert_eq!(
+            get_header_from_response(&response, "content-type"),
+            Some("application/json".to_string())
+        );
+        assert_eq!(
+            get_header_from_response(&response, "vary"),
+            Some("accept".to_string())
+        );
+
+        let response = respond("image/png");
+        assert_eq!(response_body_to_string(response).await, r#"{"foo":"loco"}"#);
+    }
+
     #[tokio::test]
     async fn empty_json_response_format() {
         let response = empty_json().unwrap();
@@ -466,6 +1592,111 @@ mod tests {
         assert_eq!(&response_body_to_string(response).await, "- loco");
     }
 
+    struct RemoteView;
+
+    #[async_trait::async_trait]
+    impl ViewRenderer for RemoteView {
+        fn render<S: Serialize>(&self, _key: &str, _data: S) -> Result<String> {
+            Err(Error::string("rendered synchronously"))
+        }
+
+        async fn render_async<S: Serialize + Send>(&self, key: &str, _data: S) -> Result<String> {
+            tokio::task::yield_now().await;
+            Ok(format!("<p>{key}</p>"))
+        }
+    }
+
+    #[tokio::test]
+    async fn view_async_response() {
+        let response = view_async(&RemoteView, "remote/home.html", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(
+            &response_body_to_string(response).await,
+            "<p>remote/home.html</p>"
+        );
+
+        let response = render()
+            .status(201)
+            .view_async(&RemoteView, "remote/new.html", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(
+            &response_body_to_string(response).await,
+            "<p>remote/new.html</p>"
+        );
+
+        let tree_res = tree_fs::from_yaml_str(
+            r"
+        files:
+        - path: template/test.html
+          content: |-
+            - {{foo}}
+        ",
+        )
+        .unwrap();
+        let v = TeraView::from_custom_dir(&tree_res).unwrap();
+        let response = view_async(&v, "template/test.html", serde_json::json!({"foo": "loco"}))
+            .await
+            .unwrap();
+        assert_eq!(&response_body_to_string(response).await, "- loco");
+    }
+
+    struct FragmentView;
+
+    impl ViewRenderer for FragmentView {
+        fn render<S: Serialize>(&self, _key: &str, _data: S) -> Result<String> {
+            Ok("<html><frag></html>".to_string())
+        }
+
+        fn render_fragment<S: Serialize>(
+            &self,
+            key: &str,
+            fragment: &str,
+            data: S,
+        ) -> Result<String> {
+            if fragment.is_empty() {
+                self.render(key, data)
+            } else {
+                Ok("<frag>".to_string())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn view_fragment_response() {
+        let response =
+            view_fragment(&FragmentView, "home.html", "list", serde_json::json!({})).unwrap();
+        assert_eq!(&response_body_to_string(response).await, "<frag>");
+
+        let response =
+            view_fragment(&FragmentView, "home.html", "", serde_json::json!({})).unwrap();
+        assert_eq!(
+            &response_body_to_string(response).await,
+            "<html><frag></html>"
+        );
+
+        let tree_res = tree_fs::from_yaml_str(
+            r"
+        files:
+        - path: template/test.html
+          content: |-
+            - {{foo}}
+        ",
+        )
+        .unwrap();
+        let v = TeraView::from_custom_dir(&tree_res).unwrap();
+        let response = view_fragment(
+            &v,
+            "template/test.html",
+            "list",
+            serde_json::json!({"foo": "loco"}),
+        )
+        .unwrap();
+        assert_eq!(&response_body_to_string(response).await, "- loco");
+    }
+
     #[tokio::test]
     async fn template_response() {
         let response = template("- {{foo}}", serde_json::json!({"foo": "loco"})).unwrap();
@@ -512,6 +1743,154 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn builder_etag_conditional_matching_response() {
+        let mut req_headers = HeaderMap::new();
+        req_headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"v1\", W/\"v2\""),
+        );
+
+        for etag in ["\"v2\"", "W/\"v1\""] {
+            let response = render()
+                .etag_conditional(&req_headers, etag)
+                .unwrap()
+                .json(serde_json::json!({"loco": "app"}))
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(
+                get_header_from_response(&response, "etag"),
+                Some(etag.to_string())
+            );
+            assert_eq!(get_header_from_response(&response, "content-type"), None);
+            assert_eq!(response_body_to_string(response).await, "");
+        }
+
+        req_headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        let response = render()
+            .etag_conditional(&req_headers, "\"v3\"")
+            .unwrap()
+            .text("loco")
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn builder_etag_conditional_not_matching_response() {
+        let mut req_headers = HeaderMap::new();
+        req_headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"v1\""));
+
+        let response = render()
+            .etag_conditional(&req_headers, "\"v2\"")
+            .unwrap()
+            .text("loco")
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            get_header_from_response(&response, "etag"),
+            Some("\"v2\"".to_string())
+        );
+        assert_eq!(response_body_to_string(response).await, "loco");
+
+        let response = render()
+            .etag_conditional(&HeaderMap::new(), "\"v1\"")
+            .unwrap()
+            .text("loco")
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn builder_auto_etag_response() {
+        let etag = |item: serde_json::Value| {
+            let response = render().auto_etag().json(item).unwrap();
+            get_header_from_response(&response, "etag").unwrap()
+        };
+
+        let loco = etag(serde_json::json!({"loco": "app"}));
+        assert!(loco.starts_with("W/\""));
+        assert_eq!(loco, etag(serde_json::json!({"loco": "app"})));
+        assert_ne!(loco, etag(serde_json::json!({"loco": "rs"})));
+
+        let response = render().auto_etag().text("loco").unwrap();
+        assert_eq!(
+            get_header_from_response(&response, "etag"),
+            Some(body_etag(b"loco"))
+        );
+        assert_eq!(body_etag(b"loco"), "W/\"4a630b8e79a0cd2fbae3f58e751abb28\"");
+        assert_eq!(response_body_to_string(response).await, "loco");
+    }
+
+    #[tokio::test]
+    async fn builder_auto_etag_conditional_response() {
+        let etag = body_etag(b"loco");
+        let mut req_headers = HeaderMap::new();
+        req_headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&etag).unwrap());
+
+        let response = render()
+            .auto_etag_conditional(&req_headers)
+            .text("loco")
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(get_header_from_response(&response, "etag"), Some(etag));
+        assert_eq!(response_body_to_string(response).await, "");
+
+        let response = render()
+            .auto_etag_conditional(&req_headers)
+            .text("changed")
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_body_to_string(response).await, "changed");
+    }
+
+    #[tokio::test]
+    async fn builder_cache_control_response() {
+        let response = render()
+            .cache_control(CacheControl {
+                max_age: Some(Duration::from_secs(3600)),
+                private: true,
+                must_revalidate: true,
+                ..Default::default()
+            })
+            .unwrap()
+            .empty()
+            .unwrap();
+        assert_eq!(
+            get_header_from_response(&response, "cache-control"),
+            Some("private, max-age=3600, must-revalidate".to_string())
+        );
+
+        let response = render()
+            .cache_control(CacheControl {
+                no_store: true,
+                ..Default::default()
+            })
+            .unwrap()
+            .empty()
+            .unwrap();
+        assert_eq!(
+            get_header_from_response(&response, "cache-control"),
+            Some("no-store".to_string())
+        );
+
+        assert!(render().cache_control(CacheControl::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn builder_last_modified_response() {
+        let at = chrono::FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 5, 9, 7, 2)
+            .unwrap();
+        let response = render().last_modified(&at).empty().unwrap();
+
+        assert_eq!(
+            get_header_from_response(&response, "last-modified"),
+            Some("Tue, 05 Mar 2024 07:07:02 GMT".to_string())
+        );
+    }
+
     #[tokio::test]
     async fn builder_cookies_response() {
         let response = render()
@@ -601,4 +1980,34 @@ mod tests {
         assert_debug_snapshot!(response);
         assert_eq!(response_body_to_string(response).await, String::new());
     }
+
+    #[tokio::test]
+    async fn builder_redirect_keeps_cookies() {
+        let response = render()
+            .cookies(&[cookie::Cookie::new("flash", "saved")])
+            .unwrap()
+            .redirect("/notes")
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            get_header_from_response(&response, "location"),
+            Some("/notes".to_string())
+        );
+        assert_eq!(
+            get_header_from_response(&response, "set-cookie"),
+            Some("flash=saved".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn builder_redirect_permanent_response() {
+        let response = render().redirect_permanent("/v2/notes").unwrap();
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            get_header_from_response(&response, "location"),
+            Some("/v2/notes".to_string())
+        );
+    }
 }
@@ -0,0 +1,59 @@
+//! Matched route pattern of a request.
+//!
+//! Every route registered through [`crate::controller::AppRoutes`] carries
+//! its full pattern (for example `/notes/:id`), which handlers can read with
+//! the [`MatchedRoute`] extractor. Unlike the concrete request path, the
+//! pattern is a low-cardinality value, fit for metric labels and log fields.
+//!
+//! ```rust
+//! use loco_rs::prelude::*;
+//!
+//! async fn show(route: MatchedRoute, Path(id): Path<i32>) -> Result<Response> {
+//!     tracing::info!(route = route.as_str(), id, "show note");
+//!     format::empty()
+//! }
+//! ```
+
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::Error;
+
+/// The route pattern that matched the request, such as `/notes/:id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedRoute(Arc<str>);
+
+impl MatchedRoute {
+    pub(crate) fn new(pattern: &str) -> Self {
+        Self(Arc::from(pattern))
+    }
+
+    /// Returns the route pattern.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MatchedRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for MatchedRoute
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Self>().cloned().ok_or_else(|| {
+            tracing::error!("matched route is only available on routes added through `AppRoutes`");
+            Error::InternalServerError
+        })
+    }
+}
@@ -1,21 +1,50 @@
 //! Configurable and Flexible CORS Middleware
 //!
 //! This middleware enables Cross-Origin Resource Sharing (CORS) by allowing
-//! configurable origins, methods, and headers in HTTP requests. It can be tailored
-//! to fit various application requirements, supporting permissive CORS or
-//! specific rules as defined in the middleware configuration.
+//! configurable origins, methods, and headers in HTTP requests. It can be
+//! tailored to fit various application requirements, supporting permissive CORS
+//! or specific rules as defined in the middleware configuration.
+//!
+//! Besides the `cors` middleware configuration, [`Cors`] builds a layer for
+//! a group of routes:
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use loco_rs::{controller::Cors, prelude::*};
+//!
+//! async fn list() -> Result<Response> {
+//!     format::json(())
+//! }
+//!
+//! # fn routes() -> Result<Routes> {
+//! let cors = Cors::new()
+//!     .allow_origin("https://example.com")
+//!     .allow_origin("https://*.example.com")
+//!     .allow_methods(["GET", "POST"])
+//!     .allow_credentials(true)
+//!     .max_age(Duration::from_secs(3600))
+//!     .cors()?;
+//! Ok(Routes::new().add("/notes", get(list)).layer(cors))
+//! # }
+//! ```
 
-use crate::{app::AppContext, controller::middleware::MiddlewareLayer, Result};
-use axum::Router as AXRouter;
-use serde::{Deserialize, Serialize};
 use std::time::Duration;
-use tower_http::cors;
+
+use axum::{http::HeaderValue, Router as AXRouter};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{self, AllowHeaders, AllowMethods, AllowOrigin, ExposeHeaders};
+
+use crate::{app::AppContext, controller::middleware::MiddlewareLayer, Error, Result};
 
 /// CORS middleware configuration
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct Cors {
     pub enable: bool,
-    /// Allow origins
+    /// Allow origins: `any` (or `*`), an origin, an origin with `*`
+    /// wildcards such as `https://*.example.com`, or a regex starting with
+    /// `^`
     #[serde(default = "default_allow_origins")]
     pub allow_origins: Vec<String>,
     /// Allow headers
@@ -24,6 +53,12 @@ pub struct Cors {
     /// Allow methods
     #[serde(default = "default_allow_methods")]
     pub allow_methods: Vec<String>,
+    /// Allow credentials (cookies and `Authorization` headers), mirroring
+    /// the request instead of answering the `*` wildcards of the methods and
+    /// headers. The origins must be listed, as any origin is rejected along
+    /// with credentials.
+    #[serde(default)]
+    pub allow_credentials: bool,
     /// Max age
     pub max_age: Option<u64>,
     // Vary headers
@@ -52,42 +87,120 @@ fn default_vary_headers() -> Vec<String> {
 }
 
 impl Cors {
-    /// Creates cors layer
+    /// Creates an enabled configuration allowing any origin, method and
+    /// header, to narrow down with the builder methods.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            enable: true,
+            allow_origins: vec![],
+            allow_headers: default_allow_headers(),
+            allow_methods: default_allow_methods(),
+            allow_credentials: false,
+            max_age: None,
+            vary: default_vary_headers(),
+        }
+    }
+
+    /// Allows `origin`, in the format of [`Cors::allow_origins`]. The first
+    /// origin replaces the default of allowing any origin.
+    #[must_use]
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        self.allow_origins.push(origin.into());
+        self
+    }
+
+    /// Allows the `methods`, or `*` for any method.
+    #[must_use]
+    pub fn allow_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_methods = methods.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Allows the request `headers`, or `*` for any header.
+    #[must_use]
+    pub fn allow_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Allows credentials, for origins added with [`Cors::allow_origin`].
+    #[must_use]
+    pub fn allow_credentials(mut self, allow_credentials: bool) -> Self {
+        self.allow_credentials = allow_credentials;
+        self
+    }
+
+    /// Lets browsers cache the preflight responses for `max_age`.
+    #[must_use]
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age.as_secs());
+        self
+    }
+
+    /// Creates cors layer, answering the preflight `OPTIONS` requests
     ///
     /// # Errors
     ///
     /// This function returns an error in the following cases:
     ///
-    /// - If any of the provided origins in `allow_origins` cannot be parsed as a valid URI,
-    ///   the function will return a parsing error.
-    /// - If any of the provided headers in `allow_headers` cannot be parsed as valid HTTP headers,
-    ///   the function will return a parsing error.
-    /// - If any of the provided methods in `allow_methods` cannot be parsed as valid HTTP methods,
-    ///   the function will return a parsing error.
+    /// - If any of the provided origins in `allow_origins` cannot be parsed as
+    ///   a valid URI, or as a regex, the function will return a parsing error.
+    /// - If any of the provided headers in `allow_headers` cannot be parsed as
+    ///   valid HTTP headers, the function will return a parsing error.
+    /// - If any of the provided methods in `allow_methods` cannot be parsed as
+    ///   valid HTTP methods, the function will return a parsing error.
+    ///
+    /// In all of these cases, the error returned will be the result of the
+    /// `parse` method of the corresponding type.
     ///
-    /// In all of these cases, the error returned will be the result of the `parse` method
-    /// of the corresponding type.
+    /// It also returns an error when `allow_credentials` is set along with
+    /// any origin, which would let any site make requests with the
+    /// credentials of the user.
     pub fn cors(&self) -> Result<cors::CorsLayer> {
-        let mut cors: cors::CorsLayer = cors::CorsLayer::permissive();
+        let origins = self.origins()?;
+        if self.allow_credentials && origins.is_none() {
+            return Err(Error::Message(
+                "CORS credentials need a list of allowed origins, not any origin".to_string(),
+            ));
+        }
 
-        let mut list = vec![];
+        let mut cors: cors::CorsLayer = cors::CorsLayer::permissive();
+        if self.allow_credentials {
+            // browsers reject the `*` wildcards of a permissive layer along with
+            // credentials
+            cors = cors
+                .allow_headers(AllowHeaders::mirror_request())
+                .allow_methods(AllowMethods::mirror_request())
+                .expose_headers(ExposeHeaders::list([]))
+                .allow_credentials(true);
+        }
 
         // testing CORS, assuming https://example.com in the allow list:
         // $ curl -v --request OPTIONS 'localhost:5150/api/_ping' -H 'Origin: https://example.com' -H 'Acces
         // look for '< access-control-allow-origin: https://example.com' in response.
         // if it doesn't appear (test with a bogus domain), it is not allowed.
-        for origin in &self.allow_origins {
-            list.push(origin.parse()?);
-        }
-        if !list.is_empty() {
-            cors = cors.allow_origin(list);
+        if let Some(origins) = origins {
+            cors = cors.allow_origin(origins);
         }
 
         let mut list = vec![];
         for header in &self.allow_headers {
             list.push(header.parse()?);
         }
-        if !list.is_empty() {
+        if is_wildcard(&self.allow_headers) {
+            if !self.allow_credentials {
+                cors = cors.allow_headers(cors::Any);
+            }
+        } else if !list.is_empty() {
             cors = cors.allow_headers(list);
         }
 
@@ -95,7 +208,11 @@ impl Cors {
         for method in &self.allow_methods {
             list.push(method.parse()?);
         }
-        if !list.is_empty() {
+        if is_wildcard(&self.allow_methods) {
+            if !self.allow_credentials {
+                cors = cors.allow_methods(cors::Any);
+            }
+        } else if !list.is_empty() {
             cors = cors.allow_methods(list);
         }
 
@@ -113,6 +230,50 @@ impl Cors {
 
         Ok(cors)
     }
+
+    /// The allowed origins, `None` for any origin.
+    fn origins(&self) -> Result<Option<AllowOrigin>> {
+        if self.allow_origins.is_empty()
+            || self
+                .allow_origins
+                .iter()
+                .any(|origin| origin == "any" || origin == "*")
+        {
+            return Ok(None);
+        }
+
+        let mut list = vec![];
+        let mut patterns = vec![];
+        for origin in &self.allow_origins {
+            if origin.starts_with('^') {
+                patterns.push(Regex::new(origin).map_err(|err| Error::Any(err.into()))?);
+            } else if origin.contains('*') {
+                let pattern = origin
+                    .split('*')
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join("[^/]+");
+                patterns.push(
+                    Regex::new(&format!("^{pattern}$")).map_err(|err| Error::Any(err.into()))?,
+                );
+            } else {
+                list.push(origin.parse::<HeaderValue>()?);
+            }
+        }
+        if patterns.is_empty() {
+            return Ok(Some(AllowOrigin::list(list)));
+        }
+        Ok(Some(AllowOrigin::predicate(move |origin, _| {
+            list.contains(origin)
+                || origin
+                    .to_str()
+                    .is_ok_and(|origin| patterns.iter().any(|pattern| pattern.is_match(origin)))
+        })))
+    }
+}
+
+fn is_wildcard(list: &[String]) -> bool {
+    list.iter().any(|value| value == "*")
 }
 
 impl MiddlewareLayer for Cors {
@@ -139,11 +300,9 @@ impl MiddlewareLayer for Cors {
 #[cfg(test)]
 mod tests {
 
-    use super::*;
-    use crate::tests_cfg;
     use axum::{
         body::Body,
-        http::{Method, Request},
+        http::{HeaderMap, Method, Request, StatusCode},
         routing::get,
         Router,
     };
@@ -151,6 +310,9 @@ mod tests {
     use rstest::rstest;
     use tower::ServiceExt;
 
+    use super::*;
+    use crate::tests_cfg;
+
     #[rstest]
     #[case("default", None, None, None)]
     #[case("with_allow_headers", Some(vec!["token".to_string(), "user".to_string()]), None, None)]
@@ -208,6 +370,139 @@ mod tests {
         );
     }
 
+    async fn call(cors: &Cors, method: Method, headers: &[(&str, &str)]) -> HeaderMap {
+        let app = Router::new()
+            .route("/", get(|| async {}).post(|| async {}))
+            .layer(cors.cors().expect("cors layer"));
+        let mut req = Request::builder().uri("/").method(method);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let response = app
+            .oneshot(req.body(Body::empty()).expect("request"))
+            .await
+            .expect("valid response");
+        assert_eq!(response.status(), StatusCode::OK);
+        response.headers().clone()
+    }
+
+    #[tokio::test]
+    async fn answers_preflight() {
+        let cors = Cors::new()
+            .allow_origin("https://example.com")
+            .allow_methods(["GET", "POST"])
+            .allow_headers(["content-type"])
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(600));
+
+        let headers = call(
+            &cors,
+            Method::OPTIONS,
+            &[
+                ("origin", "https://example.com"),
+                ("access-control-request-method", "POST"),
+                ("access-control-request-headers", "content-type"),
+            ],
+        )
+        .await;
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://example.com"
+        );
+        assert_eq!(headers["access-control-allow-methods"], "GET,POST");
+        assert_eq!(headers["access-control-allow-headers"], "content-type");
+        assert_eq!(headers["access-control-allow-credentials"], "true");
+        assert_eq!(headers["access-control-max-age"], "600");
+    }
+
+    #[tokio::test]
+    async fn allows_simple_cross_origin_get() {
+        let cors = Cors::new()
+            .allow_origin("https://example.com")
+            .allow_origin("https://*.example.com")
+            .allow_origin(r"^https://(app|admin)\.example\.org$");
+        let origin = |origin: &'static str| {
+            let cors = cors.clone();
+            async move {
+                call(&cors, Method::GET, &[("origin", origin)])
+                    .await
+                    .get("access-control-allow-origin")
+                    .cloned()
+            }
+        };
+
+        for allowed in [
+            "https://example.com",
+            "https://api.example.com",
+            "https://admin.example.org",
+        ] {
+            assert_eq!(origin(allowed).await.expect(allowed), allowed);
+        }
+        for rejected in [
+            "https://example.org",
+            "http://api.example.com",
+            "https://api.example.com.evil.com",
+            "https://www.example.org",
+        ] {
+            assert_eq!(origin(rejected).await, None, "{rejected}");
+        }
+    }
+
+    #[test]
+    fn rejects_credentials_with_any_origin() {
+        assert!(Cors::new().allow_credentials(true).cors().is_err());
+        assert!(Cors {
+            enable: true,
+            allow_credentials: true,
+            allow_origins: vec!["https://example.com".to_string(), "*".to_string()],
+            ..Default::default()
+        }
+        .cors()
+        .is_err());
+        assert!(Cors::new()
+            .allow_origin("https://example.com")
+            .allow_credentials(true)
+            .cors()
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn mirrors_wildcards_with_credentials() {
+        let cors = Cors::new()
+            .allow_origin("https://*.example.com")
+            .allow_credentials(true);
+        let headers = call(
+            &cors,
+            Method::OPTIONS,
+            &[
+                ("origin", "https://app.example.com"),
+                ("access-control-request-method", "DELETE"),
+                ("access-control-request-headers", "x-token"),
+            ],
+        )
+        .await;
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+        assert_eq!(headers["access-control-allow-methods"], "DELETE");
+        assert_eq!(headers["access-control-allow-headers"], "x-token");
+        assert_eq!(headers["access-control-allow-credentials"], "true");
+
+        // other origins are not mirrored
+        let headers = call(&cors, Method::GET, &[("origin", "https://evil.com")]).await;
+        assert!(!headers.contains_key("access-control-allow-origin"));
+
+        let headers = call(
+            &Cors::new(),
+            Method::GET,
+            &[("origin", "https://example.com")],
+        )
+        .await;
+        assert_eq!(headers["access-control-allow-origin"], "*");
+        assert!(!headers.contains_key("access-control-allow-credentials"));
+    }
+
     #[test]
     fn should_be_disabled() {
         let middleware = Cors::default();
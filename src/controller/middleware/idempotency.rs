@@ -0,0 +1,632 @@
+//! Idempotency Key Middleware
+//!
+//! A client retrying a request after a network failure cannot tell whether
+//! the first attempt went through, and retrying a `POST` can create a
+//! duplicate. With this layer, the client sends an `Idempotency-Key` header,
+//! unique per operation: the first response for a key is stored and replayed,
+//! with an `Idempotent-Replayed: true` header, for retries of the same request
+//! until the key expires. Requests with the same key are served one at a
+//! time, so a retry racing the original waits for its response instead of
+//! running the handler again.
+//!
+//! Reusing a key for a different request (another method, path or body) is
+//! rejected with `422 Unprocessable Entity`. Server errors are not stored, so
+//! they can be retried, and requests without the header pass through.
+//!
+//! Keys are scoped to the caller, so a client cannot get the response of
+//! another client by sending the same key. The caller is identified by its
+//! `Authorization` and `Cookie` headers, or by its IP when it sends neither;
+//! use [`IdempotencyLayer::scope`] to identify it otherwise, such as by user
+//! id. Stored responses do not keep their `Set-Cookie` headers, which only
+//! the first response carries.
+//!
+//! The layer reads the body of requests carrying a key to recognize them, up
+//! to [`IdempotencyLayer::max_body_size`], and answers `413 Payload Too
+//! Large` over it.
+//!
+//! Responses are stored in memory by default. To share them between app
+//! instances, store them in a [`Cache`] with [`IdempotencyLayer::with_cache`]
+//! (concurrent requests are still only serialized within an instance).
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use loco_rs::{controller::middleware::idempotency::IdempotencyLayer, prelude::*};
+//!
+//! async fn add() -> Result<Response> {
+//!     format::json(())
+//! }
+//!
+//! Routes::new().add(
+//!     "/notes",
+//!     post(add).layer(IdempotencyLayer::new(Duration::from_secs(24 * 60 * 60))),
+//! );
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, request, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tower::{Layer, Service};
+
+use super::{
+    limit_payload::LimitPayload,
+    rate_limit::{KeyFn, RateLimitKey},
+};
+use crate::{cache::Cache, controller::ErrorDetail, Error, Result};
+
+/// The request header holding the idempotency key.
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// The response header marking a replayed response.
+pub const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Response headers meant for the client of the first response only.
+const NOT_STORED: [HeaderName; 1] = [header::SET_COOKIE];
+
+/// A stored response, along with the request it answered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Stored {
+    fingerprint: String,
+    expires_at: i64,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Stored {
+    fn is_expired(&self) -> bool {
+        self.expires_at <= chrono::Utc::now().timestamp_millis()
+    }
+
+    fn replay(&self) -> Result<Response> {
+        let mut response = Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            response = response.header(name, value);
+        }
+        Ok(response
+            .header(IDEMPOTENT_REPLAYED, "true")
+            .body(Body::from(
+                STANDARD
+                    .decode(&self.body)
+                    .map_err(|err| Error::Message(err.to_string()))?,
+            ))?)
+    }
+}
+
+#[derive(Clone)]
+enum Store {
+    Memory(Arc<Mutex<HashMap<String, Stored>>>),
+    Cache(Arc<Cache>),
+}
+
+impl Store {
+    async fn get(&self, key: &str) -> Result<Option<Stored>> {
+        let stored = match self {
+            Self::Memory(entries) => entries.lock().unwrap().get(key).cloned(),
+            Self::Cache(cache) => cache
+                .get(key)
+                .await?
+                .map(|stored| serde_json::from_str(&stored))
+                .transpose()?,
+        };
+        Ok(stored.filter(|stored| !stored.is_expired()))
+    }
+
+    async fn insert(&self, key: &str, stored: Stored, ttl: Duration) -> Result<()> {
+        match self {
+            Self::Memory(entries) => {
+                let mut entries = entries.lock().unwrap();
+                entries.retain(|_, stored| !stored.is_expired());
+                entries.insert(key.to_string(), stored);
+            }
+            Self::Cache(cache) => {
+                cache
+                    .insert_with_expiry(key, &serde_json::to_string(&stored)?, ttl)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Layer storing and replaying the responses of requests carrying an
+/// `Idempotency-Key` header.
+#[derive(Clone)]
+pub struct IdempotencyLayer {
+    store: Store,
+    ttl: Duration,
+    scope: Option<KeyFn>,
+    max_body_size: usize,
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl IdempotencyLayer {
+    /// Creates a layer replaying responses for `ttl`, stored in memory, for
+    /// request bodies up to the default `limit_payload` body limit.
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            store: Store::Memory(Arc::default()),
+            ttl,
+            scope: None,
+            max_body_size: LimitPayload::default().body_limit,
+            locks: Arc::default(),
+        }
+    }
+
+    /// Sets the largest request body, in bytes, read for a request carrying
+    /// a key, such as the `limit_payload` body limit of the app.
+    #[must_use]
+    pub const fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Stores the responses in `cache`, such as the app cache
+    /// (`ctx.cache`), instead of in memory.
+    #[must_use]
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.store = Store::Cache(cache);
+        self
+    }
+
+    /// Scopes the keys to the caller computed from the request, such as the
+    /// id of the authenticated user, instead of to its `Authorization` and
+    /// `Cookie` headers. Requests for which `scope` returns `None` share their
+    /// keys.
+    #[must_use]
+    pub fn scope(
+        mut self,
+        scope: impl Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.scope = Some(Arc::new(scope));
+        self
+    }
+
+    /// The store key for the idempotency `key` of `request`.
+    fn key(&self, request: &Request, key: &str) -> String {
+        let caller = match &self.scope {
+            Some(scope) => scope(request),
+            None => caller(request),
+        };
+        // hashed, so credentials do not end up in the store
+        let mut hasher = Sha256::new();
+        hasher.update(caller.unwrap_or_default());
+        format!("idempotency:{}:{key}", STANDARD.encode(hasher.finalize()))
+    }
+
+    fn lock(&self, key: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .clone()
+    }
+
+    fn unlock(&self, key: &str, lock: Arc<tokio::sync::Mutex<()>>) {
+        drop(lock);
+        let mut locks = self.locks.lock().unwrap();
+        // no other request holds or waits for it
+        if locks
+            .get(key)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(key);
+        }
+    }
+}
+
+impl<S> Layer<S> for IdempotencyLayer {
+    type Service = IdempotencyMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IdempotencyMiddleware {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Idempotency Key Middleware
+#[derive(Clone)]
+pub struct IdempotencyMiddleware<S> {
+    inner: S,
+    layer: IdempotencyLayer,
+}
+
+impl<S> Service<Request> for IdempotencyMiddleware<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let Some(key) = request
+            .headers()
+            .get(IDEMPOTENCY_KEY)
+            .and_then(|key| key.to_str().ok())
+            .map(|key| self.layer.key(&request, key))
+        else {
+            return Box::pin(self.inner.call(request));
+        };
+
+        // keep the service that was polled ready for this request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let lock = layer.lock(&key);
+            let response = {
+                let _guard = lock.lock().await;
+                idempotent(&mut inner, &layer, &key, request).await
+            };
+            layer.unlock(&key, lock);
+            response
+        })
+    }
+}
+
+async fn idempotent<S>(
+    inner: &mut S,
+    layer: &IdempotencyLayer,
+    key: &str,
+    request: Request,
+) -> Result<Response, S::Error>
+where
+    S: Service<Request, Response = Response>,
+{
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, layer.max_body_size).await {
+        Ok(body) => body,
+        Err(err) if is_too_large(&err) => {
+            return Ok(Error::CustomError(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ErrorDetail::new(
+                    "payload_too_large".to_string(),
+                    format!("request body is over {} bytes", layer.max_body_size),
+                ),
+            )
+            .into_response());
+        }
+        Err(err) => return Ok(Error::BadRequest(err.to_string()).into_response()),
+    };
+    let fingerprint = fingerprint(&parts, &body);
+
+    match layer.store.get(key).await {
+        Ok(Some(stored)) if stored.fingerprint == fingerprint => {
+            return Ok(stored.replay().into_response());
+        }
+        Ok(Some(_)) => {
+            return Ok(Error::CustomError(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorDetail::new(
+                    "unprocessable_entity",
+                    "idempotency key was already used for a different request",
+                ),
+            )
+            .into_response());
+        }
+        Ok(None) => {}
+        Err(err) => return Ok(err.into_response()),
+    }
+
+    let response = inner
+        .call(Request::from_parts(parts, Body::from(body)))
+        .await?;
+    if response.status().is_server_error() {
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => return Ok(Error::Any(err.into()).into_response()),
+    };
+    let stored = Stored {
+        fingerprint,
+        expires_at: chrono::Utc::now()
+            .timestamp_millis()
+            .saturating_add(i64::try_from(layer.ttl.as_millis()).unwrap_or(i64::MAX)),
+        status: parts.status.as_u16(),
+        headers: parts
+            .headers
+            .iter()
+            .filter(|(name, _)| !NOT_STORED.contains(name))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: STANDARD.encode(&body),
+    };
+    if let Err(err) = layer.store.insert(key, stored, layer.ttl).await {
+        tracing::error!(error = %err, "could not store idempotent response");
+    }
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+fn is_too_large(err: &axum::Error) -> bool {
+    std::error::Error::source(err)
+        .is_some_and(|source| source.is::<http_body_util::LengthLimitError>())
+}
+
+/// Identifies the caller by its credentials, or by its IP without
+/// credentials.
+fn caller(request: &Request) -> Option<String> {
+    let credentials = [header::AUTHORIZATION, header::COOKIE]
+        .iter()
+        .filter_map(|name| request.headers().get(name)?.to_str().ok())
+        .collect::<Vec<_>>();
+    if credentials.is_empty() {
+        RateLimitKey::Ip.of(request)
+    } else {
+        Some(credentials.join("\n"))
+    }
+}
+
+/// Identifies a request by its method, path and body, so a key cannot be
+/// replayed for another request.
+fn fingerprint(parts: &request::Parts, body: &Bytes) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(parts.method.as_str());
+    hasher.update(b" ");
+    hasher.update(parts.uri.to_string());
+    hasher.update(b"\n");
+    hasher.update(body);
+    STANDARD.encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(ttl: Duration, calls: &Arc<AtomicUsize>) -> Router {
+        let calls = calls.clone();
+        Router::new().route(
+            "/notes",
+            post(move |body: String| async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                (StatusCode::CREATED, format!("note {n}: {body}"))
+            })
+            .layer(IdempotencyLayer::new(ttl)),
+        )
+    }
+
+    async fn call(app: &Router, key: Option<&str>, body: &str) -> (StatusCode, bool, String) {
+        call_as(app, "Bearer alice", key, body).await
+    }
+
+    async fn call_as(
+        app: &Router,
+        authorization: &str,
+        key: Option<&str>,
+        body: &str,
+    ) -> (StatusCode, bool, String) {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/notes")
+            .header(header::AUTHORIZATION, authorization);
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY, key);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn passes_first_request_through() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(Duration::from_secs(60), &calls);
+
+        assert_eq!(
+            call(&app, Some("a"), "hello").await,
+            (StatusCode::CREATED, false, "note 1: hello".to_string())
+        );
+        assert_eq!(
+            call(&app, None, "hello").await,
+            (StatusCode::CREATED, false, "note 2: hello".to_string())
+        );
+        assert_eq!(
+            call(&app, None, "hello").await,
+            (StatusCode::CREATED, false, "note 3: hello".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn replays_stored_response() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(Duration::from_secs(60), &calls);
+
+        call(&app, Some("a"), "hello").await;
+        assert_eq!(
+            call(&app, Some("a"), "hello").await,
+            (StatusCode::CREATED, true, "note 1: hello".to_string())
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let (status, _, _) = call(&app, Some("a"), "bye").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            call(&app, Some("b"), "bye").await,
+            (StatusCode::CREATED, false, "note 2: bye".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_the_max_body_size() {
+        let app = Router::new().route(
+            "/notes",
+            post(|body: String| async move { body })
+                .layer(IdempotencyLayer::new(Duration::from_secs(60)).max_body_size(8)),
+        );
+
+        let (status, _, body) = call(&app, Some("a"), "over the limit").await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body.contains("payload_too_large"), "{body}");
+        assert_eq!(
+            call(&app, Some("b"), "12345678").await,
+            (StatusCode::OK, false, "12345678".to_string())
+        );
+        // without a key, the body is left to the extractors
+        assert_eq!(
+            call(&app, None, "over the limit").await,
+            (StatusCode::OK, false, "over the limit".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn serializes_concurrent_requests() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(Duration::from_secs(60), &calls);
+
+        let (first, second) = tokio::join!(
+            call(&app, Some("a"), "hello"),
+            call(&app, Some("a"), "hello")
+        );
+        assert_eq!(first.2, "note 1: hello");
+        assert_eq!(second.2, "note 1: hello");
+        assert!(first.1 != second.1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn expires_stored_response() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(Duration::from_millis(30), &calls);
+
+        call(&app, Some("a"), "hello").await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(
+            call(&app, Some("a"), "hello").await,
+            (StatusCode::CREATED, false, "note 2: hello".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn scopes_keys_to_the_caller() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(Duration::from_secs(60), &calls);
+
+        call_as(&app, "Bearer alice", Some("a"), "hello").await;
+        assert_eq!(
+            call_as(&app, "Bearer bob", Some("a"), "hello").await,
+            (StatusCode::CREATED, false, "note 2: hello".to_string())
+        );
+        assert_eq!(
+            call_as(&app, "Bearer alice", Some("a"), "hello").await,
+            (StatusCode::CREATED, true, "note 1: hello".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn can_scope_keys_with_a_custom_scope() {
+        let app = Router::new().route(
+            "/notes",
+            post(|| async { (StatusCode::CREATED, "note") }).layer(
+                IdempotencyLayer::new(Duration::from_secs(60))
+                    .scope(|request| Some(request.uri().path().to_string())),
+            ),
+        );
+
+        call_as(&app, "Bearer alice", Some("a"), "").await;
+        // the same scope for both callers
+        let (_, replayed, _) = call_as(&app, "Bearer bob", Some("a"), "").await;
+        assert!(replayed);
+    }
+
+    #[tokio::test]
+    async fn does_not_replay_cookies() {
+        let app = Router::new().route(
+            "/notes",
+            post(|| async {
+                (
+                    StatusCode::CREATED,
+                    [
+                        (header::SET_COOKIE, "session=alice"),
+                        (header::ETAG, "\"1\""),
+                    ],
+                    "note",
+                )
+            })
+            .layer(IdempotencyLayer::new(Duration::from_secs(60))),
+        );
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("/notes")
+                .header(IDEMPOTENCY_KEY, "a")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let first = app.clone().oneshot(request()).await.unwrap();
+        assert!(first.headers().contains_key(header::SET_COOKIE));
+        let replayed = app.oneshot(request()).await.unwrap();
+        assert!(replayed.headers().contains_key(IDEMPOTENT_REPLAYED));
+        assert!(!replayed.headers().contains_key(header::SET_COOKIE));
+        assert_eq!(replayed.headers()[header::ETAG], "\"1\"");
+    }
+
+    #[tokio::test]
+    async fn can_store_in_cache() {
+        let cache = Arc::new(Cache::new(crate::cache::drivers::inmem::new()));
+        let layer = IdempotencyLayer::new(Duration::from_millis(30)).with_cache(cache.clone());
+        let app = Router::new().route(
+            "/notes",
+            post(|| async { (StatusCode::CREATED, "note") }).layer(layer.clone()),
+        );
+        let key = layer.key(
+            &Request::builder()
+                .header(header::AUTHORIZATION, "Bearer alice")
+                .body(Body::empty())
+                .unwrap(),
+            "a",
+        );
+
+        call(&app, Some("a"), "").await;
+        assert!(cache.contains_key(&key).await.unwrap());
+        assert_eq!(
+            call(&app, Some("a"), "").await,
+            (StatusCode::CREATED, true, "note".to_string())
+        );
+
+        // stored with the ttl of the layer
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(!cache.contains_key(&key).await.unwrap());
+    }
+}
@@ -14,12 +14,17 @@ pub mod cors;
 pub mod etag;
 pub mod fallback;
 pub mod format;
+pub mod idempotency;
 pub mod limit_payload;
 pub mod logger;
 pub mod powered_by;
+pub mod rate_limit;
 pub mod remote_ip;
 pub mod request_id;
+pub mod required_headers;
+pub mod response_schema;
 pub mod secure_headers;
+pub mod slow_requests;
 pub mod static_assets;
 pub mod timeout;
 
@@ -63,6 +68,7 @@ pub trait MiddlewareLayer {
 pub fn default_middleware_stack(ctx: &AppContext) -> Vec<Box<dyn MiddlewareLayer>> {
     vec![
         Box::new(ctx.config.server.middlewares.limit_payload.clone()),
+        Box::new(ctx.config.server.middlewares.required_headers.clone()),
         Box::new(ctx.config.server.middlewares.cors.clone()),
         Box::new(ctx.config.server.middlewares.catch_panic.clone()),
         Box::new(ctx.config.server.middlewares.etag.clone()),
@@ -71,6 +77,7 @@ pub fn default_middleware_stack(ctx: &AppContext) -> Vec<Box<dyn MiddlewareLayer
         Box::new(ctx.config.server.middlewares.timeout_request.clone()),
         Box::new(ctx.config.server.middlewares.static_assets.clone()),
         Box::new(ctx.config.server.middlewares.secure_headers.clone()),
+        Box::new(ctx.config.server.middlewares.slow_requests.clone()),
         Box::new(logger::new(
             &ctx.config.server.middlewares.logger,
             &ctx.environment,
@@ -132,4 +139,12 @@ pub struct Config {
     /// Request ID
     #[serde(default)]
     pub request_id: request_id::RequestId,
+
+    /// Reject requests missing headers required by a route
+    #[serde(default)]
+    pub required_headers: required_headers::RequiredHeaders,
+
+    /// Report requests slower than a threshold
+    #[serde(default)]
+    pub slow_requests: slow_requests::SlowRequests,
 }
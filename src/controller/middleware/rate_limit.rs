@@ -0,0 +1,515 @@
+//! Rate Limit Middleware
+//!
+//! Limits each client to a number of requests per window of time, answering
+//! `429 Too Many Requests` with a `Retry-After` header once its limit is
+//! used up. The limit is a token bucket: a client can send `limit` requests
+//! at once, then it gets a request back every `window / limit`, so after a
+//! whole window without requests it can send `limit` requests at once again.
+//!
+//! Clients are told apart by a [`RateLimitKey`]: their IP (the default),
+//! a request header such as an API key, or a key computed from the request,
+//! such as the id of the authenticated user. Requests without a key, such as
+//! requests lacking the header, are not limited.
+//!
+//! The buckets are kept in memory by default, a [`RedisStore`] shares them
+//! between app instances, and any [`RateLimitStore`] can be plugged in. When
+//! the store fails, requests are let through.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use loco_rs::{
+//!     controller::middleware::rate_limit::{RateLimitKey, RateLimitLayer},
+//!     prelude::*,
+//! };
+//!
+//! async fn login() -> Result<Response> {
+//!     format::json(())
+//! }
+//!
+//! Routes::new().add(
+//!     "/login",
+//!     post(login).layer(RateLimitLayer::new(5, Duration::from_secs(60))),
+//! );
+//!
+//! // or for a whole group of routes, keyed on an API key
+//! Routes::new().add("/search", get(login)).layer(
+//!     RateLimitLayer::new(100, Duration::from_secs(60))
+//!         .key(RateLimitKey::Header("x-api-key".parse().unwrap())),
+//! );
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bb8::Pool;
+use futures_util::future::BoxFuture;
+use sidekiq::redis_rs::Script;
+use tower::{Layer, Service};
+
+use super::remote_ip::RemoteIP;
+use crate::{controller::ErrorDetail, worker::RedisConnectionManager, Error, Result};
+
+/// How many keys a [`MemoryStore`] holds by default.
+const MEMORY_MAX_KEYS: usize = 100_000;
+
+/// A number of requests allowed per window of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    pub window: Duration,
+}
+
+impl Quota {
+    /// Allows `limit` requests per `window`, at least one.
+    #[must_use]
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit: limit.max(1),
+            window,
+        }
+    }
+
+    /// The time it takes to get a request back.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.window / self.limit
+    }
+}
+
+/// Keeps the buckets of the clients.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a request from the bucket of `key`, returning how long to wait
+    /// for the next one when the bucket is empty.
+    ///
+    /// # Errors
+    /// When the store cannot be reached.
+    async fn take(&self, key: &str, quota: Quota) -> Result<Option<Duration>>;
+}
+
+/// Keeps the buckets in memory, per app instance.
+///
+/// A bucket is the time at which it is full again, its theoretical arrival
+/// time in the GCRA flavor of token buckets, as a single timestamp is enough
+/// to track it.
+///
+/// The store holds up to [`MemoryStore::max_keys`] buckets. Once full, a new
+/// key drops the full buckets, then the buckets closest to full, down to half
+/// the keys, so clients rotating keys cannot grow it. A dropped bucket that
+/// was not full gives its client a full bucket again.
+#[derive(Debug)]
+pub struct MemoryStore {
+    full_at: Mutex<HashMap<String, Instant>>,
+    max_keys: usize,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self {
+            full_at: Mutex::default(),
+            max_keys: MEMORY_MAX_KEYS,
+        }
+    }
+}
+
+impl MemoryStore {
+    /// Holds at most `max_keys` buckets, at least one.
+    #[must_use]
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys.max(1);
+        self
+    }
+}
+
+/// Drops the full buckets, then the ones closest to full until at most `keep`
+/// are left. Dropping half the keys at once spreads the scan over as many new
+/// keys.
+fn evict(full_at: &mut HashMap<String, Instant>, now: Instant, keep: usize) {
+    full_at.retain(|_, full_at| *full_at > now);
+    if full_at.len() > keep {
+        let mut times = full_at.values().copied().collect::<Vec<_>>();
+        let index = times.len() - keep - 1;
+        let (_, cutoff, _) = times.select_nth_unstable(index);
+        let cutoff = *cutoff;
+        full_at.retain(|_, full_at| *full_at > cutoff);
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn take(&self, key: &str, quota: Quota) -> Result<Option<Duration>> {
+        let now = Instant::now();
+        let mut full_at = self.full_at.lock().unwrap();
+        if full_at.len() >= self.max_keys && !full_at.contains_key(key) {
+            evict(&mut full_at, now, self.max_keys / 2);
+        }
+
+        let next = full_at.get(key).map_or(now, |full_at| now.max(*full_at)) + quota.interval();
+        let allowed_at = now + quota.window;
+        if next > allowed_at {
+            return Ok(Some(next - allowed_at));
+        }
+        full_at.insert(key.to_string(), next);
+        drop(full_at);
+        Ok(None)
+    }
+}
+
+/// The [`MemoryStore`] algorithm, run atomically by Redis with its own clock,
+/// so app instances with skewed clocks agree. Returns the milliseconds to
+/// wait, 0 when the request is allowed.
+const REDIS_TAKE: &str = r"
+local time = redis.call('TIME')
+local now = time[1] * 1000 + time[2] / 1000
+local interval = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local next = math.max(tonumber(redis.call('GET', KEYS[1]) or now), now) + interval
+if next - now > window then
+  return math.ceil(next - now - window)
+end
+redis.call('SET', KEYS[1], tostring(next), 'PX', math.ceil(next - now))
+return 0
+";
+
+/// Keeps the buckets in Redis, shared by the app instances, such as in the
+/// queue pool (`ctx.queue`).
+///
+/// Limits sharing a Redis should use different prefixes, or they share their
+/// buckets.
+#[derive(Clone)]
+pub struct RedisStore {
+    pool: Pool<RedisConnectionManager>,
+    prefix: String,
+}
+
+impl RedisStore {
+    /// Creates a store with the `rate_limit` prefix.
+    #[must_use]
+    pub fn new(pool: Pool<RedisConnectionManager>) -> Self {
+        Self {
+            pool,
+            prefix: "rate_limit".to_string(),
+        }
+    }
+
+    /// Prefixes the Redis keys with `prefix`.
+    #[must_use]
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn take(&self, key: &str, quota: Quota) -> Result<Option<Duration>> {
+        let mut conn = self.pool.get().await?;
+        let wait_ms: u64 = Script::new(REDIS_TAKE)
+            .key(format!("{}:{key}", self.prefix))
+            .arg(quota.interval().as_secs_f64() * 1000.0)
+            .arg(quota.window.as_secs_f64() * 1000.0)
+            .invoke_async(conn.unnamespaced_borrow_mut())
+            .await?;
+        Ok((wait_ms > 0).then(|| Duration::from_millis(wait_ms)))
+    }
+}
+
+/// Computes the key of a request, see [`RateLimitKey::Custom`].
+pub type KeyFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+/// Tells the clients apart.
+#[derive(Clone)]
+pub enum RateLimitKey {
+    /// The client IP, as resolved by the `remote_ip` middleware when it is
+    /// enabled, or the peer address of the connection.
+    Ip,
+    /// The value of a request header, such as an API key.
+    Header(HeaderName),
+    /// A key computed from the request, such as the id of the authenticated
+    /// user, or `None` to not limit the request.
+    Custom(KeyFn),
+}
+
+impl fmt::Debug for RateLimitKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip => f.write_str("Ip"),
+            Self::Header(name) => f.debug_tuple("Header").field(name).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+impl RateLimitKey {
+    pub(crate) fn of(&self, request: &Request) -> Option<String> {
+        match self {
+            Self::Ip => match request.extensions().get::<RemoteIP>() {
+                Some(RemoteIP::Forwarded(ip) | RemoteIP::Socket(ip)) => Some(ip.to_string()),
+                _ => request
+                    .extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip().to_string()),
+            },
+            Self::Header(name) => request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string),
+            Self::Custom(key) => key(request),
+        }
+    }
+}
+
+/// Layer limiting the number of requests per client.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    quota: Quota,
+    key: RateLimitKey,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimitLayer {
+    /// Allows `limit` requests per `window` and client IP, kept in memory.
+    #[must_use]
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            quota: Quota::new(limit, window),
+            key: RateLimitKey::Ip,
+            store: Arc::new(MemoryStore::default()),
+        }
+    }
+
+    /// Tells the clients apart by `key` instead of by IP.
+    #[must_use]
+    pub fn key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Keeps the buckets in `store` instead of in memory.
+    #[must_use]
+    pub fn store(mut self, store: impl RateLimitStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitMiddleware {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Rate Limit Middleware
+#[derive(Clone)]
+pub struct RateLimitMiddleware<S> {
+    inner: S,
+    layer: RateLimitLayer,
+}
+
+impl<S> Service<Request> for RateLimitMiddleware<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let Some(key) = self.layer.key.of(&request) else {
+            return Box::pin(self.inner.call(request));
+        };
+
+        // keep the service that was polled ready for this request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            match layer.store.take(&key, layer.quota).await {
+                Ok(Some(retry_after)) => return Ok(too_many_requests(retry_after)),
+                Ok(None) => {}
+                Err(err) => tracing::error!(error = %err, "could not check rate limit"),
+            }
+            inner.call(request).await
+        })
+    }
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    // whole seconds, rounded up so the retry is allowed
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let mut response = Error::CustomError(
+        StatusCode::TOO_MANY_REQUESTS,
+        ErrorDetail::new("too_many_requests", "rate limit exceeded"),
+    )
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(100);
+
+    fn app(layer: RateLimitLayer) -> Router {
+        Router::new().route("/", get(|| async { "ok" }).layer(layer))
+    }
+
+    async fn call(app: &Router, api_key: Option<&str>) -> Response {
+        let mut request = Request::builder().uri("/");
+        if let Some(api_key) = api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn api_key_app() -> Router {
+        app(RateLimitLayer::new(3, WINDOW)
+            .key(RateLimitKey::Header(HeaderName::from_static("x-api-key"))))
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_over_the_limit() {
+        let app = api_key_app();
+
+        for _ in 0..3 {
+            assert_eq!(call(&app, Some("a")).await.status(), StatusCode::OK);
+        }
+        let response = call(&app, Some("a")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        // other clients have their own limit
+        assert_eq!(call(&app, Some("b")).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn resets_after_the_window() {
+        let app = api_key_app();
+
+        for _ in 0..3 {
+            call(&app, Some("a")).await;
+        }
+        assert_eq!(
+            call(&app, Some("a")).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        tokio::time::sleep(WINDOW).await;
+        for _ in 0..3 {
+            assert_eq!(call(&app, Some("a")).await.status(), StatusCode::OK);
+        }
+        assert_eq!(
+            call(&app, Some("a")).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_limit_requests_without_key() {
+        let app = api_key_app();
+
+        for _ in 0..5 {
+            assert_eq!(call(&app, None).await.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_on_client_ip() {
+        let app = app(RateLimitLayer::new(1, WINDOW));
+        let call = |ip: [u8; 4], forwarded: bool| {
+            let app = app.clone();
+            async move {
+                let ip = IpAddr::V4(Ipv4Addr::from(ip));
+                let mut request = Request::builder().uri("/").body(Body::empty()).unwrap();
+                if forwarded {
+                    request.extensions_mut().insert(RemoteIP::Forwarded(ip));
+                    request
+                        .extensions_mut()
+                        .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 80))));
+                } else {
+                    request
+                        .extensions_mut()
+                        .insert(ConnectInfo(SocketAddr::new(ip, 80)));
+                }
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(call([1, 1, 1, 1], false).await, StatusCode::OK);
+        assert_eq!(
+            call([1, 1, 1, 1], true).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        // the forwarded IP, not the proxy address
+        assert_eq!(call([2, 2, 2, 2], true).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn memory_store_refills_one_request_per_interval() {
+        let store = MemoryStore::default();
+        let quota = Quota::new(2, Duration::from_secs(60));
+
+        assert_eq!(store.take("a", quota).await.unwrap(), None);
+        assert_eq!(store.take("a", quota).await.unwrap(), None);
+        let wait = store.take("a", quota).await.unwrap().unwrap();
+        assert!(wait <= quota.interval());
+        assert!(wait > quota.interval() - Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn memory_store_holds_at_most_max_keys() {
+        let store = MemoryStore::default().max_keys(4);
+        let quota = Quota::new(1, Duration::from_secs(60));
+
+        for key in ["a", "b", "c", "d"] {
+            assert_eq!(store.take(key, quota).await.unwrap(), None);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        // a known key does not evict
+        assert!(store.take("d", quota).await.unwrap().is_some());
+        assert_eq!(store.full_at.lock().unwrap().len(), 4);
+
+        // a new key drops the buckets closest to full, down to half
+        assert_eq!(store.take("e", quota).await.unwrap(), None);
+        assert_eq!(store.full_at.lock().unwrap().len(), 3);
+        assert!(store.take("c", quota).await.unwrap().is_some());
+        assert!(store.take("d", quota).await.unwrap().is_some());
+        assert!(store.take("e", quota).await.unwrap().is_some());
+    }
+}
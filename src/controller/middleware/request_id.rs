@@ -3,17 +3,31 @@
 //! generated or sanitized if already present in the request.
 //!
 //! This can be useful for tracking requests across services, logging, and
-//! debugging.
+//! debugging. The logger middleware adds the ID to the span of the request,
+//! and handlers can read it with the [`LocoRequestId`] extractor:
+//!
+//! ```rust
+//! use loco_rs::{controller::middleware::request_id::LocoRequestId, prelude::*};
+//!
+//! async fn show(request_id: LocoRequestId) -> Result<Response> {
+//!     format::text(request_id.get())
+//! }
+//! ```
 
+use async_trait::async_trait;
 use axum::{
-    extract::Request, http::HeaderValue, middleware::Next, response::Response, Router as AXRouter,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderValue},
+    middleware::Next,
+    response::Response,
+    Router as AXRouter,
 };
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{app::AppContext, controller::middleware::MiddlewareLayer, Result};
+use crate::{app::AppContext, controller::middleware::MiddlewareLayer, Error, Result};
 
 const X_REQUEST_ID: &str = "x-request-id";
 const MAX_LEN: usize = 255;
@@ -73,6 +87,20 @@ impl LocoRequestId {
     }
 }
 
+#[async_trait]
+impl<S> FromRequestParts<S> for LocoRequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Self>().cloned().ok_or_else(|| {
+            Error::Message("request ID is missing, is the request_id middleware enabled?".into())
+        })
+    }
+}
+
 /// Middleware function to ensure or generate a unique request ID.
 ///
 /// This function intercepts requests, checks for the presence of the
@@ -114,10 +142,63 @@ fn make_request_id(maybe_request_id: Option<HeaderValue>) -> String {
 
 #[cfg(test)]
 mod tests {
-    use axum::http::HeaderValue;
+    use axum::{body::Body, http::HeaderValue, routing::get, Router};
     use insta::assert_debug_snapshot;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::tests_cfg;
+
+    async fn call(request_id: Option<&str>) -> (String, String) {
+        let app = Router::new().route(
+            "/",
+            get(|request_id: LocoRequestId| async move { request_id.get().to_string() }),
+        );
+        let app = RequestId::default()
+            .apply(app)
+            .expect("apply middleware")
+            .with_state(tests_cfg::app::get_app_context().await);
+
+        let mut request = Request::builder().uri("/");
+        if let Some(request_id) = request_id {
+            request = request.header(X_REQUEST_ID, request_id);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let header = response.headers()[X_REQUEST_ID]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn generated_request_id_round_trips() {
+        let (header, extracted) = call(None).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(header, extracted);
+    }
 
-    use super::make_request_id;
+    #[tokio::test]
+    async fn preserves_client_request_id() {
+        assert_eq!(
+            call(Some("client-id-1")).await,
+            ("client-id-1".to_string(), "client-id-1".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_request_id() {
+        let mut parts = Request::builder().body(()).unwrap().into_parts().0;
+        assert!(LocoRequestId::from_request_parts(&mut parts, &())
+            .await
+            .is_err());
+    }
 
     #[test]
     fn create_or_fetch_request_id() {
@@ -0,0 +1,314 @@
+//! Required Headers Middleware
+//!
+//! Some routes cannot be served without specific request headers, such as
+//! `X-Tenant-Id` or `Idempotency-Key`. This middleware validates a configured
+//! set of required headers per route and rejects the request with a
+//! `400 Bad Request` naming the offending header before the handler runs. A
+//! header can optionally be validated against a format (for example a UUID).
+//!
+//! ```yaml
+//! middlewares:
+//!   required_headers:
+//!     enable: true
+//!     rules:
+//!       - path: /api/tenants
+//!         headers:
+//!           - name: x-tenant-id
+//!             format: uuid
+//!       - headers:
+//!           - name: x-client-version
+//!             format:
+//!               regex: ^\d+\.\d+\.\d+$
+//! ```
+//!
+//! A rule without a `path` applies to every route. A rule `path` matches the
+//! route itself and every route nested below it.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router as AXRouter,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::AppContext,
+    controller::{middleware::MiddlewareLayer, ErrorDetail},
+    Error, Result,
+};
+
+/// Required headers middleware configuration
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct RequiredHeaders {
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// A set of headers required by the routes under `path`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rule {
+    /// Route path the rule applies to. When not set, the rule applies to all
+    /// routes.
+    #[serde(default)]
+    pub path: Option<String>,
+    pub headers: Vec<RequiredHeader>,
+}
+
+/// A single required header.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RequiredHeader {
+    pub name: String,
+    /// When set, the header value must match this format.
+    #[serde(default)]
+    pub format: Option<HeaderFormat>,
+}
+
+/// Supported formats for a required header value.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderFormat {
+    /// The value must be a valid UUID.
+    Uuid,
+    /// The value must match the given regular expression.
+    Regex(String),
+}
+
+#[derive(Debug)]
+enum Check {
+    Uuid,
+    Regex(Regex),
+}
+
+impl Check {
+    fn is_valid(&self, value: &str) -> bool {
+        match self {
+            Self::Uuid => uuid::Uuid::parse_str(value).is_ok(),
+            Self::Regex(re) => re.is_match(value),
+        }
+    }
+
+    const fn describe(&self) -> &'static str {
+        match self {
+            Self::Uuid => "must be a valid UUID",
+            Self::Regex(_) => "does not match the expected format",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    path: Option<String>,
+    headers: Vec<(HeaderName, Option<Check>)>,
+}
+
+impl CompiledRule {
+    fn matches(&self, path: &str) -> bool {
+        self.path.as_deref().map_or(true, |prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+impl RequiredHeaders {
+    fn compile(&self) -> Result<Vec<CompiledRule>> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let headers = rule
+                    .headers
+                    .iter()
+                    .map(|header| {
+                        let name = HeaderName::try_from(header.name.as_str())?;
+                        let check = match &header.format {
+                            None => None,
+                            Some(HeaderFormat::Uuid) => Some(Check::Uuid),
+                            Some(HeaderFormat::Regex(re)) => {
+                                Some(Check::Regex(Regex::new(re).map_err(|err| {
+                                    Error::Message(format!(
+                                        "invalid format for required header `{}`: {err}",
+                                        header.name
+                                    ))
+                                })?))
+                            }
+                        };
+                        Ok((name, check))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(CompiledRule {
+                    path: rule.path.clone(),
+                    headers,
+                })
+            })
+            .collect()
+    }
+}
+
+impl MiddlewareLayer for RequiredHeaders {
+    /// Returns the name of the middleware
+    fn name(&self) -> &'static str {
+        "required_headers"
+    }
+
+    /// Returns whether the middleware is enabled or not
+    fn is_enabled(&self) -> bool {
+        self.enable && !self.rules.is_empty()
+    }
+
+    fn config(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    /// Applies the required headers middleware to the application router.
+    ///
+    /// # Errors
+    /// when a configured header name or format regex is invalid.
+    fn apply(&self, app: AXRouter<AppContext>) -> Result<AXRouter<AppContext>> {
+        let rules = Arc::new(self.compile()?);
+        Ok(app.layer(axum::middleware::from_fn_with_state(
+            rules,
+            required_headers_middleware,
+        )))
+    }
+}
+
+/// Validates the request headers against every rule matching the request
+/// path, returning a description of the first violation.
+fn validate(rules: &[CompiledRule], path: &str, headers: &HeaderMap) -> Option<String> {
+    rules
+        .iter()
+        .filter(|rule| rule.matches(path))
+        .flat_map(|rule| rule.headers.iter())
+        .find_map(|(name, check)| match headers.get(name) {
+            None => Some(format!("missing required header `{name}`")),
+            Some(value) => {
+                let check = check.as_ref()?;
+                match value.to_str() {
+                    Ok(value) if check.is_valid(value) => None,
+                    _ => Some(format!("header `{name}` {}", check.describe())),
+                }
+            }
+        })
+}
+
+async fn required_headers_middleware(
+    State(rules): State<Arc<Vec<CompiledRule>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(description) = validate(&rules, request.uri().path(), request.headers()) {
+        return Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("bad_request".to_string(), description),
+        )
+        .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::tests_cfg;
+
+    fn middleware() -> RequiredHeaders {
+        serde_json::from_value(serde_json::json!({
+            "enable": true,
+            "rules": [
+                {
+                    "path": "/tenants",
+                    "headers": [{"name": "x-tenant-id", "format": "uuid"}]
+                },
+                {
+                    "headers": [{"name": "x-client-version", "format": {"regex": r"^\d+$"}}]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    async fn call(headers: &[(&str, &str)], uri: &str) -> (StatusCode, String) {
+        let app = Router::new()
+            .route("/tenants", get(|| async { "tenants" }))
+            .route("/status", get(|| async { "status" }));
+        let app = middleware()
+            .apply(app)
+            .expect("apply middleware")
+            .with_state(tests_cfg::app::get_app_context().await);
+
+        let mut req = Request::builder().uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let response = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn passes_with_valid_headers() {
+        let (status, body) = call(
+            &[
+                ("x-tenant-id", "67e55044-10b1-426f-9247-bb680e5fe0c8"),
+                ("x-client-version", "3"),
+            ],
+            "/tenants",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "tenants");
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_header() {
+        let (status, body) = call(&[("x-client-version", "3")], "/tenants").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("missing required header `x-tenant-id`"));
+
+        let (status, body) = call(&[], "/status").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("missing required header `x-client-version`"));
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_header() {
+        let (status, body) = call(
+            &[("x-tenant-id", "not-a-uuid"), ("x-client-version", "3")],
+            "/tenants",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("header `x-tenant-id` must be a valid UUID"));
+
+        let (status, body) = call(&[("x-client-version", "v3")], "/status").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("header `x-client-version` does not match the expected format"));
+    }
+
+    #[tokio::test]
+    async fn rule_applies_only_to_its_path() {
+        let (status, _) = call(&[("x-client-version", "3")], "/status").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn should_be_disabled() {
+        let middleware = RequiredHeaders::default();
+        assert!(!middleware.is_enabled());
+    }
+}
@@ -0,0 +1,264 @@
+//! Response Schema Validation Middleware
+//!
+//! Catches handlers whose JSON body drifts away from the DTO they declare
+//! (and document, for example in an OpenAPI spec). The layer deserializes the
+//! serialized response back into the declared type and logs a warning when it
+//! does not fit, such as when a required field is missing. Only successful
+//! responses are checked, as error responses have their own body.
+//!
+//! Validation only runs in debug builds. In release builds the layer passes
+//! responses through untouched, so it can stay on routes unconditionally.
+//!
+//! ```rust
+//! use loco_rs::{controller::middleware::response_schema, prelude::*};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Note {
+//!     id: i32,
+//!     title: String,
+//! }
+//!
+//! async fn show() -> Result<Response> {
+//!     format::json(Note { id: 1, title: "loco".to_string() })
+//! }
+//!
+//! Routes::new().add("/notes/:id", get(show).layer(response_schema::validate::<Note>()));
+//! ```
+
+use std::{
+    marker::PhantomData,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header::CONTENT_TYPE, response::Parts},
+    response::Response,
+};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use tower::{Layer, Service};
+
+use crate::controller::MatchedRoute;
+
+/// Returns a layer validating JSON responses against `T` in debug builds.
+#[must_use]
+pub fn validate<T: DeserializeOwned>() -> ResponseSchemaLayer<T> {
+    ResponseSchemaLayer {
+        schema: PhantomData,
+    }
+}
+
+/// Layer validating JSON responses against the schema `T`.
+pub struct ResponseSchemaLayer<T> {
+    schema: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for ResponseSchemaLayer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            schema: PhantomData,
+        }
+    }
+}
+
+impl<S, T> Layer<S> for ResponseSchemaLayer<T> {
+    type Service = ResponseSchemaMiddleware<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseSchemaMiddleware {
+            inner,
+            schema: PhantomData,
+        }
+    }
+}
+
+/// Response Schema Validation Middleware
+#[must_use]
+pub struct ResponseSchemaMiddleware<S, T> {
+    inner: S,
+    schema: PhantomData<fn() -> T>,
+}
+
+impl<S: Clone, T> Clone for ResponseSchemaMiddleware<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            schema: PhantomData,
+        }
+    }
+}
+
+impl<S, T> Service<Request> for ResponseSchemaMiddleware<S, T>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+    T: DeserializeOwned + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let route = req.extensions().get::<MatchedRoute>().map_or_else(
+            || req.uri().path().to_string(),
+            std::string::ToString::to_string,
+        );
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let response = future.await?;
+            if !cfg!(debug_assertions) || !response.status().is_success() || !is_json(&response) {
+                return Ok(response);
+            }
+
+            let (parts, body) = response.into_parts();
+            let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    tracing::error!(route, error = %err, "could not read response body");
+                    return Ok(Response::from_parts(parts, Body::empty()));
+                }
+            };
+            check::<T>(&route, &parts, &bytes);
+            Ok(Response::from_parts(parts, Body::from(bytes)))
+        })
+    }
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+fn check<T: DeserializeOwned>(route: &str, parts: &Parts, bytes: &Bytes) {
+    if let Err(err) = serde_json::from_slice::<T>(bytes) {
+        tracing::warn!(
+            route,
+            status = parts.status.as_u16(),
+            schema = std::any::type_name::<T>(),
+            error = %err,
+            "response body does not match its declared schema"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{routing::get, Router};
+    use serde::{Deserialize, Serialize};
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Note {
+        id: i32,
+        title: String,
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    async fn call(body: serde_json::Value) -> (String, String) {
+        call_route(Router::new().route(
+            "/notes",
+            get(move || async move { axum::Json(body) }).layer(validate::<Note>()),
+        ))
+        .await
+    }
+
+    async fn call_route(app: Router) -> (String, String) {
+        let logs = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/notes")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        (String::from_utf8(body.to_vec()).unwrap(), logs)
+    }
+
+    #[tokio::test]
+    async fn warns_on_missing_field() {
+        let (body, logs) = call(serde_json::json!({"id": 1})).await;
+
+        assert_eq!(body, r#"{"id":1}"#);
+        assert!(logs.contains("response body does not match its declared schema"));
+        assert!(logs.contains("missing field `title`"));
+    }
+
+    #[tokio::test]
+    async fn passes_matching_body() {
+        let (body, logs) = call(serde_json::json!({"id": 1, "title": "loco"})).await;
+
+        assert_eq!(body, r#"{"id":1,"title":"loco"}"#);
+        assert!(logs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_error_responses() {
+        let (body, logs) = call_route(
+            Router::new().route(
+                "/notes",
+                get(|| async { Err::<axum::Json<Note>, _>(crate::Error::NotFound) })
+                    .layer(validate::<Note>()),
+            ),
+        )
+        .await;
+
+        assert!(body.contains("not_found"), "{body}");
+        assert!(
+            !logs.contains("response body does not match its declared schema"),
+            "{logs}"
+        );
+    }
+}
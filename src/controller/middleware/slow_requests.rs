@@ -0,0 +1,263 @@
+//! Slow Requests Middleware
+//!
+//! Times every request and reports the ones taking longer than a threshold:
+//! they are logged as a warning and handed to the hook of the middleware, set
+//! with [`SlowRequests::on_slow_request`], to emit a custom alert or metric.
+//! The hook is spawned, so it never delays the response.
+//!
+//! ```yaml
+//! middlewares:
+//!   slow_requests:
+//!     enable: true
+//!     # threshold in milliseconds
+//!     threshold: 500
+//! ```
+//!
+//! The hook is set on the configured middleware, for example in
+//! `Hooks::after_context`:
+//!
+//! ```rust
+//! use loco_rs::{controller::middleware::slow_requests::SlowRequest, prelude::*};
+//!
+//! async fn after_context(mut ctx: AppContext) -> Result<AppContext> {
+//!     let slow_requests = ctx.config.server.middlewares.slow_requests.clone();
+//!     ctx.config.server.middlewares.slow_requests =
+//!         slow_requests.on_slow_request(|request: SlowRequest| async move {
+//!             tracing::error!(route = request.route, "slow endpoint");
+//!         });
+//!     Ok(ctx)
+//! }
+//! ```
+
+use std::{
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::Response,
+    Router as AXRouter,
+};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::AppContext, controller::middleware::MiddlewareLayer, Result};
+
+/// The route reported for requests no route matched, so that arbitrary paths
+/// do not become labels.
+pub const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// Slow requests middleware configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlowRequests {
+    #[serde(default)]
+    pub enable: bool,
+    /// Duration in milliseconds after which a request is reported
+    #[serde(default = "default_threshold")]
+    pub threshold: u64,
+    /// Called with every slow request, set by the app rather than the
+    /// configuration.
+    #[serde(skip)]
+    pub hook: Option<SlowRequestHook>,
+}
+
+impl Default for SlowRequests {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            threshold: default_threshold(),
+            hook: None,
+        }
+    }
+}
+
+const fn default_threshold() -> u64 {
+    1000
+}
+
+impl SlowRequests {
+    /// Sets the hook called for every slow request.
+    #[must_use]
+    pub fn on_slow_request<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(SlowRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hook = Some(SlowRequestHook(Arc::new(move |request| {
+            Box::pin(hook(request))
+        })));
+        self
+    }
+}
+
+/// A request that took longer than the threshold.
+#[derive(Debug, Clone)]
+pub struct SlowRequest {
+    /// The route pattern that matched, such as `/notes/:id`, or
+    /// [`UNMATCHED_ROUTE`] when no route matched.
+    pub route: String,
+    pub method: Method,
+    pub duration: Duration,
+    pub status: StatusCode,
+}
+
+/// The hook of [`SlowRequests`], see [`SlowRequests::on_slow_request`].
+#[derive(Clone)]
+pub struct SlowRequestHook(Arc<dyn Fn(SlowRequest) -> BoxFuture<'static, ()> + Send + Sync>);
+
+impl fmt::Debug for SlowRequestHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SlowRequestHook")
+    }
+}
+
+impl MiddlewareLayer for SlowRequests {
+    /// Returns the name of the middleware
+    fn name(&self) -> &'static str {
+        "slow_requests"
+    }
+
+    /// Returns whether the middleware is enabled or not
+    fn is_enabled(&self) -> bool {
+        self.enable
+    }
+
+    fn config(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    /// Applies the slow requests middleware to the application router.
+    fn apply(&self, app: AXRouter<AppContext>) -> Result<AXRouter<AppContext>> {
+        Ok(app.layer(axum::middleware::from_fn_with_state(
+            (Duration::from_millis(self.threshold), self.hook.clone()),
+            slow_requests_middleware,
+        )))
+    }
+}
+
+async fn slow_requests_middleware(
+    State((threshold, hook)): State<(Duration, Option<SlowRequestHook>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+    let method = request.method().clone();
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let duration = started.elapsed();
+
+    if duration > threshold {
+        let slow = SlowRequest {
+            route,
+            method,
+            duration,
+            status: response.status(),
+        };
+        tracing::warn!(
+            route = slow.route,
+            method = %slow.method,
+            status = slow.status.as_u16(),
+            duration_ms = slow.duration.as_millis(),
+            "slow request"
+        );
+        if let Some(SlowRequestHook(hook)) = hook {
+            tokio::spawn(hook(slow));
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::tests_cfg;
+
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        "slow"
+    }
+
+    /// Calls `uris` on an app reporting its slow requests to its own hook.
+    async fn reported(uris: &[&str]) -> Vec<SlowRequest> {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reported.clone();
+        let middleware = SlowRequests {
+            enable: true,
+            threshold: 20,
+            ..Default::default()
+        }
+        .on_slow_request(move |request| {
+            let recorded = recorded.clone();
+            async move { recorded.lock().unwrap().push(request) }
+        });
+
+        let app = Router::new()
+            .route("/fast", get(|| async { "fast" }))
+            .route("/slow/:id", get(slow))
+            .fallback(slow);
+        let app = middleware
+            .apply(app)
+            .expect("apply middleware")
+            .with_state(tests_cfg::app::get_app_context().await);
+
+        for uri in uris {
+            app.clone()
+                .oneshot(Request::builder().uri(*uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+        }
+        // let the spawned hook run
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let reported = reported.lock().unwrap().clone();
+        reported
+    }
+
+    #[tokio::test]
+    async fn hook_fires_only_for_slow_requests() {
+        let reported = reported(&["/fast", "/slow/1"]).await;
+
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].route, "/slow/:id");
+        assert_eq!(reported[0].method, Method::GET);
+        assert_eq!(reported[0].status, StatusCode::OK);
+        assert!(reported[0].duration >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn each_app_has_its_own_hook() {
+        let (first, second) = tokio::join!(reported(&["/slow/1"]), reported(&["/slow/2"]));
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reports_unmatched_routes_by_a_placeholder() {
+        let reported = reported(&["/missing/1"]).await;
+
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].route, UNMATCHED_ROUTE);
+    }
+
+    #[test]
+    fn should_be_disabled() {
+        let middleware = SlowRequests::default();
+        assert!(!middleware.is_enabled());
+    }
+}
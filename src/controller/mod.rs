@@ -78,23 +78,37 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use colored::Colorize;
-pub use routes::Routes;
+pub use matched_route::MatchedRoute;
+pub use middleware::cors::Cors;
+pub use route_table::RouteTable;
+pub use routes::{RouteInfo, Routes};
 use serde::Serialize;
+pub use valid_json::ValidJson;
 
 use crate::{errors::Error, Result};
 
 mod app_routes;
 mod backtrace;
+#[cfg(feature = "with-db")]
+pub mod batch;
 #[cfg(feature = "channels")]
 pub mod channels;
+pub mod codec;
+pub mod cursor;
 mod describe;
 pub mod format;
 #[cfg(feature = "with-db")]
 mod health;
+mod matched_route;
 pub mod middleware;
+pub mod multipart;
 mod ping;
+mod route_table;
 mod routes;
+pub mod signed_cookie;
+mod valid_json;
 pub mod views;
+pub mod webhook;
 
 /// Create an unauthorized error with a specified message.
 ///
@@ -146,6 +160,9 @@ pub struct ErrorDetail {
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Details of the error, such as the field errors of a validation error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<serde_json::Value>,
 }
 
 impl ErrorDetail {
@@ -155,6 +172,7 @@ impl ErrorDetail {
         Self {
             error: Some(error.into()),
             description: Some(description.into()),
+            errors: None,
         }
     }
 
@@ -164,8 +182,16 @@ impl ErrorDetail {
         Self {
             error: Some(error.into()),
             description: None,
+            errors: None,
         }
     }
+
+    /// Adds details of the error, such as field errors.
+    #[must_use]
+    pub fn with_errors(mut self, errors: serde_json::Value) -> Self {
+        self.errors = Some(errors);
+        self
+    }
 }
 
 #[derive(Debug, FromRequest)]
@@ -0,0 +1,322 @@
+//! Multipart forms.
+//!
+//! The [`MultipartForm`] extractor reads a `multipart/form-data` body, such
+//! as a form with file inputs, into its text fields and files. The parts are
+//! buffered in memory, so their size is limited, per part and in total, by
+//! the [`MultipartLimits`] of the route, and uploads over a limit are
+//! rejected with `413 Payload Too Large`. The `limit_payload` middleware
+//! still limits the size of the whole body.
+//!
+//! ```rust
+//! use axum::Extension;
+//! use loco_rs::{
+//!     controller::{
+//!         bad_request,
+//!         multipart::{MultipartForm, MultipartLimits},
+//!     },
+//!     prelude::*,
+//! };
+//!
+//! async fn upload(form: MultipartForm) -> Result<Response> {
+//!     let title = form.text("title").unwrap_or("untitled");
+//!     let Some(file) = form.file("avatar") else {
+//!         return bad_request("missing avatar");
+//!     };
+//!     format::text(&format!("{title}: {} bytes", file.bytes.len()))
+//! }
+//!
+//! Routes::new()
+//!     .add("/upload", post(upload))
+//!     .layer(Extension(MultipartLimits::new(1024 * 1024, 5 * 1024 * 1024)));
+//! ```
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequest, Multipart, Request},
+    http::StatusCode,
+};
+use bytes::{Bytes, BytesMut};
+
+use crate::{controller::ErrorDetail, Error, Result};
+
+/// The size limits of a [`MultipartForm`], set for a route with an
+/// `Extension` layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultipartLimits {
+    /// The maximum size of a text field or file, in bytes.
+    pub field: usize,
+    /// The maximum size of all the text fields and files, in bytes.
+    pub total: usize,
+}
+
+impl MultipartLimits {
+    #[must_use]
+    pub const fn new(field: usize, total: usize) -> Self {
+        Self { field, total }
+    }
+}
+
+impl Default for MultipartLimits {
+    /// The default body limit of the `limit_payload` middleware.
+    fn default() -> Self {
+        Self::new(2_000_000, 2_000_000)
+    }
+}
+
+/// A file of a [`MultipartForm`].
+#[derive(Debug, Clone)]
+pub struct FilePart {
+    /// The name of the form field.
+    pub name: String,
+    /// The name of the file on the client.
+    pub file_name: String,
+    /// The content type sent by the client, which is not checked against the
+    /// content.
+    pub content_type: Option<String>,
+    pub bytes: Bytes,
+}
+
+/// A `multipart/form-data` body, split into text fields and files.
+#[derive(Debug, Clone, Default)]
+pub struct MultipartForm {
+    fields: Vec<(String, String)>,
+    files: Vec<FilePart>,
+}
+
+impl MultipartForm {
+    /// The value of the first text field named `name`.
+    #[must_use]
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The text fields, as `(name, value)` pairs in the order of the form.
+    #[must_use]
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// The first file of the field `name`.
+    #[must_use]
+    pub fn file(&self, name: &str) -> Option<&FilePart> {
+        self.files.iter().find(|file| file.name == name)
+    }
+
+    /// The files, in the order of the form.
+    #[must_use]
+    pub fn files(&self) -> &[FilePart] {
+        &self.files
+    }
+
+    /// Reads all the parts of `multipart`, within `limits`.
+    ///
+    /// # Errors
+    /// When the body is not a valid multipart body, a text field is not
+    /// UTF-8, or a part is over the limits.
+    pub async fn read(mut multipart: Multipart, limits: MultipartLimits) -> Result<Self> {
+        let mut form = Self::default();
+        let mut total = 0;
+        while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+            let name = field.name().unwrap_or_default().to_string();
+            let file_name = field.file_name().map(ToString::to_string);
+            let content_type = field.content_type().map(ToString::to_string);
+
+            let mut bytes = BytesMut::new();
+            while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                total += chunk.len();
+                if bytes.len() + chunk.len() > limits.field {
+                    return Err(too_large(&format!(
+                        "field `{name}` is over {} bytes",
+                        limits.field
+                    )));
+                }
+                if total > limits.total {
+                    return Err(too_large(&format!("form is over {} bytes", limits.total)));
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+
+            match file_name {
+                Some(file_name) => form.files.push(FilePart {
+                    name,
+                    file_name,
+                    content_type,
+                    bytes: bytes.freeze(),
+                }),
+                None => form.fields.push((
+                    name,
+                    String::from_utf8(bytes.to_vec())
+                        .map_err(|err| Error::BadRequest(err.to_string()))?,
+                )),
+            }
+        }
+        Ok(form)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for MultipartForm
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self> {
+        let limits = req
+            .extensions()
+            .get::<MultipartLimits>()
+            .copied()
+            .unwrap_or_default();
+        let multipart = Multipart::from_request(req, state)
+            .await
+            .map_err(|rejection| Error::BadRequest(rejection.body_text()))?;
+        Self::read(multipart, limits).await
+    }
+}
+
+fn too_large(description: &str) -> Error {
+    Error::CustomError(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        ErrorDetail::new("payload_too_large", description),
+    )
+}
+
+/// Bodies over the body limit fail while reading a part.
+fn multipart_error(err: axum::extract::multipart::MultipartError) -> Error {
+    if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        too_large(&err.body_text())
+    } else {
+        Error::BadRequest(err.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, extract::DefaultBodyLimit, routing::post, Extension, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const BOUNDARY: &str = "loco-boundary";
+
+    fn body(parts: &[(&str, Option<&str>, &str)]) -> String {
+        let mut body = String::new();
+        for (name, file_name, value) in parts {
+            body.push_str(&format!("--{BOUNDARY}\r\n"));
+            match file_name {
+                Some(file_name) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; \
+                     filename=\"{file_name}\"\r\nContent-Type: text/plain\r\n"
+                )),
+                None => {
+                    body.push_str(&format!(
+                        "Content-Disposition: form-data; name=\"{name}\"\r\n"
+                    ));
+                }
+            }
+            body.push_str(&format!("\r\n{value}\r\n"));
+        }
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+        body
+    }
+
+    async fn upload(form: MultipartForm) -> String {
+        let file = form.file("notes").unwrap();
+        format!(
+            "{} {} {:?} {}",
+            form.text("title").unwrap(),
+            file.file_name,
+            file.content_type,
+            String::from_utf8_lossy(&file.bytes)
+        )
+    }
+
+    async fn call(app: Router, body: String) -> (StatusCode, String) {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header(
+                        "content-type",
+                        format!("multipart/form-data; boundary={BOUNDARY}"),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn can_read_fields_and_files() {
+        let app = Router::new().route("/", post(upload));
+        let body = body(&[
+            ("title", None, "loco"),
+            ("notes", Some("notes.txt"), "hello"),
+        ]);
+
+        assert_eq!(
+            call(app, body).await,
+            (
+                StatusCode::OK,
+                "loco notes.txt Some(\"text/plain\") hello".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_parts_over_the_limits() {
+        let app = || {
+            Router::new()
+                .route("/", post(upload))
+                .layer(Extension(MultipartLimits::new(8, 10)))
+        };
+
+        let (status, _) = call(
+            app(),
+            body(&[("title", None, "loco"), ("notes", Some("a.txt"), "hello")]),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = call(app(), body(&[("notes", Some("a.txt"), "over the limit")])).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (status, _) = call(
+            app(),
+            body(&[
+                ("title", None, "loco"),
+                ("notes", Some("a.txt"), "12345678"),
+            ]),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_the_body_limit() {
+        let app = Router::new()
+            .route("/", post(upload))
+            .layer(DefaultBodyLimit::max(16));
+
+        let (status, _) = call(app, body(&[("notes", Some("a.txt"), "hello")])).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_forms() {
+        let app = Router::new().route("/", post(upload));
+
+        let (status, _) = call(app, "not a form".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
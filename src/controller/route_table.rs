@@ -0,0 +1,215 @@
+//! Reverse URL generation for named routes.
+//!
+//! Tag a handler with [`crate::controller::Routes::name`] and build links to
+//! it with [`RouteTable::url_for`], instead of hardcoding its path. The table
+//! is available to every handler through the [`RouteTable`] extractor.
+//!
+//! ```rust
+//! use loco_rs::prelude::*;
+//!
+//! async fn show(Path(id): Path<i32>) -> Result<Response> {
+//!     format::empty()
+//! }
+//!
+//! async fn create(routes: RouteTable) -> Result<Response> {
+//!     let id = 5;
+//!     format::json(routes.url_for("notes.show", &[("id", &id.to_string())])?)
+//! }
+//!
+//! Routes::new()
+//!     .prefix("notes")
+//!     .add("/:id", get(show))
+//!     .name("notes.show")
+//!     .add("/", post(create));
+//! ```
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::{Error, Result};
+
+/// The characters percent-encoded in a `*param`: the ones that are not
+/// allowed in a path, or would end it.
+const PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// The characters percent-encoded in a `:param`, which is a single segment.
+const SEGMENT: &AsciiSet = &PATH.add(b'/');
+
+/// The URI patterns of the named routes, such as `notes.show` to
+/// `/notes/:id`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteTable(Arc<BTreeMap<String, String>>);
+
+impl RouteTable {
+    pub(crate) fn new(routes: BTreeMap<String, String>) -> Self {
+        Self(Arc::new(routes))
+    }
+
+    /// Returns the URI pattern of a named route.
+    #[must_use]
+    pub fn pattern(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Builds the URL of a named route, substituting its `:param` and
+    /// `*param` segments with `params`. The values are percent-encoded,
+    /// keeping the `/` of a `*param` as separators.
+    ///
+    /// # Errors
+    /// When there is no route with that name, or a param of the route is
+    /// missing.
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String> {
+        let pattern = self
+            .pattern(name)
+            .ok_or_else(|| Error::Message(format!("no route named `{name}`")))?;
+
+        pattern
+            .split('/')
+            .map(|segment| {
+                let (param, set) = if let Some(param) = segment.strip_prefix(':') {
+                    (param, SEGMENT)
+                } else if let Some(param) = segment.strip_prefix('*') {
+                    (param, PATH)
+                } else {
+                    return Ok(segment.to_string());
+                };
+                params
+                    .iter()
+                    .find(|(key, _)| *key == param)
+                    .map(|(_, value)| utf8_percent_encode(value, set).to_string())
+                    .ok_or_else(|| {
+                        Error::Message(format!("missing param `{param}` for route `{name}`"))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|segments| segments.join("/"))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RouteTable
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Self>().cloned().ok_or_else(|| {
+            tracing::error!(
+                "the route table is only available on routes added through `AppRoutes`"
+            );
+            Error::InternalServerError
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::get;
+
+    use super::*;
+    use crate::controller::{AppRoutes, Routes};
+
+    async fn action() -> &'static str {
+        "loco"
+    }
+
+    fn table() -> RouteTable {
+        AppRoutes::empty()
+            .add_route(
+                Routes::new()
+                    .prefix("notes")
+                    .add("/", get(action))
+                    .name("notes.index")
+                    .add("/:id", get(action))
+                    .name("notes.show")
+                    .add("/:id/files/*path", get(action))
+                    .name("notes.file")
+                    .add("/unnamed", get(action)),
+            )
+            .route_table()
+            .unwrap()
+    }
+
+    #[test]
+    fn can_build_url_for_named_route() {
+        let routes = table();
+
+        assert_eq!(routes.url_for("notes.index", &[]).unwrap(), "/notes");
+        assert_eq!(
+            routes.url_for("notes.show", &[("id", "5")]).unwrap(),
+            "/notes/5"
+        );
+        assert_eq!(
+            routes
+                .url_for("notes.file", &[("path", "a/b.txt"), ("id", "5")])
+                .unwrap(),
+            "/notes/5/files/a/b.txt"
+        );
+        assert_eq!(routes.pattern("notes.show"), Some("/notes/:id"));
+    }
+
+    #[test]
+    fn url_for_encodes_params() {
+        let routes = table();
+
+        assert_eq!(
+            routes
+                .url_for("notes.show", &[("id", "a b/c?d#e%")])
+                .unwrap(),
+            "/notes/a%20b%2Fc%3Fd%23e%25"
+        );
+        assert_eq!(
+            routes
+                .url_for("notes.file", &[("id", "5"), ("path", "dir/my file.txt")])
+                .unwrap(),
+            "/notes/5/files/dir/my%20file.txt"
+        );
+        assert_eq!(
+            routes.url_for("notes.show", &[("id", "café")]).unwrap(),
+            "/notes/caf%C3%A9"
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_route_names() {
+        let routes = |second: &str| {
+            AppRoutes::empty()
+                .add_route(
+                    Routes::new()
+                        .add("/notes/:id", get(action))
+                        .name("notes.show")
+                        .add(second, axum::routing::put(action))
+                        .name("notes.show"),
+                )
+                .route_table()
+        };
+
+        assert_eq!(
+            routes("/notes/:id").unwrap().pattern("notes.show"),
+            Some("/notes/:id")
+        );
+        assert!(routes("/notes/:id/edit").is_err());
+    }
+
+    #[test]
+    fn url_for_fails_on_unknown_route_or_missing_param() {
+        let routes = table();
+
+        assert!(routes.url_for("notes.edit", &[("id", "5")]).is_err());
+        assert!(routes.url_for("notes.show", &[]).is_err());
+    }
+}
@@ -1,15 +1,24 @@
 use std::convert::Infallible;
 
-use axum::{extract::Request, response::IntoResponse, routing::Route};
+use axum::{
+    extract::Request,
+    http::{header, Method, StatusCode},
+    response::IntoResponse,
+    routing::Route,
+};
 use tower::{Layer, Service};
 
-use super::describe;
+use super::{app_routes::normalize_uri, describe};
 use crate::app::AppContext;
 #[derive(Clone, Default)]
 pub struct Routes {
     pub prefix: Option<String>,
     pub handlers: Vec<Handler>,
-    // pub version: Option<String>,
+    /// Answer `OPTIONS` for every handler, see
+    /// [`Routes::with_automatic_options`].
+    pub automatic_options: bool,
+    /// Version segment put in front of the prefix, see [`Routes::version`].
+    pub version: Option<String>,
 }
 
 #[derive(Clone, Default)]
@@ -17,6 +26,20 @@ pub struct Handler {
     pub uri: String,
     pub method: axum::routing::MethodRouter<AppContext>,
     pub actions: Vec<axum::http::Method>,
+    /// Name of the route, for building URLs to it with
+    /// [`super::RouteTable::url_for`].
+    pub name: Option<String>,
+}
+
+/// A registered route, as listed by [`Routes::describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    /// The uri with the prefix applied, such as `/notes/:id`.
+    pub uri: String,
+    pub actions: Vec<axum::http::Method>,
+    /// Position of the handler, in the order it was added.
+    pub order: usize,
+    pub name: Option<String>,
 }
 
 impl Routes {
@@ -84,10 +107,72 @@ impl Routes {
             uri: uri.to_owned(),
             actions: describe::method_action(&method),
             method,
+            name: None,
         });
         self
     }
 
+    /// Adding a WebSocket endpoint, answering `GET` upgrade requests. The
+    /// handler receives the [`axum::extract::ws::WebSocketUpgrade`] and the
+    /// app context, and usually returns `ws.on_upgrade(..)`. Requires the `ws`
+    /// feature.
+    ///
+    /// # Example
+    ///
+    /// This example echoes every text message back.
+    ///
+    /// ```rust
+    /// use axum::extract::ws::{Message, WebSocketUpgrade};
+    /// use loco_rs::prelude::*;
+    ///
+    /// async fn echo(ws: WebSocketUpgrade, State(_ctx): State<AppContext>) -> Response {
+    ///     ws.on_upgrade(|mut socket| async move {
+    ///         while let Some(Ok(Message::Text(text))) = socket.recv().await {
+    ///             if socket.send(Message::Text(text)).await.is_err() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     })
+    /// }
+    /// Routes::new().add_ws("/ws", echo);
+    /// ````
+    #[cfg(feature = "ws")]
+    #[must_use]
+    pub fn add_ws<F, Fut, R>(self, uri: &str, handler: F) -> Self
+    where
+        F: FnOnce(axum::extract::ws::WebSocketUpgrade, axum::extract::State<AppContext>) -> Fut
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        Fut: std::future::Future<Output = R> + Send + 'static,
+        R: IntoResponse,
+    {
+        self.add(uri, axum::routing::get(handler))
+    }
+
+    /// Names the most recently added handler, so URLs to it can be built with
+    /// [`super::RouteTable::url_for`]. Does nothing when no handler was added
+    /// yet.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use loco_rs::prelude::*;
+    ///
+    /// async fn show(Path(id): Path<i32>) -> Result<Response> {
+    ///     format::empty()
+    /// }
+    /// Routes::new().prefix("notes").add("/:id", get(show)).name("notes.show");
+    /// ````
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        if let Some(handler) = self.handlers.last_mut() {
+            handler.name = Some(name.to_owned());
+        }
+        self
+    }
+
     /// Set a prefix for the routes. this prefix will be a prefix for all the
     /// routes.
     ///
@@ -116,6 +201,40 @@ impl Routes {
         self
     }
 
+    /// Set a version for the routes, mounted in front of the prefix: `1`
+    /// becomes `/v1`, while a version with a slash, such as `api/v2`, is used
+    /// as it is.
+    ///
+    /// # Example
+    ///
+    /// In the following example the endpoint is HOST/v1/users/_ping.
+    ///
+    /// ```rust
+    /// use loco_rs::prelude::*;
+    ///
+    /// async fn ping() -> Result<Response> {
+    ///     format::json("Ok")
+    /// }
+    /// let routes = Routes::at("users").version("1").add("/_ping", get(ping));
+    /// assert_eq!(routes.describe()[0].uri, "/v1/users/_ping");
+    /// ````
+    #[must_use]
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_owned());
+        self
+    }
+
+    /// The version segment of the uris, see [`Routes::version`].
+    pub(super) fn version_segment(&self) -> Option<String> {
+        self.version.as_ref().map(|version| {
+            if version.contains('/') {
+                version.clone()
+            } else {
+                format!("v{version}")
+            }
+        })
+    }
+
     /// Set a layer for the routes. this layer will be a layer for all the
     /// routes.
     ///
@@ -144,6 +263,8 @@ impl Routes {
     {
         Self {
             prefix: self.prefix,
+            automatic_options: self.automatic_options,
+            version: self.version,
             handlers: self
                 .handlers
                 .iter()
@@ -151,8 +272,138 @@ impl Routes {
                     uri: handler.uri.clone(),
                     actions: handler.actions.clone(),
                     method: handler.method.clone().layer(layer.clone()),
+                    name: handler.name.clone(),
                 })
                 .collect(),
         }
     }
+
+    /// Answer `OPTIONS` requests for every handler without an explicit
+    /// `OPTIONS` one, with an empty `204 No Content` and an `Allow` header
+    /// listing the methods of the route.
+    ///
+    /// `HEAD` needs no handler of its own: axum answers it for every `GET`
+    /// route, running the `GET` handler and dropping the body, so it is listed
+    /// in `Allow` alongside `GET`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use loco_rs::prelude::*;
+    ///
+    /// async fn list() -> Result<Response> {
+    ///     format::json("Ok")
+    /// }
+    /// Routes::at("notes").add("/", get(list).post(list)).with_automatic_options();
+    /// ```
+    #[must_use]
+    pub fn with_automatic_options(mut self) -> Self {
+        self.automatic_options = true;
+        self
+    }
+
+    /// Lists the routes, in the order they were added, with the prefix applied
+    /// to their uri. The prefix of [`super::AppRoutes`] is not known here, see
+    /// [`super::AppRoutes::collect`] for the routes of the whole app.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use loco_rs::prelude::*;
+    ///
+    /// async fn ping() -> Result<Response> {
+    ///     format::json("Ok")
+    /// }
+    /// let routes = Routes::at("status").add("/_ping", get(ping).head(ping));
+    /// let described = routes.describe();
+    /// assert_eq!(described[0].uri, "/status/_ping");
+    /// ```
+    #[must_use]
+    pub fn describe(&self) -> Vec<RouteInfo> {
+        let version = self.version_segment().unwrap_or_default();
+        let prefix = self.prefix.as_deref().unwrap_or_default();
+        self.handlers
+            .iter()
+            .enumerate()
+            .map(|(order, handler)| RouteInfo {
+                uri: normalize_uri(&format!("/{version}/{prefix}/{}", handler.uri)),
+                actions: handler.actions.clone(),
+                order,
+                name: handler.name.clone(),
+            })
+            .collect()
+    }
+
+    /// Set a layer for the most recently added handler only, unlike
+    /// [`Routes::layer`] which layers all the routes.
+    ///
+    /// # Example
+    ///
+    /// In the following example, only the login endpoint gets a timeout.
+    ///
+    /// ```rust
+    /// use loco_rs::prelude::*;
+    /// use tower_http::timeout::TimeoutLayer;
+    /// async fn login() -> Result<Response> {
+    ///     format::json("Ok")
+    /// }
+    /// async fn logout() -> Result<Response> {
+    ///     format::json("Ok")
+    /// }
+    /// Routes::new()
+    ///     .prefix("auth")
+    ///     .add("/login", post(login))
+    ///     .handler_layer(TimeoutLayer::new(std::time::Duration::from_secs(5)))
+    ///     .add("/logout", post(logout));
+    /// ```
+    #[must_use]
+    pub fn handler_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        if let Some(handler) = self.handlers.last_mut() {
+            handler.method = std::mem::take(&mut handler.method).layer(layer);
+        }
+        self
+    }
+}
+
+impl Handler {
+    /// Adds the automatic `OPTIONS` handler, unless one is defined, see
+    /// [`Routes::with_automatic_options`].
+    pub(super) fn with_automatic_options(&self) -> Self {
+        if self.actions.contains(&Method::OPTIONS) {
+            return self.clone();
+        }
+
+        let mut allowed = vec![];
+        for action in &self.actions {
+            allowed.push(action.clone());
+            if *action == Method::GET && !self.actions.contains(&Method::HEAD) {
+                allowed.push(Method::HEAD);
+            }
+        }
+        allowed.push(Method::OPTIONS);
+        let allow = allowed
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut actions = self.actions.clone();
+        actions.push(Method::OPTIONS);
+        Self {
+            uri: self.uri.clone(),
+            method: self
+                .method
+                .clone()
+                .options(move || async move { (StatusCode::NO_CONTENT, [(header::ALLOW, allow)]) }),
+            actions,
+            name: self.name.clone(),
+        }
+    }
 }
@@ -0,0 +1,179 @@
+//! Signed cookies.
+//!
+//! A signed cookie carries its value in the clear, followed by an HMAC of its
+//! name and value, so clients can read it but cannot change it. The HMAC key
+//! is derived from `server.secret_key` for cookies only, so a signature made
+//! for another use of the secret, such as a pagination cursor, is never a
+//! valid cookie signature. Set them with [`RenderBuilder::signed_cookies`] and
+//! read them back with the [`SignedCookies`] extractor, which strips the
+//! signature and rejects tampered values.
+//!
+//! [`RenderBuilder::signed_cookies`]: crate::controller::format::RenderBuilder::signed_cookies
+//!
+//! ```rust
+//! use loco_rs::{controller::signed_cookie::SignedCookies, prelude::*};
+//!
+//! async fn login(State(ctx): State<AppContext>) -> Result<Response> {
+//!     format::render()
+//!         .signed_cookies(&[cookie::Cookie::new("plan", "pro")], ctx.config.server.secret()?)?
+//!         .empty()
+//! }
+//!
+//! async fn dashboard(cookies: SignedCookies) -> Result<Response> {
+//!     let plan = cookies.get("plan")?.unwrap_or_else(|| "free".to_string());
+//!     format::text(&plan)
+//! }
+//! ```
+
+use async_trait::async_trait;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::Mac;
+
+use crate::{
+    app::AppContext,
+    hash::{hmac_sha256, HmacSha256},
+    Error, Result,
+};
+
+/// Labels the key derived from the secret for signing cookies.
+const KEY_LABEL: &[u8] = b"loco signed-cookie";
+
+fn mac(name: &str, value: &str, secret: &str) -> HmacSha256 {
+    let mut key = hmac_sha256(secret.as_bytes());
+    key.update(KEY_LABEL);
+    let mut mac = hmac_sha256(&key.finalize().into_bytes());
+    // the name is signed too, so a value cannot be moved to another cookie
+    mac.update(name.as_bytes());
+    mac.update(b"=");
+    mac.update(value.as_bytes());
+    mac
+}
+
+/// Returns the cookie with its value signed, as `<value>.<signature>`.
+#[must_use]
+pub fn sign(cookie: &Cookie<'_>, secret: &str) -> Cookie<'static> {
+    let signature = URL_SAFE_NO_PAD.encode(
+        mac(cookie.name(), cookie.value(), secret)
+            .finalize()
+            .into_bytes(),
+    );
+    let mut signed = cookie.clone().into_owned();
+    signed.set_value(format!("{}.{signature}", cookie.value()));
+    signed
+}
+
+/// Verifies the signature of a signed cookie value and returns the value
+/// without it, `None` when the value is not signed or was tampered with.
+#[must_use]
+pub fn verify(name: &str, signed_value: &str, secret: &str) -> Option<String> {
+    let (value, signature) = signed_value.rsplit_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    mac(name, value, secret).verify_slice(&signature).ok()?;
+    Some(value.to_string())
+}
+
+/// The cookies of a request, to read signed cookies from.
+pub struct SignedCookies {
+    jar: CookieJar,
+    secret: String,
+}
+
+impl SignedCookies {
+    /// Returns the value of the signed cookie `name`, `None` when the request
+    /// has no such cookie.
+    ///
+    /// # Errors
+    /// When the cookie is not signed or its value was tampered with.
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        self.jar
+            .get(name)
+            .map(|cookie| {
+                verify(name, cookie.value(), &self.secret).ok_or_else(|| {
+                    Error::BadRequest(format!("cookie `{name}` signature does not match"))
+                })
+            })
+            .transpose()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppContext> for SignedCookies {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        ctx: &AppContext,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            jar: CookieJar::from_headers(&parts.headers),
+            secret: ctx.config.server.secret()?.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, Request};
+
+    use super::*;
+    use crate::{controller::format, tests_cfg};
+
+    async fn extract(cookie: &str) -> SignedCookies {
+        let mut ctx = tests_cfg::app::get_app_context().await;
+        ctx.config.server.secret_key = Some("secret".to_string());
+        let (mut parts, ()) = Request::builder()
+            .header(header::COOKIE, cookie)
+            .body(())
+            .unwrap()
+            .into_parts();
+        SignedCookies::from_request_parts(&mut parts, &ctx)
+            .await
+            .unwrap()
+    }
+
+    fn set_cookie(response: &axum::response::Response) -> String {
+        let set_cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        set_cookie.split(';').next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn can_round_trip_signed_cookie() {
+        let response = format::render()
+            .signed_cookies(&[Cookie::new("plan", "pro.annual")], "secret")
+            .unwrap()
+            .empty()
+            .unwrap();
+        let cookie = set_cookie(&response);
+        assert!(cookie.starts_with("plan=pro.annual."));
+
+        let cookies = extract(&format!("{cookie}; theme=dark")).await;
+        assert_eq!(cookies.get("plan").unwrap().as_deref(), Some("pro.annual"));
+        assert_eq!(cookies.get("missing").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_tampered_cookie() {
+        let signed = sign(&Cookie::new("plan", "free"), "secret");
+        let (_, signature) = signed.value().rsplit_once('.').unwrap();
+
+        let cookies = extract(&format!("plan=pro.{signature}; theme=dark")).await;
+        assert!(matches!(cookies.get("plan"), Err(Error::BadRequest(_))));
+        assert!(cookies.get("theme").is_err());
+
+        // a signature is only valid for its own cookie name and secret
+        assert_eq!(verify("tier", signed.value(), "secret"), None);
+        assert_eq!(verify("plan", signed.value(), "other"), None);
+
+        // nor is a signature keyed with the secret itself
+        let mut raw = HmacSha256::new_from_slice(b"secret").unwrap();
+        raw.update(b"plan=free");
+        let raw = URL_SAFE_NO_PAD.encode(raw.finalize().into_bytes());
+        assert_eq!(verify("plan", &format!("free.{raw}"), "secret"), None);
+        assert_eq!(
+            verify("plan", signed.value(), "secret").as_deref(),
+            Some("free")
+        );
+    }
+}
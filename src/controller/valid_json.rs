@@ -0,0 +1,151 @@
+//! Validated JSON bodies.
+//!
+//! [`ValidJson`] deserializes a JSON body like [`Json`], then runs its
+//! [`Validate`] rules, so handlers only see valid params. A body failing
+//! validation is rejected with `422 Unprocessable Entity` and the errors of
+//! each field:
+//!
+//! ```json
+//! {
+//!   "error": "validation_error",
+//!   "description": "The request body is invalid",
+//!   "errors": {
+//!     "title": [{ "code": "length", "message": "title cannot be empty" }]
+//!   }
+//! }
+//! ```
+//!
+//! A body that is not valid JSON for the type is rejected like with
+//! [`Json`].
+//!
+//! ```rust
+//! use loco_rs::prelude::*;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Validate)]
+//! pub struct Params {
+//!     #[validate(length(min = 1, message = "title cannot be empty"))]
+//!     pub title: String,
+//! }
+//!
+//! async fn add(ValidJson(params): ValidJson<Params>) -> Result<Response> {
+//!     format::text(&params.title)
+//! }
+//! ```
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequest, Request},
+    http::StatusCode,
+};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::{
+    controller::{ErrorDetail, Json},
+    validation, Error,
+};
+
+/// A JSON body, deserialized into `T` and validated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        value.validate().map_err(|errors| {
+            Error::CustomError(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorDetail::new("validation_error", "The request body is invalid").with_errors(
+                    serde_json::to_value(validation::field_errors(&errors)).unwrap_or_default(),
+                ),
+            )
+        })?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        response::{IntoResponse, Response},
+        routing::post,
+        Router,
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Params {
+        #[validate(length(min = 1, message = "title cannot be empty"))]
+        title: String,
+        #[validate(range(max = 5))]
+        rating: u8,
+    }
+
+    async fn add(ValidJson(params): ValidJson<Params>) -> Response {
+        format!("{} {}", params.title, params.rating).into_response()
+    }
+
+    async fn call(body: &str) -> (StatusCode, String) {
+        let response = Router::new()
+            .route("/", post(add))
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn passes_valid_body_through() {
+        assert_eq!(
+            call(r#"{"title": "loco", "rating": 5}"#).await,
+            (StatusCode::OK, "loco 5".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_body_with_field_errors() {
+        let (status, body) = call(r#"{"title": "", "rating": 6}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+            json!({
+                "error": "validation_error",
+                "description": "The request body is invalid",
+                "errors": {
+                    "title": [{"code": "length", "message": "title cannot be empty"}],
+                    "rating": [{"code": "range", "message": null}],
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_body() {
+        let (status, _) = call(r#"{"title": "loco"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
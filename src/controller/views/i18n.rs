@@ -0,0 +1,319 @@
+//! Message translations for views.
+//!
+//! Messages are loaded from a directory holding a JSON file per locale, such
+//! as `assets/i18n/en-US.json`, mapping keys to messages. Nested objects are
+//! flattened into dotted keys, and `{name}` placeholders are replaced with
+//! the named arguments given at lookup:
+//!
+//! ```json
+//! { "greeting": { "hello": "Hello, {name}!" } }
+//! ```
+//!
+//! Install them on the Tera engine to call `t` from templates, with the
+//! locale of the request resolved by [`Translations::locale`]:
+//!
+//! ```rust,ignore
+//! let translations = Translations::from_dir("assets/i18n", "en-US")?;
+//! let view = TeraView::build()?.with_translations(translations);
+//! ```
+//!
+//! ```html
+//! {{ t(key="greeting.hello", locale=locale, name=user.name) }}
+//! ```
+//!
+//! Resolving the locale from a session value is left to the app, since there
+//! is no session store to read it from.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Arc,
+};
+
+use axum::http::{header, HeaderMap};
+use fs_err as fs;
+
+use crate::{controller::views::engines::TeraView, Error, Result};
+
+/// Messages by locale, with a fallback locale for missing messages.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    locales: Arc<BTreeMap<String, HashMap<String, String>>>,
+    fallback: String,
+}
+
+impl Translations {
+    /// Creates empty translations falling back to `fallback`.
+    #[must_use]
+    pub fn new(fallback: &str) -> Self {
+        Self {
+            locales: Arc::default(),
+            fallback: fallback.to_string(),
+        }
+    }
+
+    /// Loads the `<locale>.json` files of a directory.
+    ///
+    /// # Errors
+    /// When the directory or a file cannot be read, or a file is not a JSON
+    /// object.
+    pub fn from_dir<P: AsRef<Path>>(path: P, fallback: &str) -> Result<Self> {
+        let mut translations = Self::new(fallback);
+        for entry in fs::read_dir(path.as_ref())? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let messages = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            translations = translations.add(locale, &messages)?;
+        }
+        Ok(translations)
+    }
+
+    /// Adds the messages of a locale from a JSON object.
+    ///
+    /// # Errors
+    /// When `messages` is not a JSON object.
+    pub fn add(mut self, locale: &str, messages: &serde_json::Value) -> Result<Self> {
+        let serde_json::Value::Object(messages) = messages else {
+            return Err(Error::Message(format!(
+                "translations of `{locale}` must be a JSON object"
+            )));
+        };
+        let mut flat = HashMap::new();
+        flatten(&mut flat, "", messages);
+        Arc::make_mut(&mut self.locales)
+            .entry(locale.to_string())
+            .or_default()
+            .extend(flat);
+        Ok(self)
+    }
+
+    /// The loaded locales.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.locales.keys().map(String::as_str)
+    }
+
+    /// Translates `key` for `locale`, trying its language (`en` for `en-US`)
+    /// and then the fallback locale. Returns the key itself when no locale
+    /// has the message.
+    #[must_use]
+    pub fn t(&self, key: &str, args: &[(&str, &str)], locale: &str) -> String {
+        let language = locale.split('-').next().unwrap_or(locale);
+        let message = [locale, language, self.fallback.as_str()]
+            .iter()
+            .find_map(|locale| self.locales.get(*locale)?.get(key));
+
+        let Some(message) = message else {
+            return key.to_string();
+        };
+        args.iter().fold(message.clone(), |message, (name, value)| {
+            message.replace(&format!("{{{name}}}"), value)
+        })
+    }
+
+    /// Resolves the locale of a request from its `Accept-Language` header,
+    /// picking the loaded locale with the highest quality, matched exactly or
+    /// by language. Returns the fallback locale when none matches.
+    #[must_use]
+    pub fn locale(&self, headers: &HeaderMap) -> &str {
+        let mut ranges = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut params = range.split(';');
+                let tag = params.next()?.trim();
+                let quality = params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+        // stable, so equal qualities keep the header order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranges
+            .iter()
+            .find_map(|(tag, _)| {
+                let language = tag.split('-').next().unwrap_or(tag);
+                self.locales()
+                    .find(|locale| locale.eq_ignore_ascii_case(tag))
+                    .or_else(|| {
+                        self.locales().find(|locale| {
+                            locale
+                                .split('-')
+                                .next()
+                                .is_some_and(|l| l.eq_ignore_ascii_case(language))
+                        })
+                    })
+            })
+            .unwrap_or(&self.fallback)
+    }
+}
+
+fn flatten(
+    flat: &mut HashMap<String, String>,
+    prefix: &str,
+    messages: &serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, value) in messages {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            serde_json::Value::Object(nested) => flatten(flat, &key, nested),
+            serde_json::Value::String(message) => {
+                flat.insert(key, message.clone());
+            }
+            other => {
+                flat.insert(key, other.to_string());
+            }
+        }
+    }
+}
+
+impl TeraView {
+    /// Registers the `t` template function, translating `key` for `locale`
+    /// (the fallback locale when omitted) with the other arguments as named
+    /// arguments.
+    #[must_use]
+    pub fn with_translations(mut self, translations: Translations) -> Self {
+        self.tera.register_function(
+            "t",
+            move |args: &HashMap<String, tera::Value>| -> tera::Result<tera::Value> {
+                let key = args
+                    .get("key")
+                    .and_then(tera::Value::as_str)
+                    .ok_or_else(|| tera::Error::msg("`t` requires a `key` argument"))?;
+                let locale = args
+                    .get("locale")
+                    .and_then(tera::Value::as_str)
+                    .unwrap_or(&translations.fallback);
+                let named = args
+                    .iter()
+                    .filter(|(name, _)| *name != "key" && *name != "locale")
+                    .map(|(name, value)| {
+                        let value = value
+                            .as_str()
+                            .map_or_else(|| value.to_string(), ToString::to_string);
+                        (name.as_str(), value)
+                    })
+                    .collect::<Vec<_>>();
+                let named = named
+                    .iter()
+                    .map(|(name, value)| (*name, value.as_str()))
+                    .collect::<Vec<_>>();
+                Ok(translations.t(key, &named, locale).into())
+            },
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+    use crate::controller::views::ViewRenderer;
+
+    fn translations() -> Translations {
+        Translations::new("en")
+            .add(
+                "en",
+                &json!({ "greeting": { "hello": "Hello, {name}!" }, "bye": "Bye" }),
+            )
+            .unwrap()
+            .add(
+                "fr",
+                &json!({ "greeting": { "hello": "Bonjour, {name} !" } }),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn can_translate_keys() {
+        let translations = translations();
+
+        assert_eq!(
+            translations.t("greeting.hello", &[("name", "Loco")], "fr"),
+            "Bonjour, Loco !"
+        );
+        assert_eq!(
+            translations.t("greeting.hello", &[("name", "Loco")], "en"),
+            "Hello, Loco!"
+        );
+        assert_eq!(
+            translations.t("greeting.hello", &[], "en"),
+            "Hello, {name}!"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_language_then_fallback_locale() {
+        let translations = translations();
+
+        assert_eq!(
+            translations.t("greeting.hello", &[("name", "Loco")], "fr-CA"),
+            "Bonjour, Loco !"
+        );
+        assert_eq!(translations.t("bye", &[], "fr"), "Bye");
+        assert_eq!(translations.t("bye", &[], "de"), "Bye");
+        assert_eq!(translations.t("missing.key", &[], "fr"), "missing.key");
+    }
+
+    #[test]
+    fn can_resolve_locale_from_accept_language() {
+        let translations = translations();
+        let locale = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::ACCEPT_LANGUAGE,
+                HeaderValue::from_str(accept).unwrap(),
+            );
+            translations.locale(&headers).to_string()
+        };
+
+        assert_eq!(translations.locale(&HeaderMap::new()), "en");
+        assert_eq!(locale("fr-CH, fr;q=0.9, en;q=0.8"), "fr");
+        assert_eq!(locale("de, en;q=0.5, fr;q=0.7"), "fr");
+        assert_eq!(locale("de"), "en");
+    }
+
+    #[test]
+    fn can_load_from_dir_and_translate_in_templates() {
+        let tree_res = tree_fs::from_yaml_str(
+            r#"
+        files:
+        - path: i18n/fr.json
+          content: |-
+            { "greeting": { "hello": "Bonjour, {name} !" } }
+        - path: views/home.html
+          content: |-
+            {{ t(key="greeting.hello", locale=locale, name=name) }}
+        "#,
+        )
+        .unwrap();
+
+        let translations = Translations::from_dir(tree_res.join("i18n"), "fr").unwrap();
+        assert_eq!(translations.locales().collect::<Vec<_>>(), ["fr"]);
+
+        let v = TeraView::from_custom_dir(&tree_res.join("views"))
+            .unwrap()
+            .with_translations(translations);
+        assert_eq!(
+            v.render("home.html", json!({ "locale": "fr", "name": "Loco" }))
+                .unwrap(),
+            "Bonjour, Loco !"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub mod engines;
+pub mod i18n;
 use axum::{async_trait, extract::FromRequestParts, http::request::Parts, Extension};
 use serde::Serialize;
 
@@ -7,6 +8,7 @@ use crate::Result;
 #[cfg(feature = "with-db")]
 pub mod pagination;
 
+#[async_trait]
 pub trait ViewRenderer {
     /// Render a view template located by `key`
     ///
@@ -14,6 +16,30 @@ pub trait ViewRenderer {
     ///
     /// This function will return an error if render fails
     fn render<S: Serialize>(&self, key: &str, data: S) -> Result<String>;
+
+    /// Render only the `fragment` part (such as a block) of the view template
+    /// located by `key`, for partial page updates. Defaults to rendering the
+    /// whole template.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if render fails
+    fn render_fragment<S: Serialize>(&self, key: &str, fragment: &str, data: S) -> Result<String> {
+        let _ = fragment;
+        self.render(key, data)
+    }
+
+    /// Render a view template located by `key`, for engines that need to
+    /// await while rendering, such as loading partials from remote storage.
+    /// Defaults to [`ViewRenderer::render`]; overriding it takes an
+    /// `#[async_trait]` impl.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if render fails
+    async fn render_async<S: Serialize + Send>(&self, key: &str, data: S) -> Result<String> {
+        self.render(key, data)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -24,12 +50,71 @@ impl<E> ViewEngine<E> {
     pub fn new(engine: E) -> Self {
         Self(engine)
     }
+
+    /// Wraps every page rendered by the engine in the `layout_key` layout
+    /// template, see [`WithLayout`]. Without it, pages are rendered bare.
+    #[must_use]
+    pub fn with_layout(&self, layout_key: &str) -> ViewEngine<WithLayout<E>>
+    where
+        E: Clone,
+    {
+        ViewEngine(WithLayout {
+            engine: self.0.clone(),
+            layout: layout_key.to_string(),
+        })
+    }
+}
+
+/// A view engine rendering pages into a layout template.
+///
+/// The rendered page is passed to the layout as `content`, along with the
+/// page data. With Tera, output it unescaped: `{{ content | safe }}`.
+/// Fragments are rendered without the layout.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WithLayout<E> {
+    pub engine: E,
+    pub layout: String,
+}
+
+impl<E> WithLayout<E> {
+    fn layout_data<S: Serialize>(content: String, data: S) -> Result<serde_json::Value> {
+        let mut data = serde_json::to_value(data)?;
+        match data.as_object_mut() {
+            Some(object) => {
+                object.insert("content".to_string(), content.into());
+            }
+            None => data = serde_json::json!({ "content": content }),
+        }
+        Ok(data)
+    }
+}
+
+#[async_trait]
+impl<E: ViewRenderer + Send + Sync> ViewRenderer for WithLayout<E> {
+    fn render<S: Serialize>(&self, key: &str, data: S) -> Result<String> {
+        let data = serde_json::to_value(data)?;
+        let content = self.engine.render(key, &data)?;
+        self.engine
+            .render(&self.layout, Self::layout_data(content, data)?)
+    }
+
+    fn render_fragment<S: Serialize>(&self, key: &str, fragment: &str, data: S) -> Result<String> {
+        self.engine.render_fragment(key, fragment, data)
+    }
+
+    async fn render_async<S: Serialize + Send>(&self, key: &str, data: S) -> Result<String> {
+        let data = serde_json::to_value(data)?;
+        let content = self.engine.render_async(key, &data).await?;
+        self.engine
+            .render_async(&self.layout, Self::layout_data(content, data)?)
+            .await
+    }
 }
 
 /// A struct representing an inline Tera view renderer.
 ///
-/// This struct provides functionality to render templates using the Tera templating engine
-/// directly from raw template strings.
+/// This struct provides functionality to render templates using the Tera
+/// templating engine directly from raw template strings.
 ///
 /// # Example
 /// ```
@@ -85,3 +170,43 @@ where
         Ok(tl)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct MockView;
+
+    impl ViewRenderer for MockView {
+        fn render<S: Serialize>(&self, key: &str, data: S) -> Result<String> {
+            let data = serde_json::to_value(data)?;
+            Ok(match key {
+                "layout.html" => format!("<main>{}</main>", data["content"].as_str().unwrap()),
+                _ => format!("<p>{}</p>", data["name"].as_str().unwrap()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn can_render_with_layout() {
+        let data = serde_json::json!({ "name": "loco" });
+
+        let engine = ViewEngine::from(MockView);
+        assert_eq!(engine.0.render("home.html", &data).unwrap(), "<p>loco</p>");
+
+        let ViewEngine(v) = engine.with_layout("layout.html");
+        assert_eq!(
+            v.render("home.html", &data).unwrap(),
+            "<main><p>loco</p></main>"
+        );
+        assert_eq!(
+            v.render_async("home.html", &data).await.unwrap(),
+            "<main><p>loco</p></main>"
+        );
+        assert_eq!(
+            v.render_fragment("home.html", "list", &data).unwrap(),
+            "<p>loco</p>"
+        );
+    }
+}
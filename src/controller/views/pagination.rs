@@ -17,6 +17,10 @@ pub struct PagerMeta {
     pub page_size: u64,
     #[serde(rename(serialize = "total_pages"))]
     pub total_pages: u64,
+    /// The SQL of the query, see
+    /// [`crate::model::query::PaginationQuery::debug`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_sql: Option<String>,
 }
 
 impl<T> Pager<T> {
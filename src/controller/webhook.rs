@@ -0,0 +1,307 @@
+//! Webhook signature verification.
+//!
+//! Providers sign webhook deliveries with a shared secret so the receiver
+//! can tell them apart from forged requests. [`verify`] checks the signature
+//! of a raw request body for the supported [`Provider`] schemes and, on
+//! success, parses the payload.
+//!
+//! The signature is computed over the exact bytes that were sent, so the body
+//! has to be taken as raw bytes rather than through a JSON extractor.
+//!
+//! ```rust
+//! use axum::{body::Bytes, http::HeaderMap};
+//! use loco_rs::{controller::webhook, prelude::*};
+//!
+//! async fn github(headers: HeaderMap, body: Bytes) -> Result<Response> {
+//!     let event: serde_json::Value =
+//!         webhook::verify(&webhook::Provider::GitHub, "secret", &headers, &body)?;
+//!     format::json(event)
+//! }
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::http::{HeaderMap, HeaderName};
+use hmac::Mac;
+use serde::de::DeserializeOwned;
+
+use crate::{controller::ErrorDetail, hash::hmac_sha256, Error};
+
+/// Default age after which a Stripe delivery is rejected, matching Stripe's
+/// own libraries.
+pub const STRIPE_TOLERANCE: Duration = Duration::from_secs(300);
+
+/// A webhook signature scheme.
+#[derive(Debug, Clone)]
+pub enum Provider {
+    /// `Stripe-Signature: t=<timestamp>,v1=<hex>`, signing
+    /// `<timestamp>.<body>`. Deliveries older than `tolerance` are
+    /// rejected.
+    Stripe { tolerance: Duration },
+    /// `X-Hub-Signature-256: sha256=<hex>`, signing the body.
+    GitHub,
+    /// A hex encoded HMAC-SHA256 of the body, optionally prefixed with
+    /// `sha256=`, in the given header.
+    Hmac { header: HeaderName },
+}
+
+impl Provider {
+    /// Stripe with the default [`STRIPE_TOLERANCE`].
+    #[must_use]
+    pub const fn stripe() -> Self {
+        Self::Stripe {
+            tolerance: STRIPE_TOLERANCE,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WebhookError {
+    #[error("missing signature header `{0}`")]
+    MissingSignature(HeaderName),
+
+    #[error("malformed signature header `{0}`")]
+    MalformedSignature(HeaderName),
+
+    #[error("signature does not match")]
+    InvalidSignature,
+
+    #[error("signature timestamp is outside the tolerance")]
+    Expired,
+
+    #[error(transparent)]
+    Payload(#[from] serde_json::Error),
+}
+
+impl From<WebhookError> for Error {
+    fn from(err: WebhookError) -> Self {
+        Self::CustomError(
+            axum::http::StatusCode::BAD_REQUEST,
+            ErrorDetail::new("bad_request".to_string(), err.to_string()),
+        )
+    }
+}
+
+/// Verifies the signature of a webhook delivery and parses its payload.
+///
+/// # Errors
+/// When the signature header is missing or malformed, the signature does not
+/// match the body, the delivery is too old, or the body cannot be parsed
+/// into `T`.
+pub fn verify<T: DeserializeOwned>(
+    provider: &Provider,
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<T, WebhookError> {
+    match provider {
+        Provider::Stripe { tolerance } => verify_stripe(secret, headers, body, *tolerance)?,
+        Provider::GitHub => verify_hex(
+            secret,
+            headers,
+            &HeaderName::from_static("x-hub-signature-256"),
+            body,
+        )?,
+        Provider::Hmac { header } => verify_hex(secret, headers, header, body)?,
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &HeaderName) -> Result<&'a str, WebhookError> {
+    headers
+        .get(name)
+        .ok_or_else(|| WebhookError::MissingSignature(name.clone()))?
+        .to_str()
+        .map_err(|_| WebhookError::MalformedSignature(name.clone()))
+}
+
+fn verify_hex(
+    secret: &str,
+    headers: &HeaderMap,
+    name: &HeaderName,
+    body: &[u8],
+) -> Result<(), WebhookError> {
+    let value = header(headers, name)?;
+    let signature = hex::decode(value.strip_prefix("sha256=").unwrap_or(value))
+        .map_err(|_| WebhookError::MalformedSignature(name.clone()))?;
+
+    let mut mac = hmac_sha256(secret.as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| WebhookError::InvalidSignature)
+}
+
+fn verify_stripe(
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    tolerance: Duration,
+) -> Result<(), WebhookError> {
+    let name = HeaderName::from_static("stripe-signature");
+    let value = header(headers, &name)?;
+
+    let mut timestamp = None;
+    let mut has_v1 = false;
+    let mut signatures = vec![];
+    for (key, value) in value.split(',').filter_map(|part| part.split_once('=')) {
+        match key.trim() {
+            "t" => timestamp = value.parse::<u64>().ok(),
+            // an entry that is not hex cannot match, and must not hide the
+            // valid entries next to it
+            "v1" => {
+                has_v1 = true;
+                signatures.extend(hex::decode(value.trim()).ok());
+            }
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or_else(|| WebhookError::MalformedSignature(name.clone()))?;
+    if !has_v1 {
+        return Err(WebhookError::MalformedSignature(name));
+    }
+
+    let mut mac = hmac_sha256(secret.as_bytes());
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    if !signatures
+        .iter()
+        .any(|signature| mac.clone().verify_slice(signature).is_ok())
+    {
+        return Err(WebhookError::InvalidSignature);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return Err(WebhookError::Expired);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const BODY: &[u8] = br#"{"action":"opened","number":1}"#;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::try_from(name).unwrap(),
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn can_verify_generic_hmac() {
+        let provider = Provider::Hmac {
+            header: HeaderName::from_static("x-signature"),
+        };
+        let headers = headers(
+            "x-signature",
+            "df5e633c5231d3559798084e246e6be0fd2e93e226c68b4a2eb8be3f64ed11de",
+        );
+
+        let payload: serde_json::Value = verify(&provider, "secret", &headers, BODY).unwrap();
+        assert_eq!(payload["number"], 1);
+
+        assert!(matches!(
+            verify::<serde_json::Value>(&provider, "other", &headers, BODY),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify::<serde_json::Value>(&provider, "secret", &HeaderMap::new(), BODY),
+            Err(WebhookError::MissingSignature(_))
+        ));
+    }
+
+    #[test]
+    fn can_verify_github() {
+        let headers = headers(
+            "x-hub-signature-256",
+            "sha256=df5e633c5231d3559798084e246e6be0fd2e93e226c68b4a2eb8be3f64ed11de",
+        );
+
+        let payload: serde_json::Value =
+            verify(&Provider::GitHub, "secret", &headers, BODY).unwrap();
+        assert_eq!(payload["action"], "opened");
+
+        assert!(matches!(
+            verify::<serde_json::Value>(
+                &Provider::GitHub,
+                "secret",
+                &headers,
+                br#"{"action":"closed","number":1}"#
+            ),
+            Err(WebhookError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn can_verify_stripe() {
+        let headers = headers(
+            "stripe-signature",
+            "t=1700000000,v1=f0b3a3c6a3a1d36c3fe1b0e1cb7f2ef3ad7b3b39e9c407d38b4e94ec3f5d4ad0,\
+             v1=8622a263e32885d3925bc90ef296042b71a2b48a918a67681cbc58bac57f5d21",
+        );
+        let provider = Provider::Stripe {
+            tolerance: Duration::MAX,
+        };
+
+        let payload: serde_json::Value = verify(&provider, "whsec", &headers, BODY).unwrap();
+        assert_eq!(payload["number"], 1);
+
+        assert!(matches!(
+            verify::<serde_json::Value>(&Provider::stripe(), "whsec", &headers, BODY),
+            Err(WebhookError::Expired)
+        ));
+        assert!(matches!(
+            verify::<serde_json::Value>(&provider, "other", &headers, BODY),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify::<serde_json::Value>(
+                &provider,
+                "whsec",
+                &self::headers("stripe-signature", "v1=00"),
+                BODY
+            ),
+            Err(WebhookError::MalformedSignature(_))
+        ));
+    }
+
+    #[test]
+    fn stripe_skips_malformed_signatures() {
+        let provider = Provider::Stripe {
+            tolerance: Duration::MAX,
+        };
+        let verify_header = |value: &str| {
+            verify::<serde_json::Value>(
+                &provider,
+                "whsec",
+                &headers("stripe-signature", value),
+                BODY,
+            )
+        };
+
+        assert!(verify_header(
+            "t=1700000000,v1=not-hex,\
+             v1=8622a263e32885d3925bc90ef296042b71a2b48a918a67681cbc58bac57f5d21"
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_header("t=1700000000,v1=not-hex"),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_header("t=1700000000,v0=00"),
+            Err(WebhookError::MalformedSignature(_))
+        ));
+    }
+}
@@ -41,8 +41,7 @@ impl Params {
 }
 
 async fn load_item(ctx: &AppContext, id: i32) -> Result<Model> {
-    let item = Entity::find_by_id(id).one(&ctx.db).await?;
-    item.ok_or_else(|| Error::NotFound)
+    Entity::find_by_id_or_404(&ctx.db, id).await
 }
 
 #[debug_handler]
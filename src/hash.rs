@@ -2,9 +2,19 @@ use argon2::{
     password_hash::SaltString, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier,
     Version,
 };
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 
 use crate::{Error, Result};
 
+pub(crate) type HmacSha256 = Hmac<Sha256>;
+
+/// Returns an HMAC-SHA256 keyed with `key`.
+pub(crate) fn hmac_sha256(key: &[u8]) -> HmacSha256 {
+    // HMAC accepts keys of any length
+    HmacSha256::new_from_slice(key).expect("HMAC can take key of any size")
+}
+
 /// Hashes a plain text password and returns the hashed result.
 ///
 /// # Errors
@@ -25,6 +25,7 @@ pub mod cli;
 pub mod auth;
 pub mod boot;
 pub mod cache;
+pub mod circuit_breaker;
 pub mod config;
 pub mod controller;
 pub mod environment;
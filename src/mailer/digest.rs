@@ -0,0 +1,326 @@
+//! # Digests
+//!
+//! A digest is a recurring email summarizing the records created since the
+//! previous one, such as a daily "new comments" email. Implement [`Digest`]
+//! to query the records and build the email, and register a [`DigestTask`]
+//! so the scheduler can run it:
+//!
+//! ```yaml
+//! scheduler:
+//!   jobs:
+//!     notes_digest:
+//!       run: "digest:new_notes"
+//!       cron: "0 8 * * *"
+//! ```
+//!
+//! The time of the last digest, its watermark, is kept in the cache under
+//! `digest:<name>`, so each run picks up where the previous one stopped. Use
+//! a persistent cache driver for it to survive restarts. The watermark is
+//! stored and read back before the email goes out, so a cache that cannot
+//! hold values, such as the null driver, fails the digest instead of mailing
+//! the same records on every run.
+//!
+//! ```rust
+//! use chrono::NaiveDateTime;
+//! use loco_rs::{
+//!     mailer::{digest::Digest, Email},
+//!     prelude::*,
+//!     tests_cfg::db::test_db,
+//! };
+//!
+//! struct NewNotes;
+//!
+//! #[async_trait]
+//! impl Digest for NewNotes {
+//!     type Record = test_db::Model;
+//!
+//!     fn name(&self) -> &'static str {
+//!         "new_notes"
+//!     }
+//!
+//!     async fn records(
+//!         &self,
+//!         ctx: &AppContext,
+//!         since: NaiveDateTime,
+//!         until: NaiveDateTime,
+//!     ) -> Result<Vec<Self::Record>> {
+//!         Ok(test_db::Entity::find()
+//!             .filter(
+//!                 query::condition()
+//!                     .gt(test_db::Column::CreatedAt, since)
+//!                     .lte(test_db::Column::CreatedAt, until)
+//!                     .build(),
+//!             )
+//!             .all(&ctx.db)
+//!             .await?)
+//!     }
+//!
+//!     fn email(&self, records: &[Self::Record]) -> Result<Email> {
+//!         let names = records.iter().map(|note| note.name.as_str()).collect::<Vec<_>>();
+//!         Ok(Email {
+//!             to: "team@example.com".to_string(),
+//!             subject: format!("{} new notes", records.len()),
+//!             text: names.join("\n"),
+//!             html: names.join("<br>"),
+//!             ..Default::default()
+//!         })
+//!     }
+//! }
+//! ```
+
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDateTime};
+
+use super::{Email, Mailer};
+use crate::{
+    app::AppContext,
+    model::query::{Clock, SystemClock},
+    task::{Task, TaskInfo, Vars},
+    Error, Result,
+};
+
+const WATERMARK_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// A recurring summary email of the records created since the previous one.
+#[async_trait]
+pub trait Digest: Send + Sync {
+    type Record: Send + Sync;
+
+    /// Unique name of the digest, keying its watermark.
+    fn name(&self) -> &'static str;
+
+    /// Returns the records created after `since` and up to `until`.
+    async fn records(
+        &self,
+        ctx: &AppContext,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<Self::Record>>;
+
+    /// Builds the email summarizing the records, never called without
+    /// records.
+    ///
+    /// # Errors
+    /// When the email cannot be built, for example when a template fails to
+    /// render.
+    fn email(&self, records: &[Self::Record]) -> Result<Email>;
+
+    /// How far back the first digest, with no watermark yet, looks.
+    fn lookback(&self) -> Duration {
+        Duration::days(1)
+    }
+}
+
+struct DigestMailer;
+
+impl Mailer for DigestMailer {}
+
+fn watermark_key<D: Digest>(digest: &D) -> String {
+    format!("digest:{}", digest.name())
+}
+
+/// Stores the watermark and reads it back, failing when the cache did not
+/// keep it.
+async fn store_watermark(ctx: &AppContext, key: &str, watermark: &str) -> Result<()> {
+    let stored = match ctx.cache.insert(key, watermark).await {
+        Ok(()) => ctx.cache.get(key).await?,
+        Err(err) => {
+            return Err(Error::Message(format!(
+                "the digest watermark `{key}` could not be stored in the cache: {err}"
+            )))
+        }
+    };
+    if stored.as_deref() == Some(watermark) {
+        Ok(())
+    } else {
+        Err(Error::Message(format!(
+            "the digest watermark `{key}` was not kept by the cache, use a driver that can hold \
+             values"
+        )))
+    }
+}
+
+/// Sends the digest of the records created since the previous digest, and
+/// moves the watermark to the current time of `clock`. No email is sent when
+/// there are no new records.
+///
+/// The new watermark is stored before mailing, and the previous one is put
+/// back when the email cannot be sent.
+///
+/// Returns the number of records in the digest.
+///
+/// # Errors
+/// When the watermark cannot be read or stored, or the records cannot be
+/// queried or mailed.
+pub async fn send<D: Digest>(ctx: &AppContext, digest: &D, clock: &impl Clock) -> Result<usize> {
+    let key = watermark_key(digest);
+    let now = clock.now();
+    let previous = ctx.cache.get(&key).await?;
+    let since = previous
+        .as_deref()
+        .and_then(|watermark| NaiveDateTime::parse_from_str(watermark, WATERMARK_FORMAT).ok())
+        .unwrap_or_else(|| now - digest.lookback());
+
+    let records = digest.records(ctx, since, now).await?;
+    store_watermark(ctx, &key, &now.format(WATERMARK_FORMAT).to_string()).await?;
+    if !records.is_empty() {
+        let sent = match digest.email(&records) {
+            Ok(email) => DigestMailer::mail(ctx, &email).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = sent {
+            let restored = match previous {
+                Some(previous) => ctx.cache.insert(&key, &previous).await,
+                None => ctx.cache.remove(&key).await,
+            };
+            if let Err(restore_err) = restored {
+                tracing::error!(
+                    digest = digest.name(),
+                    error = %restore_err,
+                    "could not restore the digest watermark"
+                );
+            }
+            return Err(err);
+        }
+    }
+
+    tracing::info!(
+        digest = digest.name(),
+        records = records.len(),
+        "digest sent"
+    );
+    Ok(records.len())
+}
+
+/// Runs a [`Digest`] as the `digest:<name>` task, for the scheduler.
+pub struct DigestTask<D>(pub D);
+
+#[async_trait]
+impl<D: Digest> Task for DigestTask<D> {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: format!("digest:{}", self.0.name()),
+            detail: format!("Send the {} digest", self.0.name()),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &Vars) -> Result<()> {
+        send(app_context, &self.0, &SystemClock).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{ActiveModelTrait, EntityTrait, QueryFilter, Set};
+
+    use super::*;
+    use crate::{
+        cache,
+        mailer::EmailSender,
+        model::query::{self, FixedClock},
+        tests_cfg::{
+            self,
+            db::{seeded_connection, test_db},
+        },
+    };
+
+    struct NewNotes;
+
+    #[async_trait]
+    impl Digest for NewNotes {
+        type Record = test_db::Model;
+
+        fn name(&self) -> &'static str {
+            "new_notes"
+        }
+
+        async fn records(
+            &self,
+            ctx: &AppContext,
+            since: NaiveDateTime,
+            until: NaiveDateTime,
+        ) -> Result<Vec<Self::Record>> {
+            Ok(test_db::Entity::find()
+                .filter(
+                    query::condition()
+                        .gt(test_db::Column::CreatedAt, since)
+                        .lte(test_db::Column::CreatedAt, until)
+                        .build(),
+                )
+                .all(&ctx.db)
+                .await?)
+        }
+
+        fn email(&self, records: &[Self::Record]) -> Result<Email> {
+            let names = records
+                .iter()
+                .map(|note| note.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Ok(Email {
+                to: "team@example.com".to_string(),
+                subject: format!("{} new notes", records.len()),
+                text: names.clone(),
+                html: names,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn at(hour: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    async fn insert(ctx: &AppContext, name: &str, created_at: NaiveDateTime) {
+        test_db::ActiveModel {
+            created_at: Set(created_at),
+            updated_at: Set(created_at),
+            ..test_db::active_model(name)
+        }
+        .insert(&ctx.db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn sends_records_since_last_digest() {
+        let mut ctx = tests_cfg::app::get_app_context().await;
+        ctx.db = seeded_connection(&[]).await;
+        ctx.mailer = Some(EmailSender::stub());
+
+        insert(&ctx, "too old", at(9) - Duration::days(2)).await;
+        insert(&ctx, "first", at(9)).await;
+        assert_eq!(send(&ctx, &NewNotes, &FixedClock(at(10))).await.unwrap(), 1);
+
+        insert(&ctx, "second", at(11)).await;
+        insert(&ctx, "third", at(12)).await;
+        assert_eq!(send(&ctx, &NewNotes, &FixedClock(at(13))).await.unwrap(), 2);
+
+        // nothing new, nothing sent
+        assert_eq!(send(&ctx, &NewNotes, &FixedClock(at(14))).await.unwrap(), 0);
+
+        let deliveries = ctx.mailer.as_ref().unwrap().deliveries();
+        assert_eq!(deliveries.count, 2);
+        assert!(deliveries.messages[0].contains("Subject: 1 new notes"));
+        assert!(deliveries.messages[0].contains("first"));
+        assert!(!deliveries.messages[0].contains("too old"));
+        assert!(deliveries.messages[1].contains("Subject: 2 new notes"));
+        assert!(deliveries.messages[1].contains("second, third"));
+    }
+
+    #[tokio::test]
+    async fn fails_before_mailing_when_the_cache_cannot_store() {
+        let mut ctx = tests_cfg::app::get_app_context().await;
+        ctx.db = seeded_connection(&[]).await;
+        ctx.cache = cache::Cache::new(cache::drivers::null::new()).into();
+        ctx.mailer = Some(EmailSender::stub());
+
+        insert(&ctx, "first", at(9)).await;
+        assert!(send(&ctx, &NewNotes, &FixedClock(at(10))).await.is_err());
+        assert_eq!(ctx.mailer.as_ref().unwrap().deliveries().count, 0);
+    }
+}
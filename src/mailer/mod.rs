@@ -2,6 +2,8 @@
 //! trait and its implementation, `Email` structure, and the `MailerWorker` for
 //! asynchronous email processing.
 
+#[cfg(feature = "with-db")]
+pub mod digest;
 mod email_sender;
 mod template;
 
@@ -0,0 +1,56 @@
+//! # Find or 404
+//!
+//! Loads a row by primary key in a controller, answering `404 Not Found`
+//! when there is no such row:
+//!
+//! ```rust,ignore
+//! async fn show(Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+//!     format::json(notes::Entity::find_by_id_or_404(&ctx.db, id).await?)
+//! }
+//! ```
+
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, EntityTrait, PrimaryKeyTrait};
+
+use crate::{Error, Result};
+
+/// Primary key lookups returning [`Error::NotFound`] for missing rows,
+/// implemented for every entity.
+#[async_trait]
+pub trait FindOr404: EntityTrait {
+    /// Like [`EntityTrait::find_by_id`], failing with [`Error::NotFound`]
+    /// when there is no row with this primary key.
+    ///
+    /// # Errors
+    /// When the row is not found, or the query fails.
+    async fn find_by_id_or_404<C, T>(db: &C, id: T) -> Result<Self::Model>
+    where
+        C: ConnectionTrait,
+        T: Into<<Self::PrimaryKey as PrimaryKeyTrait>::ValueType> + Send,
+    {
+        Self::find_by_id(id).one(db).await?.ok_or(Error::NotFound)
+    }
+}
+
+impl<E: EntityTrait> FindOr404 for E {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests_cfg::db::{seeded_connection, test_db};
+
+    #[tokio::test]
+    async fn can_find_by_id_or_404() {
+        let db = seeded_connection(&["loco"]).await;
+        let note = test_db::Entity::find().one(&db).await.unwrap().unwrap();
+
+        let found = test_db::Entity::find_by_id_or_404(&db, note.id)
+            .await
+            .unwrap();
+        assert_eq!(found.name, "loco");
+        assert!(matches!(
+            test_db::Entity::find_by_id_or_404(&db, note.id + 1).await,
+            Err(Error::NotFound)
+        ));
+    }
+}
@@ -2,10 +2,14 @@
 //!
 //! Useful when using `sea_orm` and want to propagate errors
 
+mod find_or_404;
 pub mod query;
+mod soft_delete;
 use async_trait::async_trait;
+pub use find_or_404::FindOr404;
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
+pub use soft_delete::{SoftDelete, SoftDeleteActiveModel};
 
 #[derive(Debug, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
@@ -0,0 +1,31 @@
+use chrono::{NaiveDateTime, Utc};
+
+/// Source of the current time for conditions relative to now, such as
+/// [`super::ConditionBuilder::within_next`].
+///
+/// Swap [`SystemClock`] for a [`FixedClock`] to make those conditions
+/// deterministic in tests.
+pub trait Clock {
+    /// The current UTC time.
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// The system time.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+}
+
+/// A clock stopped at the given time.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub NaiveDateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.0
+    }
+}
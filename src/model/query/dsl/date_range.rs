@@ -1,55 +1,137 @@
-use chrono::NaiveDateTime;
-use sea_orm::ColumnTrait;
+use chrono::{Duration, NaiveDateTime};
+use sea_orm::{ColumnTrait, Value};
 
-use super::{with, ConditionBuilder};
-pub struct DateRangeBuilder<T: ColumnTrait> {
+use super::{with, Clock, ConditionBuilder, SystemClock};
+pub struct DateRangeBuilder<T: ColumnTrait, D = NaiveDateTime> {
     col: T,
     condition_builder: ConditionBuilder,
-    from_date: Option<NaiveDateTime>,
-    to_date: Option<NaiveDateTime>,
+    from_date: Option<D>,
+    to_date: Option<D>,
+    from_inclusive: Option<bool>,
+    to_inclusive: Option<bool>,
 }
 
-impl<T: ColumnTrait> DateRangeBuilder<T> {
+impl<T: ColumnTrait, D: Into<Value> + Copy> DateRangeBuilder<T, D> {
     pub const fn new(condition_builder: ConditionBuilder, col: T) -> Self {
         Self {
             col,
             condition_builder,
             from_date: None,
             to_date: None,
+            from_inclusive: None,
+            to_inclusive: None,
         }
     }
 
     #[must_use]
-    pub fn dates(self, from: Option<&NaiveDateTime>, to: Option<&NaiveDateTime>) -> Self {
+    pub fn dates(self, from: Option<&D>, to: Option<&D>) -> Self {
         Self {
             col: self.col,
             condition_builder: self.condition_builder,
             from_date: from.copied(),
             to_date: to.copied(),
+            from_inclusive: self.from_inclusive,
+            to_inclusive: self.to_inclusive,
         }
     }
 
     #[must_use]
-    pub fn from(self, from: &NaiveDateTime) -> Self {
+    pub fn from(self, from: &D) -> Self {
         Self {
             col: self.col,
             condition_builder: self.condition_builder,
             from_date: Some(*from),
             to_date: self.to_date,
+            from_inclusive: self.from_inclusive,
+            to_inclusive: self.to_inclusive,
         }
     }
 
     #[must_use]
-    pub fn to(self, to: &NaiveDateTime) -> Self {
+    pub fn to(self, to: &D) -> Self {
         Self {
             col: self.col,
             condition_builder: self.condition_builder,
             from_date: self.from_date,
             to_date: Some(*to),
+            from_inclusive: self.from_inclusive,
+            to_inclusive: self.to_inclusive,
+        }
+    }
+
+    /// Whether the lower bound is included, as `>=` rather than `>`.
+    ///
+    /// Setting either this or [`DateRangeBuilder::to_inclusive`] emits a
+    /// comparison on each side instead of `BETWEEN`. A side without a toggle
+    /// keeps its default: inclusive when both dates are set (like
+    /// `BETWEEN`), exclusive otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let from = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let to = chrono::NaiveDate::from_ymd_opt(2024, 3, 2).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    ///
+    /// let query_str = |from_inclusive, to_inclusive| {
+    ///     let condition = query::condition()
+    ///         .date_range(test_db::Column::CreatedAt)
+    ///         .dates(Some(&from), Some(&to))
+    ///         .from_inclusive(from_inclusive)
+    ///         .to_inclusive(to_inclusive)
+    ///         .build();
+    ///
+    ///     test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(condition.build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     query_str(true, false),
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" >= '2024-03-01 00:00:00.000000' AND \"loco\".\"created_at\" < '2024-03-02 00:00:00.000000'"
+    /// );
+    /// assert_eq!(
+    ///     query_str(true, true),
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" >= '2024-03-01 00:00:00.000000' AND \"loco\".\"created_at\" <= '2024-03-02 00:00:00.000000'"
+    /// );
+    /// assert_eq!(
+    ///     query_str(false, true),
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" > '2024-03-01 00:00:00.000000' AND \"loco\".\"created_at\" <= '2024-03-02 00:00:00.000000'"
+    /// );
+    /// assert_eq!(
+    ///     query_str(false, false),
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" > '2024-03-01 00:00:00.000000' AND \"loco\".\"created_at\" < '2024-03-02 00:00:00.000000'"
+    /// );
+    /// ````
+    #[must_use]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_inclusive(self, inclusive: bool) -> Self {
+        Self {
+            from_inclusive: Some(inclusive),
+            ..self
+        }
+    }
+
+    /// Whether the upper bound is included, as `<=` rather than `<`. See
+    /// [`DateRangeBuilder::from_inclusive`].
+    #[must_use]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_inclusive(self, inclusive: bool) -> Self {
+        Self {
+            to_inclusive: Some(inclusive),
+            ..self
         }
     }
 
     pub fn build(self) -> ConditionBuilder {
+        if self.from_inclusive.is_some() || self.to_inclusive.is_some() {
+            return self.build_bounds();
+        }
         let con = match (self.from_date, self.to_date) {
             (None, None) => self.condition_builder.condition,
             (None, Some(to)) => self.condition_builder.condition.add(self.col.lt(to)),
@@ -61,6 +143,89 @@ impl<T: ColumnTrait> DateRangeBuilder<T> {
         };
         with(con)
     }
+
+    fn build_bounds(self) -> ConditionBuilder {
+        let inclusive = self.from_date.is_some() && self.to_date.is_some();
+        let mut con = self.condition_builder.condition;
+        if let Some(from) = self.from_date {
+            con = con.add(if self.from_inclusive.unwrap_or(inclusive) {
+                self.col.gte(from)
+            } else {
+                self.col.gt(from)
+            });
+        }
+        if let Some(to) = self.to_date {
+            con = con.add(if self.to_inclusive.unwrap_or(inclusive) {
+                self.col.lte(to)
+            } else {
+                self.col.lt(to)
+            });
+        }
+        with(con)
+    }
+}
+
+impl<T: ColumnTrait> DateRangeBuilder<T> {
+    /// Sets the range to the last `n` days up to now.
+    #[must_use]
+    pub fn last_days(self, n: i64) -> Self {
+        self.since(Duration::days(n))
+    }
+
+    /// Sets the range to the last `n` hours up to now.
+    #[must_use]
+    pub fn last_hours(self, n: i64) -> Self {
+        self.since(Duration::hours(n))
+    }
+
+    /// Sets the range to the given duration up to now.
+    #[must_use]
+    pub fn since(self, duration: Duration) -> Self {
+        self.since_at(&SystemClock, duration)
+    }
+
+    /// Like [`DateRangeBuilder::since`], taking the current time from `clock`.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let now = chrono::NaiveDate::from_ymd_opt(2024, 3, 8)
+    ///     .unwrap()
+    ///     .and_hms_opt(12, 0, 0)
+    ///     .unwrap();
+    ///
+    /// let condition = query::condition()
+    ///     .date_range(test_db::Column::CreatedAt)
+    ///     .since_at(&query::FixedClock(now), chrono::Duration::days(7))
+    ///     .build();
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(condition.build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN '2024-03-01 12:00:00.000000' AND '2024-03-08 12:00:00.000000'"
+    /// );
+    /// ````
+    #[must_use]
+    pub fn since_at(self, clock: &impl Clock, duration: Duration) -> Self {
+        let now = clock.now();
+        Self {
+            col: self.col,
+            condition_builder: self.condition_builder,
+            from_date: Some(now - duration),
+            to_date: Some(now),
+            from_inclusive: self.from_inclusive,
+            to_inclusive: self.to_inclusive,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -147,4 +312,54 @@ mod tests {
              '2024-03-01 22:10:57' AND '2024-03-25 22:10:57'"
         );
     }
+
+    #[test]
+    fn condition_date_range_since() {
+        let now = chrono::NaiveDateTime::parse_from_str("2024-03-01 22:10:57", "%Y-%m-%d %H:%M:%S")
+            .unwrap();
+
+        let condition = dsl::condition()
+            .date_range(test_db::Column::CreatedAt)
+            .since_at(&FixedClock(now), chrono::Duration::hours(2))
+            .build();
+
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition.build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN \
+             '2024-03-01 20:10:57.000000' AND '2024-03-01 22:10:57.000000'"
+        );
+    }
+
+    #[test]
+    fn condition_date_range_to_inclusive() {
+        let date =
+            chrono::NaiveDateTime::parse_from_str("2024-03-01 22:10:57", "%Y-%m-%d %H:%M:%S")
+                .unwrap();
+
+        let condition = dsl::condition()
+            .date_range(test_db::Column::CreatedAt)
+            .to(&date)
+            .to_inclusive(true)
+            .build();
+
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition.build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" <= '2024-03-01 \
+             22:10:57.000000'"
+        );
+    }
 }
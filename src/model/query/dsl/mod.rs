@@ -1,10 +1,26 @@
+use std::ops::{Range, RangeInclusive};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+pub use ipnetwork::IpNetwork;
 use sea_orm::{
-    sea_query::{IntoCondition, Order},
-    ColumnTrait, Condition, Value,
+    sea_query::{
+        extension::postgres::PgExpr, Alias, BinOper, ColumnType, Expr, Func, IntoCondition,
+        LikeExpr, Order, Query, SelectStatement, SimpleExpr,
+    },
+    ColumnTrait, Condition, DbBackend, Value,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::model::{ModelError, ModelResult};
+
+mod clock;
 mod date_range;
+mod order;
+mod search;
+
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use order::OrderBuilder;
+pub use search::RankedSearch;
 
 // pub mod pagination;
 
@@ -40,6 +56,12 @@ pub fn condition() -> ConditionBuilder {
     }
 }
 
+/// Starts an [`OrderBuilder`], the sorting counterpart of [`condition`]
+#[must_use]
+pub fn order() -> OrderBuilder {
+    OrderBuilder::default()
+}
+
 #[must_use]
 pub const fn with(condition: Condition) -> ConditionBuilder {
     ConditionBuilder { condition }
@@ -157,12 +179,620 @@ pub fn is_not_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
     condition().is_not_in(col, values)
 }
 
+/// See [`ConditionBuilder::modulo_eq`]
+#[must_use]
+pub fn modulo_eq<T: ColumnTrait, V: Into<Value>>(
+    col: T,
+    divisor: V,
+    remainder: V,
+) -> ConditionBuilder {
+    condition().modulo_eq(col, divisor, remainder)
+}
+
+/// See [`ConditionBuilder::char_length_between`]
+#[must_use]
+pub fn char_length_between<T: ColumnTrait, V: Into<Value>>(
+    col: T,
+    min: V,
+    max: V,
+) -> ConditionBuilder {
+    condition().char_length_between(col, min, max)
+}
+
+/// See [`ConditionBuilder::json_contains_any`]
+#[must_use]
+pub fn json_contains_any<T: ColumnTrait, V: Into<String>, I: IntoIterator<Item = V>>(
+    col: T,
+    values: I,
+) -> ConditionBuilder {
+    condition().json_contains_any(col, values)
+}
+
+/// See [`ConditionBuilder::json_contains_all`]
+#[must_use]
+pub fn json_contains_all<T: ColumnTrait, V: Into<String>, I: IntoIterator<Item = V>>(
+    col: T,
+    values: I,
+) -> ConditionBuilder {
+    condition().json_contains_all(col, values)
+}
+
+/// Builds `col <op> $1::text[]` for the `jsonb` key existence operators, with
+/// the values bound as a single `Postgres` array literal.
+///
+/// The operator goes through [`BinOper::Custom`] so its `?` is never mistaken
+/// for a placeholder.
+fn jsonb_exists<T: ColumnTrait, V: Into<String>, I: IntoIterator<Item = V>>(
+    col: T,
+    op: &'static str,
+    values: I,
+) -> SimpleExpr {
+    let elements = values
+        .into_iter()
+        .map(|v| array_element(v.into()))
+        .collect::<Vec<_>>()
+        .join(",");
+    col.into_expr().binary(
+        BinOper::Custom(op),
+        Expr::cust_with_values("$1::text[]", [format!("{{{elements}}}")]),
+    )
+}
+
+/// Quotes a `Postgres` array literal element when it would otherwise be
+/// misread, escaping `"` and `\`.
+fn array_element(value: String) -> String {
+    let plain = !value.is_empty()
+        && !value.eq_ignore_ascii_case("null")
+        && !value
+            .chars()
+            .any(|c| matches!(c, '{' | '}' | ',' | '"' | '\\') || c.is_whitespace());
+    if plain {
+        value
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// See [`ConditionBuilder::date_in_range`]
+#[must_use]
+pub fn date_in_range<T: ColumnTrait>(col: T, range: RangeInclusive<NaiveDate>) -> ConditionBuilder {
+    condition().date_in_range(col, range)
+}
+
+/// See [`ConditionBuilder::date_in_range_exclusive`]
+#[must_use]
+pub fn date_in_range_exclusive<T: ColumnTrait>(
+    col: T,
+    range: Range<NaiveDate>,
+) -> ConditionBuilder {
+    condition().date_in_range_exclusive(col, range)
+}
+
+/// Whether the column holds a date and a time, rather than a plain date.
+fn is_timestamp<T: ColumnTrait>(col: &T) -> bool {
+    matches!(
+        col.def().get_column_type(),
+        ColumnType::DateTime | ColumnType::Timestamp | ColumnType::TimestampWithTimeZone
+    )
+}
+
+/// See [`ConditionBuilder::coalesce_in`]
+#[must_use]
+pub fn coalesce_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+    col: T,
+    default: V,
+    values: I,
+) -> ConditionBuilder {
+    condition().coalesce_in(col, default, values)
+}
+
+/// See [`ConditionBuilder::within_next`]
+#[must_use]
+pub fn within_next<T: ColumnTrait>(col: T, duration: Duration) -> ConditionBuilder {
+    condition().within_next(col, duration)
+}
+
+/// See [`ConditionBuilder::within_past`]
+#[must_use]
+pub fn within_past<T: ColumnTrait>(col: T, duration: Duration) -> ConditionBuilder {
+    condition().within_past(col, duration)
+}
+
+/// See [`ConditionBuilder::any_of`]
+#[must_use]
+pub fn any_of(f: impl FnOnce(ConditionBuilder) -> ConditionBuilder) -> ConditionBuilder {
+    condition().any_of(f)
+}
+
+/// See [`ConditionBuilder::all_of`]
+#[must_use]
+pub fn all_of(f: impl FnOnce(ConditionBuilder) -> ConditionBuilder) -> ConditionBuilder {
+    condition().all_of(f)
+}
+
+/// See [`ConditionBuilder::ilike`]
+#[must_use]
+pub fn ilike<T: ColumnTrait, V: Into<String>>(
+    backend: DbBackend,
+    col: T,
+    pattern: V,
+) -> ConditionBuilder {
+    condition().ilike(backend, col, pattern)
+}
+
+/// See [`ConditionBuilder::not_ilike`]
+#[must_use]
+pub fn not_ilike<T: ColumnTrait, V: Into<String>>(
+    backend: DbBackend,
+    col: T,
+    pattern: V,
+) -> ConditionBuilder {
+    condition().not_ilike(backend, col, pattern)
+}
+
+/// Case-insensitive `LIKE`: `ILIKE` on `Postgres`, and
+/// `LOWER(col) LIKE LOWER(pattern)` on backends without it.
+fn ilike_expr<T: ColumnTrait>(
+    backend: DbBackend,
+    col: T,
+    pattern: String,
+    negate: bool,
+) -> SimpleExpr {
+    match backend {
+        DbBackend::Postgres if negate => col.into_expr().not_ilike(pattern),
+        DbBackend::Postgres => col.into_expr().ilike(pattern),
+        DbBackend::MySql | DbBackend::Sqlite => {
+            let lowered = Expr::expr(Func::lower(col.into_expr()));
+            let pattern = Func::lower(Expr::val(pattern));
+            if negate {
+                lowered.binary(BinOper::NotLike, pattern)
+            } else {
+                lowered.binary(BinOper::Like, pattern)
+            }
+        }
+    }
+}
+
+/// See [`ConditionBuilder::not`]
+#[must_use]
+pub fn not(f: impl FnOnce(ConditionBuilder) -> ConditionBuilder) -> ConditionBuilder {
+    condition().not(f)
+}
+
+/// See [`ConditionBuilder::contains_escaped`]
+#[must_use]
+pub fn contains_escaped<T: ColumnTrait, V: AsRef<str>>(col: T, term: V) -> ConditionBuilder {
+    condition().contains_escaped(col, term)
+}
+
+/// See [`ConditionBuilder::starts_with_escaped`]
+#[must_use]
+pub fn starts_with_escaped<T: ColumnTrait, V: AsRef<str>>(col: T, term: V) -> ConditionBuilder {
+    condition().starts_with_escaped(col, term)
+}
+
+/// See [`ConditionBuilder::ends_with_escaped`]
+#[must_use]
+pub fn ends_with_escaped<T: ColumnTrait, V: AsRef<str>>(col: T, term: V) -> ConditionBuilder {
+    condition().ends_with_escaped(col, term)
+}
+
+/// Escapes the `LIKE` wildcards `%` and `_`, and the escape character `\`
+/// itself, so `term` only matches literally.
+fn escape_like(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len());
+    for c in term.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn like_escaped<T: ColumnTrait>(col: T, pattern: String) -> SimpleExpr {
+    col.like(LikeExpr::new(pattern).escape('\\'))
+}
+
+/// `to_tsvector(document)`, in the given text search configuration if any
+fn tsvector(config: Option<&str>, document: SimpleExpr) -> SimpleExpr {
+    Func::cust(Alias::new("to_tsvector"))
+        .args(
+            config
+                .map(|config| Expr::val(config).into())
+                .into_iter()
+                .chain([document]),
+        )
+        .into()
+}
+
+/// `plainto_tsquery(term)`, in the given text search configuration if any
+fn tsquery(config: Option<&str>, term: &str) -> SimpleExpr {
+    Func::cust(Alias::new("plainto_tsquery"))
+        .args(
+            config
+                .map(|config| Expr::val(config).into())
+                .into_iter()
+                .chain([Expr::val(term).into()]),
+        )
+        .into()
+}
+
+/// The full-text search fallback of the backends without one: a
+/// case-insensitive match of the literal term
+fn contains_ignore_case(col: SimpleExpr, term: &str) -> SimpleExpr {
+    let pattern = format!("%{}%", escape_like(&term.to_lowercase()));
+    Expr::expr(Func::lower(col)).like(LikeExpr::new(pattern).escape('\\'))
+}
+
+/// See [`ConditionBuilder::add_if`]
+#[must_use]
+pub fn add_if(
+    cond: bool,
+    f: impl FnOnce(ConditionBuilder) -> ConditionBuilder,
+) -> ConditionBuilder {
+    condition().add_if(cond, f)
+}
+
+/// See [`ConditionBuilder::add_some`]
+#[must_use]
+pub fn add_some<V>(
+    opt: Option<V>,
+    f: impl FnOnce(ConditionBuilder, V) -> ConditionBuilder,
+) -> ConditionBuilder {
+    condition().add_some(opt, f)
+}
+
+/// See [`ConditionBuilder::col_eq`]
+#[must_use]
+pub fn col_eq<A: ColumnTrait, B: ColumnTrait>(a: A, b: B) -> ConditionBuilder {
+    condition().col_eq(a, b)
+}
+
+/// See [`ConditionBuilder::col_ne`]
+#[must_use]
+pub fn col_ne<A: ColumnTrait, B: ColumnTrait>(a: A, b: B) -> ConditionBuilder {
+    condition().col_ne(a, b)
+}
+
+/// See [`ConditionBuilder::col_gt`]
+#[must_use]
+pub fn col_gt<A: ColumnTrait, B: ColumnTrait>(a: A, b: B) -> ConditionBuilder {
+    condition().col_gt(a, b)
+}
+
+/// See [`ConditionBuilder::col_gte`]
+#[must_use]
+pub fn col_gte<A: ColumnTrait, B: ColumnTrait>(a: A, b: B) -> ConditionBuilder {
+    condition().col_gte(a, b)
+}
+
+/// See [`ConditionBuilder::col_lt`]
+#[must_use]
+pub fn col_lt<A: ColumnTrait, B: ColumnTrait>(a: A, b: B) -> ConditionBuilder {
+    condition().col_lt(a, b)
+}
+
+/// See [`ConditionBuilder::col_lte`]
+#[must_use]
+pub fn col_lte<A: ColumnTrait, B: ColumnTrait>(a: A, b: B) -> ConditionBuilder {
+    condition().col_lte(a, b)
+}
+
+/// See [`ConditionBuilder::col_gt_by`]
+#[must_use]
+pub fn col_gt_by<A: ColumnTrait, B: ColumnTrait>(
+    backend: DbBackend,
+    end: A,
+    start: B,
+    offset: Duration,
+) -> ConditionBuilder {
+    condition().col_gt_by(backend, end, start, offset)
+}
+
+/// See [`ConditionBuilder::search_ranked`]
+#[must_use]
+pub fn search_ranked<T: ColumnTrait, I: IntoIterator<Item = T>>(
+    backend: DbBackend,
+    columns: I,
+    term: &str,
+) -> RankedSearch {
+    condition().search_ranked(backend, columns, term)
+}
+
+/// See [`ConditionBuilder::full_text`]
+#[must_use]
+pub fn full_text<T: ColumnTrait>(backend: DbBackend, col: T, query: &str) -> ConditionBuilder {
+    condition().full_text(backend, col, query)
+}
+
+/// See [`ConditionBuilder::full_text_in`]
+#[must_use]
+pub fn full_text_in<T: ColumnTrait>(
+    backend: DbBackend,
+    config: &str,
+    col: T,
+    query: &str,
+) -> ConditionBuilder {
+    condition().full_text_in(backend, config, col, query)
+}
+
+/// See [`ConditionBuilder::eq_any_or_all`]
+#[must_use]
+pub fn eq_any_or_all<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+    col: T,
+    values: I,
+) -> ConditionBuilder {
+    condition().eq_any_or_all(col, values)
+}
+
+/// See [`ConditionBuilder::json_eq`]
+#[must_use]
+pub fn json_eq<T: ColumnTrait, V: Into<Value>>(
+    backend: DbBackend,
+    col: T,
+    path: &str,
+    value: V,
+) -> ConditionBuilder {
+    condition().json_eq(backend, col, path, value)
+}
+
+/// See [`ConditionBuilder::ip_in_subnet`]
+#[must_use]
+pub fn ip_in_subnet<T: ColumnTrait>(col: T, network: IpNetwork) -> ConditionBuilder {
+    condition().ip_in_subnet(col, network)
+}
+
+/// Parses an `address/prefix` block, such as user input for
+/// [`ConditionBuilder::ip_in_subnet`]. Unlike parsing an [`IpNetwork`], a
+/// bare address is rejected.
+///
+/// # Errors
+/// When `cidr` is not a valid `address/prefix` block.
+pub fn parse_cidr(cidr: &str) -> ModelResult<IpNetwork> {
+    let invalid = || ModelError::Any(format!("invalid CIDR block: `{cidr}`").into());
+    if !cidr.contains('/') {
+        return Err(invalid());
+    }
+    cidr.parse::<IpNetwork>().map_err(|_| invalid())
+}
+
+/// See [`ConditionBuilder::nullif_eq`]
+#[must_use]
+pub fn nullif_eq<T: ColumnTrait, S: Into<Value>, V: Into<Value>>(
+    col: T,
+    sentinel: S,
+    value: V,
+) -> ConditionBuilder {
+    condition().nullif_eq(col, sentinel, value)
+}
+
+/// See [`ConditionBuilder::between_percentiles`]
+#[must_use]
+pub fn between_percentiles<T: ColumnTrait>(
+    backend: DbBackend,
+    col: T,
+    low: f64,
+    high: f64,
+) -> ConditionBuilder {
+    condition().between_percentiles(backend, col, low, high)
+}
+
+/// `(SELECT percentile_cont(fraction) WITHIN GROUP (ORDER BY col) FROM table)`
+fn percentile<T: ColumnTrait>(col: T, fraction: f64) -> SimpleExpr {
+    SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            Query::select()
+                .expr(Expr::cust_with_exprs(
+                    "percentile_cont($1) WITHIN GROUP (ORDER BY $2)",
+                    [Expr::val(fraction).into(), col.into_expr().into()],
+                ))
+                .from(col.entity_name())
+                .to_owned()
+                .into_sub_query_statement(),
+        ),
+    )
+}
+
+/// A type a `jsonb` field can be cast to for comparison, see
+/// [`ConditionBuilder::json_field_as_gt`].
+pub trait JsonCast: Into<Value> {
+    /// The `Postgres` type the extracted text is cast to.
+    const SQL_TYPE: &'static str;
+}
+
+impl JsonCast for i16 {
+    const SQL_TYPE: &'static str = "smallint";
+}
+
+impl JsonCast for i32 {
+    const SQL_TYPE: &'static str = "int";
+}
+
+impl JsonCast for i64 {
+    const SQL_TYPE: &'static str = "bigint";
+}
+
+impl JsonCast for f32 {
+    const SQL_TYPE: &'static str = "real";
+}
+
+impl JsonCast for f64 {
+    const SQL_TYPE: &'static str = "double precision";
+}
+
+impl JsonCast for bool {
+    const SQL_TYPE: &'static str = "boolean";
+}
+
+/// Builds `(col #>> '{path}')::type` to extract the field at the dotted
+/// `path` cast to the `Postgres` type of `V`.
+fn json_field_as<T: ColumnTrait, V: JsonCast>(col: T, path: &str) -> Expr {
+    let path = path
+        .split('.')
+        .map(|key| array_element(key.to_string()))
+        .collect::<Vec<_>>()
+        .join(",");
+    Expr::expr(Expr::cust_with_exprs(
+        format!("($1 #>> $2)::{}", V::SQL_TYPE),
+        [
+            col.into_expr().into(),
+            Expr::val(format!("{{{path}}}")).into(),
+        ],
+    ))
+}
+
+/// See [`ConditionBuilder::json_field_as_eq`]
+#[must_use]
+pub fn json_field_as_eq<T: ColumnTrait, V: JsonCast>(
+    col: T,
+    path: &str,
+    value: V,
+) -> ConditionBuilder {
+    condition().json_field_as_eq(col, path, value)
+}
+
+/// See [`ConditionBuilder::json_field_as_gt`]
+#[must_use]
+pub fn json_field_as_gt<T: ColumnTrait, V: JsonCast>(
+    col: T,
+    path: &str,
+    value: V,
+) -> ConditionBuilder {
+    condition().json_field_as_gt(col, path, value)
+}
+
+/// See [`ConditionBuilder::json_field_as_gte`]
+#[must_use]
+pub fn json_field_as_gte<T: ColumnTrait, V: JsonCast>(
+    col: T,
+    path: &str,
+    value: V,
+) -> ConditionBuilder {
+    condition().json_field_as_gte(col, path, value)
+}
+
+/// See [`ConditionBuilder::json_field_as_lt`]
+#[must_use]
+pub fn json_field_as_lt<T: ColumnTrait, V: JsonCast>(
+    col: T,
+    path: &str,
+    value: V,
+) -> ConditionBuilder {
+    condition().json_field_as_lt(col, path, value)
+}
+
+/// See [`ConditionBuilder::json_field_as_lte`]
+#[must_use]
+pub fn json_field_as_lte<T: ColumnTrait, V: JsonCast>(
+    col: T,
+    path: &str,
+    value: V,
+) -> ConditionBuilder {
+    condition().json_field_as_lte(col, path, value)
+}
+
+/// See [`ConditionBuilder::array_contains`]
+#[must_use]
+pub fn array_contains<T: ColumnTrait, V: Into<Value>>(col: T, values: Vec<V>) -> ConditionBuilder {
+    condition().array_contains(col, values)
+}
+
+/// See [`ConditionBuilder::array_overlaps`]
+#[must_use]
+pub fn array_overlaps<T: ColumnTrait, V: Into<Value>>(col: T, values: Vec<V>) -> ConditionBuilder {
+    condition().array_overlaps(col, values)
+}
+
+/// `col <op> ARRAY[..]`, with one bound parameter per value
+fn pg_array_op<T: ColumnTrait, V: Into<Value>>(op: &str, col: T, values: Vec<V>) -> SimpleExpr {
+    // custom expressions read brackets as quotes, so the brackets are
+    // expressions of their own, apart from the values since a placeholder
+    // cannot be followed by another: `$1 <op> $2 $3, $4 $5` for two values
+    let len = values.len();
+    let placeholders = (3..len + 3)
+        .map(|index| format!("${index}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let exprs = [col.into_expr().into(), Expr::cust("ARRAY[")]
+        .into_iter()
+        .chain(values.into_iter().map(|value| Expr::val(value).into()))
+        .chain([Expr::cust("]")]);
+    Expr::cust_with_exprs(format!("$1 {op} $2 {placeholders} ${}", len + 3), exprs)
+}
+
+/// See [`ConditionBuilder::exists`]
+#[must_use]
+pub fn exists(subquery: SelectStatement) -> ConditionBuilder {
+    condition().exists(subquery)
+}
+
+/// See [`ConditionBuilder::not_exists`]
+#[must_use]
+pub fn not_exists(subquery: SelectStatement) -> ConditionBuilder {
+    condition().not_exists(subquery)
+}
+
+/// See [`ConditionBuilder::exists_related`]
+#[must_use]
+pub fn exists_related<R: ColumnTrait, M: ColumnTrait>(fk: R, pk: M) -> ConditionBuilder {
+    condition().exists_related(fk, pk)
+}
+
+/// See [`ConditionBuilder::related_count_gte`]
+#[must_use]
+pub fn related_count_gte<R: ColumnTrait, M: ColumnTrait>(
+    fk: R,
+    pk: M,
+    count: u64,
+) -> ConditionBuilder {
+    condition().related_count_gte(fk, pk, count)
+}
+
+/// `SELECT expr FROM related WHERE related.fk = main.pk`
+fn correlated<R: ColumnTrait, M: ColumnTrait>(fk: R, pk: M, expr: SimpleExpr) -> SelectStatement {
+    Query::select()
+        .expr(expr)
+        .from(fk.entity_name())
+        .and_where(fk.into_expr().eq(pk.into_expr()))
+        .to_owned()
+}
+
+/// See [`ConditionBuilder::normalized_eq`]
+#[must_use]
+pub fn normalized_eq<T: ColumnTrait, V: AsRef<str>>(
+    backend: DbBackend,
+    col: T,
+    value: V,
+) -> ConditionBuilder {
+    condition().normalized_eq(backend, col, value)
+}
+
+/// Lowercases, trims and collapses the whitespace runs of a value, like the
+/// column side of [`ConditionBuilder::normalized_eq`].
+fn normalize_text(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
 /// See [`ConditionBuilder::date_range`]
 #[must_use]
 pub fn date_range<T: ColumnTrait>(col: T) -> date_range::DateRangeBuilder<T> {
     date_range::DateRangeBuilder::new(condition(), col)
 }
 
+/// See [`ConditionBuilder::date_range_tz`]
+#[must_use]
+pub fn date_range_tz<T: ColumnTrait>(
+    col: T,
+) -> date_range::DateRangeBuilder<T, DateTime<FixedOffset>> {
+    date_range::DateRangeBuilder::new(condition(), col)
+}
+
 impl IntoCondition for ConditionBuilder {
     fn into_condition(self) -> Condition {
         self.build()
@@ -599,8 +1229,8 @@ impl ConditionBuilder {
         with(self.condition.add(col.is_not_null()))
     }
 
-    /// where condition the given column is in
-    /// value
+    /// where condition the row is not soft deleted, its given `deleted_at`
+    /// column being null. See [`crate::model::SoftDelete`].
     ///
     /// # Examples
     /// ```
@@ -611,20 +1241,48 @@ impl ConditionBuilder {
     /// let query_str = test_db::Entity::find()
     ///         .select_only()
     ///         .column(test_db::Column::Id)
-    ///         .filter(query::condition().is_in(test_db::Column::Id, [1]).build())
+    ///         .filter(query::condition().not_deleted(test_db::Column::UpdatedAt).build())
     ///         .build(sea_orm::DatabaseBackend::Postgres)
     ///         .to_string();
     ///
     ///     assert_eq!(
     ///         query_str,
-    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" IN (1)"
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"updated_at\" IS NULL"
     ///     );
     /// ````
     #[must_use]
-    #[allow(clippy::wrong_self_convention)]
-    pub fn is_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
-        self,
-        col: T,
+    pub fn not_deleted<T: ColumnTrait>(self, col: T) -> Self {
+        self.is_null(col)
+    }
+
+    /// where condition the given column is in
+    /// value
+    ///
+    /// An empty set of values matches nothing (`1 = 2`).
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().is_in(test_db::Column::Id, [1, 2, 3]).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" IN (1, 2, 3)"
+    ///     );
+    /// ````
+    #[must_use]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn is_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+        self,
+        col: T,
         values: I,
     ) -> Self {
         with(self.condition.add(col.is_in(values)))
@@ -633,32 +1291,1871 @@ impl ConditionBuilder {
     /// where condition the given column is not in
     /// value
     ///
+    /// An empty set of values matches everything (`1 = 1`).
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().is_not_in(test_db::Column::Id, [1, 2, 3]).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" NOT IN (1, 2, 3)"
+    ///     );
+    /// ````
+    #[must_use]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn is_not_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+        self,
+        col: T,
+        values: I,
+    ) -> Self {
+        with(self.condition.add(col.is_not_in(values)))
+    }
+
+    /// where condition the given column modulo the divisor equals the given
+    /// remainder. useful for sharding or sampling rows (`WHERE id % 10 = 0`).
+    ///
+    /// The `%` operator is rendered for every backend, `Postgres`, `MySQL` and
+    /// `SQLite` all accept it, so there is no need for the `MOD(...)` form.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().modulo_eq(test_db::Column::Id, 10, 0).build());
+    ///
+    ///     assert_eq!(
+    ///         query.build(sea_orm::DatabaseBackend::Postgres).to_string(),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" % 10 = 0"
+    ///     );
+    ///     assert_eq!(
+    ///         query.build(sea_orm::DatabaseBackend::MySql).to_string(),
+    ///         "SELECT `loco`.`id` FROM `loco` WHERE `loco`.`id` % 10 = 0"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn modulo_eq<T: ColumnTrait, V: Into<Value>>(
+        self,
+        col: T,
+        divisor: V,
+        remainder: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(col.into_expr().modulo(divisor).eq(remainder)),
+        )
+    }
+
+    /// where condition the number of characters in the given column is
+    /// between the given values
+    ///
+    /// Characters are counted rather than bytes, so multibyte text is
+    /// measured correctly: `CHAR_LENGTH` is used on `Postgres` and `MySQL`,
+    /// and `LENGTH` (which counts characters for text) on `SQLite`.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().char_length_between(test_db::Column::Name, 5, 100).build());
+    ///
+    ///     assert_eq!(
+    ///         query.build(sea_orm::DatabaseBackend::Postgres).to_string(),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE CHAR_LENGTH(\"loco\".\"name\") BETWEEN 5 AND 100"
+    ///     );
+    ///     assert_eq!(
+    ///         query.build(sea_orm::DatabaseBackend::Sqlite).to_string(),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE LENGTH(\"loco\".\"name\") BETWEEN 5 AND 100"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn char_length_between<T: ColumnTrait, V: Into<Value>>(
+        self,
+        col: T,
+        min: V,
+        max: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(Expr::expr(Func::char_length(col.into_expr())).between(min, max)),
+        )
+    }
+
+    /// where condition the given `jsonb` column contains any of the given
+    /// values, as a top-level array element or object key (`?|`)
+    ///
+    /// `Postgres` only.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().json_contains_any(test_db::Column::Name, ["rust", "web"]).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" ?| ('{rust,web}'::text[])"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn json_contains_any<T: ColumnTrait, V: Into<String>, I: IntoIterator<Item = V>>(
+        self,
+        col: T,
+        values: I,
+    ) -> Self {
+        with(self.condition.add(jsonb_exists(col, "?|", values)))
+    }
+
+    /// where condition the given `jsonb` column contains all of the given
+    /// values, as top-level array elements or object keys (`?&`)
+    ///
+    /// `Postgres` only.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().json_contains_all(test_db::Column::Name, ["rust", "web"]).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" ?& ('{rust,web}'::text[])"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn json_contains_all<T: ColumnTrait, V: Into<String>, I: IntoIterator<Item = V>>(
+        self,
+        col: T,
+        values: I,
+    ) -> Self {
+        with(self.condition.add(jsonb_exists(col, "?&", values)))
+    }
+
+    /// where condition the given date column is within the given inclusive
+    /// range of days
+    ///
+    /// On a timestamp column the range is expanded to whole days, from the
+    /// start of the first day to the end of the last one.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let from = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    /// let to = chrono::NaiveDate::from_ymd_opt(2024, 3, 25).unwrap();
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().date_in_range(test_db::Column::ReleasedOn, from..=to).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"released_on\" BETWEEN '2024-03-01' AND '2024-03-25'"
+    ///     );
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().date_in_range(test_db::Column::CreatedAt, from..=to).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN '2024-03-01 00:00:00.000000' AND '2024-03-25 23:59:59.999999'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn date_in_range<T: ColumnTrait>(self, col: T, range: RangeInclusive<NaiveDate>) -> Self {
+        let (from, to) = range.into_inner();
+        let expr = if is_timestamp(&col) {
+            let end_of_day = to
+                .and_hms_micro_opt(23, 59, 59, 999_999)
+                .unwrap_or_else(|| to.and_time(NaiveTime::MIN));
+            col.between(from.and_time(NaiveTime::MIN), end_of_day)
+        } else {
+            col.between(from, to)
+        };
+        with(self.condition.add(expr))
+    }
+
+    /// where condition the given date column is within the given half-open
+    /// range of days, excluding the end day
+    ///
+    /// On a timestamp column the range starts at the beginning of the first
+    /// day and stops right before the end day begins.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let from = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    /// let to = chrono::NaiveDate::from_ymd_opt(2024, 4, 1).unwrap();
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().date_in_range_exclusive(test_db::Column::ReleasedOn, from..to).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"released_on\" >= '2024-03-01' AND \"loco\".\"released_on\" < '2024-04-01'"
+    ///     );
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().date_in_range_exclusive(test_db::Column::CreatedAt, from..to).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" >= '2024-03-01 00:00:00.000000' AND \"loco\".\"created_at\" < '2024-04-01 00:00:00.000000'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn date_in_range_exclusive<T: ColumnTrait>(self, col: T, range: Range<NaiveDate>) -> Self {
+        let condition = if is_timestamp(&col) {
+            self.condition
+                .add(col.gte(range.start.and_time(NaiveTime::MIN)))
+                .add(col.lt(range.end.and_time(NaiveTime::MIN)))
+        } else {
+            self.condition
+                .add(col.gte(range.start))
+                .add(col.lt(range.end))
+        };
+        with(condition)
+    }
+
+    /// where condition the given column, or `default` when it is null, is in
+    /// the given values
+    ///
+    /// An empty set of values matches nothing.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().coalesce_in(test_db::Column::Name, "active", ["active", "pending"]).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE COALESCE(\"loco\".\"name\", 'active') IN ('active', 'pending')"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn coalesce_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+        self,
+        col: T,
+        default: V,
+        values: I,
+    ) -> Self {
+        let coalesced = Func::coalesce([col.into_expr().into(), Expr::val(default).into()]);
+        with(self.condition.add(Expr::expr(coalesced).is_in(values)))
+    }
+
+    /// where condition the given column is between now and `duration` from
+    /// now
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().within_next(test_db::Column::CreatedAt, chrono::Duration::hours(48)).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    /// assert!(query_str.starts_with("SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN"));
+    /// ````
+    #[must_use]
+    pub fn within_next<T: ColumnTrait>(self, col: T, duration: Duration) -> Self {
+        self.within_next_at(&SystemClock, col, duration)
+    }
+
+    /// Like [`ConditionBuilder::within_next`], reading now from the given
+    /// [`Clock`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let now = chrono::NaiveDateTime::parse_from_str("2024-03-01 22:10:57", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let clock = query::FixedClock(now);
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().within_next_at(&clock, test_db::Column::CreatedAt, chrono::Duration::hours(48)).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN '2024-03-01 22:10:57.000000' AND '2024-03-03 22:10:57.000000'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn within_next_at<C: Clock, T: ColumnTrait>(
+        self,
+        clock: &C,
+        col: T,
+        duration: Duration,
+    ) -> Self {
+        let now = clock.now();
+        // a duration past the range of dates reaches the latest date
+        let until = now
+            .checked_add_signed(duration)
+            .unwrap_or(NaiveDateTime::MAX);
+        with(self.condition.add(col.between(now, until)))
+    }
+
+    /// where condition the given column is between `duration` ago and now
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().within_past(test_db::Column::CreatedAt, chrono::Duration::hours(48)).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    /// assert!(query_str.starts_with("SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN"));
+    /// ````
+    #[must_use]
+    pub fn within_past<T: ColumnTrait>(self, col: T, duration: Duration) -> Self {
+        self.within_past_at(&SystemClock, col, duration)
+    }
+
+    /// Like [`ConditionBuilder::within_past`], reading now from the given
+    /// [`Clock`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let now = chrono::NaiveDateTime::parse_from_str("2024-03-03 22:10:57", "%Y-%m-%d %H:%M:%S").unwrap();
+    /// let clock = query::FixedClock(now);
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().within_past_at(&clock, test_db::Column::CreatedAt, chrono::Duration::hours(48)).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN '2024-03-01 22:10:57.000000' AND '2024-03-03 22:10:57.000000'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn within_past_at<C: Clock, T: ColumnTrait>(
+        self,
+        clock: &C,
+        col: T,
+        duration: Duration,
+    ) -> Self {
+        let now = clock.now();
+        // a duration past the range of dates reaches the earliest date
+        let since = now
+            .checked_sub_signed(duration)
+            .unwrap_or(NaiveDateTime::MIN);
+        with(self.condition.add(col.between(since, now)))
+    }
+
+    /// where condition any of the conditions built by `f` matches
+    ///
+    /// `f` receives a fresh builder whose conditions are OR-ed together, and
+    /// the result is added to this builder as a single parenthesized group.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .any_of(|c| c.eq(test_db::Column::Id, 1).eq(test_db::Column::Name, "loco"))
+    ///                 .like(test_db::Column::Name, "%lo%")
+    ///                 .build(),
+    ///         )
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE (\"loco\".\"id\" = 1 OR \"loco\".\"name\" = 'loco') AND \"loco\".\"name\" LIKE '%lo%'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn any_of(self, f: impl FnOnce(Self) -> Self) -> Self {
+        let group = f(with(Condition::any()));
+        with(self.condition.add(group.condition))
+    }
+
+    /// where condition all of the conditions built by `f` match
+    ///
+    /// `f` receives a fresh builder whose conditions are AND-ed together,
+    /// which is mostly useful nested inside [`ConditionBuilder::any_of`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .any_of(|c| {
+    ///                     c.eq(test_db::Column::Id, 1)
+    ///                         .all_of(|c| c.gt(test_db::Column::Id, 10).eq(test_db::Column::Name, "loco"))
+    ///                 })
+    ///                 .build(),
+    ///         )
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" = 1 OR (\"loco\".\"id\" > 10 AND \"loco\".\"name\" = 'loco')"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn all_of(self, f: impl FnOnce(Self) -> Self) -> Self {
+        let group = f(condition());
+        with(self.condition.add(group.condition))
+    }
+
+    /// where condition the given column matches the pattern, ignoring case
+    ///
+    /// Emits `ILIKE` on `Postgres`. `MySQL` and `SQLite` have no `ILIKE`, so
+    /// both sides are lowercased instead: `LOWER(col) LIKE LOWER(pattern)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().ilike(DbBackend::Postgres, test_db::Column::Name, "%Lo%").build())
+    ///         .build(DbBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" ILIKE '%Lo%'"
+    ///     );
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().ilike(DbBackend::Sqlite, test_db::Column::Name, "%Lo%").build())
+    ///         .build(DbBackend::Sqlite)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE LOWER(\"loco\".\"name\") LIKE LOWER('%Lo%')"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn ilike<T: ColumnTrait, V: Into<String>>(
+        self,
+        backend: DbBackend,
+        col: T,
+        pattern: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(ilike_expr(backend, col, pattern.into(), false)),
+        )
+    }
+
+    /// where condition the given column does not match the pattern, ignoring
+    /// case
+    ///
+    /// Emits `NOT ILIKE` on `Postgres`, and
+    /// `LOWER(col) NOT LIKE LOWER(pattern)` on `MySQL` and `SQLite`.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().not_ilike(DbBackend::Postgres, test_db::Column::Name, "%Lo%").build())
+    ///         .build(DbBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" NOT ILIKE '%Lo%'"
+    ///     );
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().not_ilike(DbBackend::Sqlite, test_db::Column::Name, "%Lo%").build())
+    ///         .build(DbBackend::Sqlite)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE LOWER(\"loco\".\"name\") NOT LIKE LOWER('%Lo%')"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn not_ilike<T: ColumnTrait, V: Into<String>>(
+        self,
+        backend: DbBackend,
+        col: T,
+        pattern: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(ilike_expr(backend, col, pattern.into(), true)),
+        )
+    }
+
+    /// where condition the conditions built by `f` do not all match
+    ///
+    /// `f` receives a fresh builder whose conditions are AND-ed together, and
+    /// the result is added to this builder negated, as `NOT (...)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .gt(test_db::Column::Id, 1)
+    ///                 .not(|c| c.eq(test_db::Column::Name, "archived").is_null(test_db::Column::CreatedAt))
+    ///                 .build(),
+    ///         )
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" > 1 AND (NOT (\"loco\".\"name\" = 'archived' AND \"loco\".\"created_at\" IS NULL))"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn not(self, f: impl FnOnce(Self) -> Self) -> Self {
+        let group = f(condition());
+        with(self.condition.add(group.condition.not()))
+    }
+
+    /// where condition the given column contains the given term literally
+    ///
+    /// Unlike [`ConditionBuilder::contains`], `%`, `_` and `\` in the term
+    /// are escaped rather than treated as wildcards, which makes it the one to
+    /// use with user input such as a search box.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().contains_escaped(test_db::Column::Name, "50%").build())
+    ///         .build(sea_orm::DatabaseBackend::Sqlite)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE '%50\%%' ESCAPE '\'"#
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn contains_escaped<T: ColumnTrait, V: AsRef<str>>(self, col: T, term: V) -> Self {
+        let pattern = format!("%{}%", escape_like(term.as_ref()));
+        with(self.condition.add(like_escaped(col, pattern)))
+    }
+
+    /// where condition the given column starts with the given term literally
+    ///
+    /// See [`ConditionBuilder::contains_escaped`] for the escaping.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().starts_with_escaped(test_db::Column::Name, "lo_co").build())
+    ///         .build(sea_orm::DatabaseBackend::Sqlite)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE 'lo\_co%' ESCAPE '\'"#
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn starts_with_escaped<T: ColumnTrait, V: AsRef<str>>(self, col: T, term: V) -> Self {
+        let pattern = format!("{}%", escape_like(term.as_ref()));
+        with(self.condition.add(like_escaped(col, pattern)))
+    }
+
+    /// where condition the given column ends with the given term literally
+    ///
+    /// See [`ConditionBuilder::contains_escaped`] for the escaping.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().ends_with_escaped(test_db::Column::Name, "100%_off").build())
+    ///         .build(sea_orm::DatabaseBackend::Sqlite)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE '%100\%\_off' ESCAPE '\'"#
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn ends_with_escaped<T: ColumnTrait, V: AsRef<str>>(self, col: T, term: V) -> Self {
+        let pattern = format!("%{}", escape_like(term.as_ref()));
+        with(self.condition.add(like_escaped(col, pattern)))
+    }
+
+    /// adds the conditions built by `f` only when `cond` is true
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let only_named = true;
+    /// let newest = false;
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .add_if(only_named, |c| c.is_not_null(test_db::Column::Name))
+    ///                 .add_if(newest, |c| c.gt(test_db::Column::Id, 100))
+    ///                 .build(),
+    ///         )
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" IS NOT NULL"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn add_if(self, cond: bool, f: impl FnOnce(Self) -> Self) -> Self {
+        if cond {
+            f(self)
+        } else {
+            self
+        }
+    }
+
+    /// adds the conditions built by `f` with the value of `opt`, only when it
+    /// is `Some`
+    ///
+    /// Handy for optional filters, such as query string parameters.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let name: Option<&str> = Some("%lo%");
+    /// let min_id: Option<i32> = None;
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .add_some(name, |c, name| c.like(test_db::Column::Name, name))
+    ///                 .add_some(min_id, |c, id| c.gte(test_db::Column::Id, id))
+    ///                 .build(),
+    ///         )
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo%'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn add_some<V>(self, opt: Option<V>, f: impl FnOnce(Self, V) -> Self) -> Self {
+        match opt {
+            Some(value) => f(self, value),
+            None => self,
+        }
+    }
+
+    /// where condition the first column equals the second column
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().col_eq(test_db::Column::CreatedAt, test_db::Column::UpdatedAt).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" = \"loco\".\"updated_at\""
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_eq<A: ColumnTrait, B: ColumnTrait>(self, a: A, b: B) -> Self {
+        with(self.condition.add(a.into_expr().eq(b.into_expr())))
+    }
+
+    /// where condition the first column not equals the second column
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().col_ne(test_db::Column::CreatedAt, test_db::Column::UpdatedAt).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" <> \"loco\".\"updated_at\""
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_ne<A: ColumnTrait, B: ColumnTrait>(self, a: A, b: B) -> Self {
+        with(self.condition.add(a.into_expr().ne(b.into_expr())))
+    }
+
+    /// where condition the first column greater than the second column
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().col_gt(test_db::Column::CreatedAt, test_db::Column::UpdatedAt).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" > \"loco\".\"updated_at\""
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_gt<A: ColumnTrait, B: ColumnTrait>(self, a: A, b: B) -> Self {
+        with(self.condition.add(a.into_expr().gt(b.into_expr())))
+    }
+
+    /// where condition the first column greater than or equal to the second
+    /// column
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().col_gte(test_db::Column::CreatedAt, test_db::Column::UpdatedAt).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" >= \"loco\".\"updated_at\""
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_gte<A: ColumnTrait, B: ColumnTrait>(self, a: A, b: B) -> Self {
+        with(self.condition.add(a.into_expr().gte(b.into_expr())))
+    }
+
+    /// where condition the first column less than the second column
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().col_lt(test_db::Column::CreatedAt, test_db::Column::UpdatedAt).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" < \"loco\".\"updated_at\""
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_lt<A: ColumnTrait, B: ColumnTrait>(self, a: A, b: B) -> Self {
+        with(self.condition.add(a.into_expr().lt(b.into_expr())))
+    }
+
+    /// where condition the first column less than or equal to the second column
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().col_lte(test_db::Column::CreatedAt, test_db::Column::UpdatedAt).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" <= \"loco\".\"updated_at\""
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_lte<A: ColumnTrait, B: ColumnTrait>(self, a: A, b: B) -> Self {
+        with(self.condition.add(a.into_expr().lte(b.into_expr())))
+    }
+
+    /// where condition the first column is later than the second column
+    /// shifted by `offset`, as in "updated more than an hour after creation"
+    ///
+    /// The interval arithmetic differs per backend: `SQLite` has no interval
+    /// type, so both sides are compared as `julianday` values there. The
+    /// offset is truncated to whole seconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = |backend| {
+    ///     test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .col_gt_by(
+    ///                     backend,
+    ///                     test_db::Column::UpdatedAt,
+    ///                     test_db::Column::CreatedAt,
+    ///                     chrono::Duration::hours(1),
+    ///                 )
+    ///                 .build(),
+    ///         )
+    ///         .build(backend)
+    ///         .to_string()
+    /// };
+    ///
+    ///     assert_eq!(
+    ///         query_str(DbBackend::Postgres),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"updated_at\" > \"loco\".\"created_at\" + (INTERVAL '3600 seconds')"
+    ///     );
+    ///     assert_eq!(
+    ///         query_str(DbBackend::Sqlite),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE julianday(\"loco\".\"updated_at\") > julianday(\"loco\".\"created_at\", '+3600 seconds')"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn col_gt_by<A: ColumnTrait, B: ColumnTrait>(
+        self,
+        backend: DbBackend,
+        end: A,
+        start: B,
+        offset: Duration,
+    ) -> Self {
+        let seconds = offset.num_seconds();
+        let expr = match backend {
+            DbBackend::Postgres => end.into_expr().gt(SimpleExpr::from(start.into_expr())
+                .add(Expr::cust(format!("INTERVAL '{seconds} seconds'")))),
+            DbBackend::MySql => end.into_expr().gt(SimpleExpr::from(start.into_expr())
+                .add(Expr::cust(format!("INTERVAL {seconds} SECOND")))),
+            DbBackend::Sqlite => Expr::expr(
+                Func::cust(Alias::new("julianday")).arg(end.into_expr()),
+            )
+            .gt(Func::cust(Alias::new("julianday")).args([
+                SimpleExpr::from(start.into_expr()),
+                Expr::val(format!("{seconds:+} seconds")).into(),
+            ])),
+        };
+        with(self.condition.add(expr))
+    }
+
+    /// where condition the given columns match the search term, along with
+    /// the relevance ranking to order the results by
+    ///
+    /// On `Postgres` the columns are searched as one full-text document, with
+    /// `@@` for the filter and `ts_rank` for the ranking, both built from the
+    /// same document and query so they cannot disagree. Other backends fall
+    /// back to a case-insensitive match of the literal term in any of the
+    /// columns, without ranking.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let search = query::condition().search_ranked(DbBackend::Postgres, [test_db::Column::Name], "loco app");
+    /// let query_str = search
+    ///         .order(test_db::Entity::find().select_only().column(test_db::Column::Id))
+    ///         .filter(search.condition.build())
+    ///         .build(DbBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE to_tsvector(concat_ws(' ', \"loco\".\"name\")) @@ plainto_tsquery('loco app') ORDER BY ts_rank(to_tsvector(concat_ws(' ', \"loco\".\"name\")), plainto_tsquery('loco app')) DESC"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn search_ranked<T: ColumnTrait, I: IntoIterator<Item = T>>(
+        self,
+        backend: DbBackend,
+        columns: I,
+        term: &str,
+    ) -> RankedSearch {
+        let columns = columns
+            .into_iter()
+            .map(|col| SimpleExpr::from(col.into_expr()));
+        match backend {
+            DbBackend::Postgres => {
+                let document = tsvector(
+                    None,
+                    Func::cust(Alias::new("concat_ws"))
+                        .args(std::iter::once(Expr::val(" ").into()).chain(columns))
+                        .into(),
+                );
+                let query = tsquery(None, term);
+                let filter = Expr::expr(document.clone()).matches(query.clone());
+                RankedSearch {
+                    condition: with(self.condition.add(filter)),
+                    rank: Some(
+                        Func::cust(Alias::new("ts_rank"))
+                            .args([document, query])
+                            .into(),
+                    ),
+                }
+            }
+            DbBackend::MySql | DbBackend::Sqlite => {
+                let any = columns.fold(Condition::any(), |any, col| {
+                    any.add(contains_ignore_case(col, term))
+                });
+                RankedSearch {
+                    condition: with(self.condition.add(any)),
+                    rank: None,
+                }
+            }
+        }
+    }
+
+    /// where condition the given column matches the full-text search query
+    ///
+    /// On `Postgres` this is `to_tsvector(col) @@ plainto_tsquery(query)`,
+    /// with the default text search configuration. Postgres cannot index
+    /// that expression, as it depends on the `default_text_search_config`
+    /// setting, so use [`ConditionBuilder::full_text_in`] on indexed columns.
+    ///
+    /// Other backends fall back to a case-insensitive `LIKE` match of the
+    /// literal query, like [`ConditionBuilder::search_ranked`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().full_text(DbBackend::Postgres, test_db::Column::Name, "loco app").build())
+    ///         .build(DbBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE to_tsvector(\"loco\".\"name\") @@ plainto_tsquery('loco app')"
+    ///     );
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().full_text(DbBackend::Sqlite, test_db::Column::Name, "100%").build())
+    ///         .build(DbBackend::Sqlite)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE LOWER(\"loco\".\"name\") LIKE '%100\\%%' ESCAPE '\\'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn full_text<T: ColumnTrait>(self, backend: DbBackend, col: T, query: &str) -> Self {
+        self.full_text_search(backend, None, col, query)
+    }
+
+    /// like [`ConditionBuilder::full_text`], with the given `Postgres` text
+    /// search configuration, such as `english`
+    ///
+    /// Create an index on the exact same expression for it to be used:
+    ///
+    /// ```sql
+    /// CREATE INDEX notes_title_search ON notes USING GIN (to_tsvector('english', title));
+    /// ```
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().full_text_in(DbBackend::Postgres, "english", test_db::Column::Name, "apps").build())
+    ///         .build(DbBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE to_tsvector('english', \"loco\".\"name\") @@ plainto_tsquery('english', 'apps')"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn full_text_in<T: ColumnTrait>(
+        self,
+        backend: DbBackend,
+        config: &str,
+        col: T,
+        query: &str,
+    ) -> Self {
+        self.full_text_search(backend, Some(config), col, query)
+    }
+
+    fn full_text_search<T: ColumnTrait>(
+        self,
+        backend: DbBackend,
+        config: Option<&str>,
+        col: T,
+        query: &str,
+    ) -> Self {
+        match backend {
+            DbBackend::Postgres => with(
+                self.condition.add(
+                    Expr::expr(tsvector(config, col.into_expr().into()))
+                        .matches(tsquery(config, query)),
+                ),
+            ),
+            DbBackend::MySql | DbBackend::Sqlite => with(
+                self.condition
+                    .add(contains_ignore_case(col.into_expr().into(), query)),
+            ),
+        }
+    }
+
+    /// where condition the given column is in the given values, or no
+    /// condition at all when there are no values
+    ///
+    /// Matches filter UIs where an empty selection means "all", unlike
+    /// [`ConditionBuilder::is_in`] which matches nothing for an empty set.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = |ids: Vec<i32>| {
+    ///     test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .eq(test_db::Column::Name, "loco")
+    ///                 .eq_any_or_all(test_db::Column::Id, ids)
+    ///                 .build(),
+    ///         )
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string()
+    /// };
+    ///
+    ///     assert_eq!(
+    ///         query_str(vec![1, 2]),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" = 'loco' AND \"loco\".\"id\" IN (1, 2)"
+    ///     );
+    ///     assert_eq!(
+    ///         query_str(vec![]),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" = 'loco'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn eq_any_or_all<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+        self,
+        col: T,
+        values: I,
+    ) -> Self {
+        let values = values.into_iter().collect::<Vec<_>>();
+        if values.is_empty() {
+            self
+        } else {
+            self.is_in(col, values)
+        }
+    }
+
+    /// where condition the value at the given path of a JSON column equals
+    /// the given value
+    ///
+    /// `path` is a dot separated list of keys, such as `address.city`. It
+    /// becomes `col->'address'->>'city'` on `Postgres`, and
+    /// `JSON_EXTRACT(col, '$.address.city')` on `MySQL`. `SQLite` uses its
+    /// `json_extract` function with the same path. `->>` yields text, so on
+    /// `Postgres` compare against a string value.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = |backend| {
+    ///     test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .json_eq(backend, test_db::Column::Name, "address.city", "Lisbon")
+    ///                 .build(),
+    ///         )
+    ///         .build(backend)
+    ///         .to_string()
+    /// };
+    ///
+    ///     assert_eq!(
+    ///         query_str(DbBackend::Postgres),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE ((\"loco\".\"name\" -> 'address') ->> 'city') = 'Lisbon'"
+    ///     );
+    ///     assert_eq!(
+    ///         query_str(DbBackend::MySql),
+    ///         "SELECT `loco`.`id` FROM `loco` WHERE JSON_EXTRACT(`loco`.`name`, '$.address.city') = 'Lisbon'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn json_eq<T: ColumnTrait, V: Into<Value>>(
+        self,
+        backend: DbBackend,
+        col: T,
+        path: &str,
+        value: V,
+    ) -> Self {
+        let expr = match backend {
+            DbBackend::Postgres => {
+                let mut keys = path.split('.').collect::<Vec<_>>();
+                let last = keys.pop().unwrap_or_default();
+                let object = keys
+                    .into_iter()
+                    .fold(SimpleExpr::from(col.into_expr()), |expr, key| {
+                        expr.binary(BinOper::Custom("->"), Expr::val(key))
+                    });
+                object.binary(BinOper::Custom("->>"), Expr::val(last))
+            }
+            DbBackend::MySql => Func::cust(Alias::new("JSON_EXTRACT"))
+                .args([
+                    SimpleExpr::from(col.into_expr()),
+                    Expr::val(format!("$.{path}")).into(),
+                ])
+                .into(),
+            DbBackend::Sqlite => Func::cust(Alias::new("json_extract"))
+                .args([
+                    SimpleExpr::from(col.into_expr()),
+                    Expr::val(format!("$.{path}")).into(),
+                ])
+                .into(),
+        };
+        with(self.condition.add(Expr::expr(expr).eq(value)))
+    }
+
+    /// where condition the address in the given `inet` column is contained
+    /// in the given CIDR block (`<<`)
+    ///
+    /// `Postgres` only, for columns of type `inet` or `cidr`. Parse user
+    /// input with [`parse_cidr`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().ip_in_subnet(test_db::Column::Name, query::parse_cidr("10.0.0.0/8").unwrap()).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" << (CAST('10.0.0.0/8' AS inet))"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn ip_in_subnet<T: ColumnTrait>(self, col: T, network: IpNetwork) -> Self {
+        // `inet '...'` only takes a string constant, not a bound parameter
+        with(self.condition.add(col.into_expr().binary(
+            BinOper::Custom("<<"),
+            Expr::cust_with_values("CAST($1 AS inet)", [network.to_string()]),
+        )))
+    }
+
+    /// where condition the given column, with `sentinel` read as null,
+    /// equals the given value (`NULLIF(col, sentinel) = value`)
+    ///
+    /// Useful on legacy data storing `''` or `0` for "no value": rows holding
+    /// the sentinel never match.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().nullif_eq(test_db::Column::Name, "", "loco").build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE NULLIF(\"loco\".\"name\", '') = 'loco'"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn nullif_eq<T: ColumnTrait, S: Into<Value>, V: Into<Value>>(
+        self,
+        col: T,
+        sentinel: S,
+        value: V,
+    ) -> Self {
+        let expr: SimpleExpr = Func::cust(Alias::new("NULLIF"))
+            .args([
+                SimpleExpr::from(col.into_expr()),
+                Expr::val(sentinel).into(),
+            ])
+            .into();
+        with(self.condition.add(Expr::expr(expr).eq(value)))
+    }
+
+    /// where condition the given numeric column lies between the `low` and
+    /// `high` percentiles of the column over the whole table, for example
+    /// `0.25` and `0.75` for the middle half of the rows
+    ///
+    /// `Postgres` only: the thresholds are computed in subqueries with
+    /// `percentile_cont`, which `SQLite` and `MySQL` do not have, so the
+    /// condition is skipped on those backends.
+    ///
+    /// The fractions are clamped between `0.0` and `1.0` and put in order, as
+    /// `Postgres` would only reject them when running the query, and a `NaN`
+    /// fraction skips the condition.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = |backend| {
+    ///     test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(
+    ///             query::condition()
+    ///                 .between_percentiles(backend, test_db::Column::Id, 0.25, 0.75)
+    ///                 .build(),
+    ///         )
+    ///         .build(backend)
+    ///         .to_string()
+    /// };
+    ///
+    ///     assert_eq!(
+    ///         query_str(DbBackend::Postgres),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" BETWEEN \
+    ///          (SELECT percentile_cont(0.25) WITHIN GROUP (ORDER BY \"loco\".\"id\") FROM \"loco\") AND \
+    ///          (SELECT percentile_cont(0.75) WITHIN GROUP (ORDER BY \"loco\".\"id\") FROM \"loco\")"
+    ///     );
+    ///     assert_eq!(
+    ///         query_str(DbBackend::Sqlite),
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE TRUE"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn between_percentiles<T: ColumnTrait>(
+        self,
+        backend: DbBackend,
+        col: T,
+        low: f64,
+        high: f64,
+    ) -> Self {
+        if backend != DbBackend::Postgres || low.is_nan() || high.is_nan() {
+            return self;
+        }
+        let (low, high) = (low.clamp(0.0, 1.0), high.clamp(0.0, 1.0));
+        let (low, high) = (low.min(high), low.max(high));
+        with(
+            self.condition.add(
+                Expr::expr(col.into_expr()).between(percentile(col, low), percentile(col, high)),
+            ),
+        )
+    }
+
+    /// where condition the field at the dotted `path` of the given `jsonb`
+    /// column, cast to the type of `value`, equals the value
+    ///
+    /// `Postgres` only. See [`JsonCast`] for the supported types.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().json_field_as_eq(test_db::Column::Name, "settings.public", true).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE ((\"loco\".\"name\" #>> '{settings,public}')::boolean) = TRUE"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn json_field_as_eq<T: ColumnTrait, V: JsonCast>(
+        self,
+        col: T,
+        path: &str,
+        value: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(json_field_as::<T, V>(col, path).eq(value)),
+        )
+    }
+
+    /// where condition the field at the dotted `path` of the given `jsonb`
+    /// column, cast to the type of `value`, is greater than the value
+    ///
+    /// The cast compares numeric fields as numbers rather than as text, where
+    /// `'9' > '10'`. `Postgres` only. See [`JsonCast`] for the supported
+    /// types.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().json_field_as_gt(test_db::Column::Name, "stats.views", 10).build())
+    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE ((\"loco\".\"name\" #>> '{stats,views}')::int) > 10"
+    ///     );
+    /// ````
+    #[must_use]
+    pub fn json_field_as_gt<T: ColumnTrait, V: JsonCast>(
+        self,
+        col: T,
+        path: &str,
+        value: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(json_field_as::<T, V>(col, path).gt(value)),
+        )
+    }
+
+    /// Like [`ConditionBuilder::json_field_as_gt`], for greater than or equal
+    #[must_use]
+    pub fn json_field_as_gte<T: ColumnTrait, V: JsonCast>(
+        self,
+        col: T,
+        path: &str,
+        value: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(json_field_as::<T, V>(col, path).gte(value)),
+        )
+    }
+
+    /// Like [`ConditionBuilder::json_field_as_gt`], for less than
+    #[must_use]
+    pub fn json_field_as_lt<T: ColumnTrait, V: JsonCast>(
+        self,
+        col: T,
+        path: &str,
+        value: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(json_field_as::<T, V>(col, path).lt(value)),
+        )
+    }
+
+    /// Like [`ConditionBuilder::json_field_as_gt`], for less than or equal
+    #[must_use]
+    pub fn json_field_as_lte<T: ColumnTrait, V: JsonCast>(
+        self,
+        col: T,
+        path: &str,
+        value: V,
+    ) -> Self {
+        with(
+            self.condition
+                .add(json_field_as::<T, V>(col, path).lte(value)),
+        )
+    }
+
+    /// where condition the given array column contains all the given values
+    /// (`@>`), always true for no values
+    ///
+    /// Postgres only: other databases have no array columns and reject the
+    /// query when it runs. The values are bound as parameters, one per
+    /// element.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let statement = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(query::condition().array_contains(test_db::Column::Name, vec!["rust", "web"]).build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres);
+    ///
+    /// assert_eq!(
+    ///     statement.sql,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" @> ARRAY[ $1, $2 ]"
+    /// );
+    /// assert_eq!(statement.values, Some(sea_orm::Values(vec!["rust".into(), "web".into()])));
+    /// ````
+    #[must_use]
+    pub fn array_contains<T: ColumnTrait, V: Into<Value>>(self, col: T, values: Vec<V>) -> Self {
+        if values.is_empty() {
+            return self;
+        }
+        with(self.condition.add(pg_array_op("@>", col, values)))
+    }
+
+    /// where condition the given array column has at least one of the given
+    /// values (`&&`), always false for no values
+    ///
+    /// Postgres only, like [`ConditionBuilder::array_contains`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let statement = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(query::condition().array_overlaps(test_db::Column::Id, vec![1, 2]).build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres);
+    ///
+    /// assert_eq!(
+    ///     statement.sql,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" && ARRAY[ $1, $2 ]"
+    /// );
+    /// assert_eq!(statement.values, Some(sea_orm::Values(vec![1.into(), 2.into()])));
+    /// ````
+    #[must_use]
+    pub fn array_overlaps<T: ColumnTrait, V: Into<Value>>(self, col: T, values: Vec<V>) -> Self {
+        if values.is_empty() {
+            return with(self.condition.add(Expr::value(false)));
+        }
+        with(self.condition.add(pg_array_op("&&", col, values)))
+    }
+
+    /// where condition the given subquery returns at least one row
+    ///
+    /// For the common case of related rows, see
+    /// [`ConditionBuilder::exists_related`].
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{sea_query::{Alias, Expr, Query}, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let orders = Query::select()
+    ///     .expr(Expr::val(1))
+    ///     .from(Alias::new("orders"))
+    ///     .and_where(Expr::col((Alias::new("orders"), Alias::new("loco_id"))).equals((test_db::Entity, test_db::Column::Id)))
+    ///     .and_where(Expr::col((Alias::new("orders"), Alias::new("paid"))).eq(true))
+    ///     .to_owned();
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(query::condition().exists(orders).build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE EXISTS(\
+    ///      SELECT 1 FROM \"orders\" WHERE \"orders\".\"loco_id\" = \"loco\".\"id\" AND \"orders\".\"paid\" = TRUE)"
+    /// );
+    /// ````
+    #[must_use]
+    pub fn exists(self, subquery: SelectStatement) -> Self {
+        with(self.condition.add(Expr::exists(subquery)))
+    }
+
+    /// where condition the given subquery returns no rows
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{sea_query::{Alias, Expr, Query}, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let orders = Query::select()
+    ///     .expr(Expr::val(1))
+    ///     .from(Alias::new("orders"))
+    ///     .and_where(Expr::col((Alias::new("orders"), Alias::new("loco_id"))).equals((test_db::Entity, test_db::Column::Id)))
+    ///     .to_owned();
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(query::condition().not_exists(orders).build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE NOT EXISTS(\
+    ///      SELECT 1 FROM \"orders\" WHERE \"orders\".\"loco_id\" = \"loco\".\"id\")"
+    /// );
+    /// ````
+    #[must_use]
+    pub fn not_exists(self, subquery: SelectStatement) -> Self {
+        with(self.condition.add(Expr::exists(subquery).not()))
+    }
+
+    /// where condition the row has at least one related row, the ones whose
+    /// `fk` column references the `pk` column of the row. The check is a
+    /// correlated `EXISTS`, so rows with several related rows are not
+    /// duplicated as they would be with a join.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// mod notes {
+    ///     use sea_orm::entity::prelude::*;
+    ///
+    ///     #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+    ///     #[sea_orm(table_name = "notes")]
+    ///     pub struct Model {
+    ///         #[sea_orm(primary_key)]
+    ///         pub id: i32,
+    ///         pub loco_id: i32,
+    ///     }
+    ///
+    ///     #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    ///     pub enum Relation {}
+    ///
+    ///     impl ActiveModelBehavior for ActiveModel {}
+    /// }
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(query::condition().exists_related(notes::Column::LocoId, test_db::Column::Id).build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE EXISTS(\
+    ///      SELECT 1 FROM \"notes\" WHERE \"notes\".\"loco_id\" = \"loco\".\"id\")"
+    /// );
+    /// ````
+    #[must_use]
+    pub fn exists_related<R: ColumnTrait, M: ColumnTrait>(self, fk: R, pk: M) -> Self {
+        with(
+            self.condition
+                .add(Expr::exists(correlated(fk, pk, Expr::val(1).into()))),
+        )
+    }
+
+    /// where condition the row has at least `count` related rows, see
+    /// [`ConditionBuilder::exists_related`]. The related rows are counted
+    /// with a correlated subquery.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// mod notes {
+    ///     use sea_orm::entity::prelude::*;
+    ///
+    ///     #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+    ///     #[sea_orm(table_name = "notes")]
+    ///     pub struct Model {
+    ///         #[sea_orm(primary_key)]
+    ///         pub id: i32,
+    ///         pub loco_id: i32,
+    ///     }
+    ///
+    ///     #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    ///     pub enum Relation {}
+    ///
+    ///     impl ActiveModelBehavior for ActiveModel {}
+    /// }
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(query::condition().related_count_gte(notes::Column::LocoId, test_db::Column::Id, 3).build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \
+    ///      (SELECT COUNT(*) FROM \"notes\" WHERE \"notes\".\"loco_id\" = \"loco\".\"id\") >= 3"
+    /// );
+    /// ````
+    #[must_use]
+    pub fn related_count_gte<R: ColumnTrait, M: ColumnTrait>(
+        self,
+        fk: R,
+        pk: M,
+        count: u64,
+    ) -> Self {
+        let related = correlated(fk, pk, Expr::cust("COUNT(*)"));
+        with(
+            self.condition.add(
+                Expr::expr(SimpleExpr::SubQuery(
+                    None,
+                    Box::new(related.into_sub_query_statement()),
+                ))
+                .gte(count),
+            ),
+        )
+    }
+
+    /// where condition the given column equals the value once both are
+    /// lowercased, trimmed and have their whitespace runs collapsed to a
+    /// single space, for matching user-entered names
+    ///
+    /// The value is normalized before it is bound. `SQLite` has no
+    /// `REGEXP_REPLACE`, so there the column is only lowercased and trimmed.
+    ///
     /// # Examples
     /// ```
     /// use loco_rs::tests_cfg::db::test_db;
-    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use sea_orm::{DbBackend, EntityTrait, QueryFilter, QuerySelect, QueryTrait};
     /// use loco_rs::prelude::*;
     ///
     /// let query_str = test_db::Entity::find()
     ///         .select_only()
     ///         .column(test_db::Column::Id)
-    ///         .filter(query::condition().is_not_in(test_db::Column::Id, [1]).build())
-    ///         .build(sea_orm::DatabaseBackend::Postgres)
+    ///         .filter(query::condition().normalized_eq(DbBackend::Postgres, test_db::Column::Name, "  Loco   RS ").build())
+    ///         .build(DbBackend::Postgres)
+    ///         .to_string();
+    ///
+    ///     assert_eq!(
+    ///         query_str,
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \
+    ///          LOWER(TRIM(REGEXP_REPLACE(\"loco\".\"name\", '\\s+', ' ', 'g'))) = 'loco rs'"
+    ///     );
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///         .select_only()
+    ///         .column(test_db::Column::Id)
+    ///         .filter(query::condition().normalized_eq(DbBackend::Sqlite, test_db::Column::Name, "  Loco   RS ").build())
+    ///         .build(DbBackend::Sqlite)
     ///         .to_string();
     ///
     ///     assert_eq!(
     ///         query_str,
-    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" NOT IN (1)"
+    ///         "SELECT \"loco\".\"id\" FROM \"loco\" WHERE LOWER(TRIM(\"loco\".\"name\")) = 'loco rs'"
     ///     );
     /// ````
     #[must_use]
-    #[allow(clippy::wrong_self_convention)]
-    pub fn is_not_in<T: ColumnTrait, V: Into<Value>, I: IntoIterator<Item = V>>(
+    pub fn normalized_eq<T: ColumnTrait, V: AsRef<str>>(
         self,
+        backend: DbBackend,
         col: T,
-        values: I,
+        value: V,
     ) -> Self {
-        with(self.condition.add(col.is_not_in(values)))
+        let collapsed: SimpleExpr = match backend {
+            DbBackend::Postgres => Func::cust(Alias::new("REGEXP_REPLACE"))
+                .args([
+                    col.into_expr().into(),
+                    Expr::cust(r"'\s+'"),
+                    Expr::cust("' '"),
+                    Expr::cust("'g'"),
+                ])
+                .into(),
+            DbBackend::MySql => Func::cust(Alias::new("REGEXP_REPLACE"))
+                .args([
+                    col.into_expr().into(),
+                    Expr::cust("'[[:space:]]+'"),
+                    Expr::cust("' '"),
+                ])
+                .into(),
+            DbBackend::Sqlite => col.into_expr().into(),
+        };
+        let normalized = Func::lower(Func::cust(Alias::new("TRIM")).arg(collapsed));
+        with(
+            self.condition
+                .add(Expr::expr(normalized).eq(normalize_text(value.as_ref()))),
+        )
     }
 
     /// where condition the given column is not null
@@ -696,10 +3193,61 @@ impl ConditionBuilder {
         date_range::DateRangeBuilder::new(self, col)
     }
 
+    /// Like [`ConditionBuilder::date_range`], for `timestamptz` columns
+    ///
+    /// The bounds are bound as timestamps with their offset, so the database
+    /// compares instants rather than wall-clock times, which stays correct
+    /// across DST changes. Convert other time zones with
+    /// `DateTime::fixed_offset`.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QueryFilter, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let from = chrono::DateTime::parse_from_rfc3339("2024-03-10T01:30:00-05:00").unwrap();
+    /// let to = chrono::DateTime::parse_from_rfc3339("2024-03-10T12:00:00+00:00").unwrap();
+    ///
+    /// let condition = query::condition()
+    ///     .date_range_tz(test_db::Column::CreatedAt)
+    ///     .dates(Some(&from), Some(&to))
+    ///     .build();
+    ///
+    /// let query_str = test_db::Entity::find()
+    ///     .select_only()
+    ///     .column(test_db::Column::Id)
+    ///     .filter(condition.build())
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" BETWEEN '2024-03-10 01:30:00.000000 -05:00' AND '2024-03-10 12:00:00.000000 +00:00'" );
+    /// ````
+    #[must_use]
+    pub fn date_range_tz<T: ColumnTrait>(
+        self,
+        col: T,
+    ) -> date_range::DateRangeBuilder<T, DateTime<FixedOffset>> {
+        date_range::DateRangeBuilder::new(self, col)
+    }
+
     #[must_use]
     pub fn build(&self) -> Condition {
         self.condition.clone().into_condition()
     }
+
+    /// Like [`ConditionBuilder::build`], but consumes the builder and moves the
+    /// condition out instead of cloning it.
+    ///
+    /// `build` copies every expression in the condition on each call, which
+    /// adds up on hot paths building many conditions per request. Prefer
+    /// `build_into` whenever the builder is not needed afterwards.
+    #[must_use]
+    pub fn build_into(self) -> Condition {
+        self.condition
+    }
 }
 
 #[cfg(test)]
@@ -715,253 +3263,994 @@ mod tests {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().eq(test_db::Column::Id, 1).build())
+            .filter(condition().eq(test_db::Column::Id, 1).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" = 1"
+        );
+    }
+
+    #[test]
+    fn condition_ne() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().ne(test_db::Column::Name, "loco").build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" <> 'loco'"
+        );
+    }
+
+    #[test]
+    fn condition_gt() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().gt(test_db::Column::Id, 1).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" > 1"
+        );
+    }
+
+    #[test]
+    fn condition_gte() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().gte(test_db::Column::Id, 1).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" >= 1"
+        );
+    }
+
+    #[test]
+    fn condition_lt() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().lt(test_db::Column::Id, 1).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" < 1"
+        );
+    }
+
+    #[test]
+    fn condition_lte() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().lte(test_db::Column::Id, 1).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" <= 1"
+        );
+    }
+
+    #[test]
+    fn condition_between() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().between(test_db::Column::Id, 1, 2).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" BETWEEN 1 AND 2"
+        );
+    }
+
+    #[test]
+    fn condition_not_between() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().not_between(test_db::Column::Id, 1, 2).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" NOT BETWEEN 1 AND 2"
+        );
+    }
+
+    #[test]
+    fn condition_like() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().like(test_db::Column::Name, "%lo").build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo'"
+        );
+    }
+
+    #[test]
+    fn condition_not_like() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().not_like(test_db::Column::Name, "%lo%").build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" NOT LIKE '%lo%'"
+        );
+    }
+
+    #[test]
+    fn condition_starts_with() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().starts_with(test_db::Column::Name, "lo").build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE 'lo%'"
+        );
+    }
+
+    #[test]
+    fn condition_ends_with() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().ends_with(test_db::Column::Name, "lo").build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo'"
+        );
+    }
+
+    #[test]
+    fn condition_contains() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().contains(test_db::Column::Name, "lo").build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo%'"
+        );
+    }
+
+    #[test]
+    fn condition_is_null() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().is_null(test_db::Column::Name).build())
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" IS NULL"
+        );
+    }
+
+    #[test]
+    fn condition_is_not_null() {
+        let query_str = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(condition().is_not_null(test_db::Column::Name).build())
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" = 1"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" IS NOT NULL"
         );
     }
 
     #[test]
-    fn condition_ne() {
+    fn condition_is_in() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().ne(test_db::Column::Name, "loco").build())
+            .filter(condition().is_in(test_db::Column::Id, [1]).build())
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" <> 'loco'"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" IN (1)"
         );
     }
 
     #[test]
-    fn condition_gt() {
+    fn condition_is_not_in() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().gt(test_db::Column::Id, 1).build())
+            .filter(condition().is_not_in(test_db::Column::Id, [1]).build())
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" > 1"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" NOT IN (1)"
         );
     }
 
     #[test]
-    fn condition_gte() {
+    fn condition_is_in_empty() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().gte(test_db::Column::Id, 1).build())
+            .filter(
+                condition()
+                    .is_in(test_db::Column::Id, Vec::<i32>::new())
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" >= 1"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE 1 = 2"
         );
-    }
 
-    #[test]
-    fn condition_lt() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().lt(test_db::Column::Id, 1).build())
+            .filter(
+                condition()
+                    .is_not_in(test_db::Column::Id, Vec::<i32>::new())
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" < 1"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE 1 = 1"
         );
     }
 
     #[test]
-    fn condition_lte() {
-        let query_str = test_db::Entity::find()
+    fn condition_modulo_eq() {
+        let query = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().lte(test_db::Column::Id, 1).build())
-            .build(sea_orm::DatabaseBackend::Postgres)
-            .to_string();
+            .filter(condition().modulo_eq(test_db::Column::Id, 10, 0).build());
 
         assert_eq!(
-            query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" <= 1"
+            query.build(sea_orm::DatabaseBackend::Postgres).to_string(),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" % 10 = 0"
+        );
+        assert_eq!(
+            query.build(sea_orm::DatabaseBackend::MySql).to_string(),
+            "SELECT `loco`.`id` FROM `loco` WHERE `loco`.`id` % 10 = 0"
         );
     }
 
     #[test]
-    fn condition_between() {
-        let query_str = test_db::Entity::find()
+    fn condition_char_length_between() {
+        let query = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().between(test_db::Column::Id, 1, 2).build())
-            .build(sea_orm::DatabaseBackend::Postgres)
-            .to_string();
+            .filter(
+                condition()
+                    .char_length_between(test_db::Column::Name, 5, 100)
+                    .build(),
+            );
 
         assert_eq!(
-            query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" BETWEEN 1 AND 2"
+            query.build(sea_orm::DatabaseBackend::Postgres).to_string(),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE CHAR_LENGTH(\"loco\".\"name\") BETWEEN 5 \
+             AND 100"
+        );
+        assert_eq!(
+            query.build(sea_orm::DatabaseBackend::Sqlite).to_string(),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE LENGTH(\"loco\".\"name\") BETWEEN 5 AND \
+             100"
         );
     }
 
     #[test]
-    fn condition_not_between() {
-        let query_str = test_db::Entity::find()
+    fn condition_json_contains_any_all() {
+        let query = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().not_between(test_db::Column::Id, 1, 2).build())
-            .build(sea_orm::DatabaseBackend::Postgres)
-            .to_string();
+            .filter(
+                condition()
+                    .json_contains_any(test_db::Column::Name, ["rust", "web"])
+                    .json_contains_all(test_db::Column::Name, vec![r#"say "hi""#.to_string()])
+                    .build(),
+            );
 
+        let stmt = query.build(sea_orm::DatabaseBackend::Postgres);
         assert_eq!(
-            query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" NOT BETWEEN 1 AND 2"
+            stmt.sql,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE (\"loco\".\"name\" ?| ($1::text[])) AND \
+             (\"loco\".\"name\" ?& ($2::text[]))"
+        );
+        assert_eq!(
+            stmt.values.unwrap().0,
+            vec![Value::from("{rust,web}"), Value::from(r#"{"say \"hi\""}"#)]
         );
     }
 
     #[test]
-    fn condition_like() {
+    fn condition_date_in_range() {
+        let from = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 3, 25).unwrap();
+
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().like(test_db::Column::Name, "%lo").build())
+            .filter(
+                condition()
+                    .date_in_range(test_db::Column::ReleasedOn, from..=to)
+                    .date_in_range_exclusive(test_db::Column::UpdatedAt, from..to)
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo'"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE (\"loco\".\"released_on\" BETWEEN \
+             '2024-03-01' AND '2024-03-25') AND \"loco\".\"updated_at\" >= '2024-03-01 \
+             00:00:00.000000' AND \"loco\".\"updated_at\" < '2024-03-25 00:00:00.000000'"
         );
     }
 
     #[test]
-    fn condition_not_like() {
+    fn condition_coalesce_in() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().not_like(test_db::Column::Name, "%lo%").build())
+            .filter(
+                condition()
+                    .coalesce_in(test_db::Column::Name, "active", ["active", "pending"])
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" NOT LIKE '%lo%'"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE COALESCE(\"loco\".\"name\", 'active') IN \
+             ('active', 'pending')"
         );
-    }
 
-    #[test]
-    fn condition_starts_with() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().starts_with(test_db::Column::Name, "lo").build())
+            .filter(
+                condition()
+                    .coalesce_in(test_db::Column::Name, "active", Vec::<&str>::new())
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE 'lo%'"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE 1 = 2"
         );
     }
 
     #[test]
-    fn condition_ends_with() {
+    fn condition_within_next_and_past() {
+        let now = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let clock = FixedClock(now);
+
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().ends_with(test_db::Column::Name, "lo").build())
+            .filter(
+                condition()
+                    .within_next_at(&clock, test_db::Column::CreatedAt, Duration::hours(48))
+                    .within_past_at(&clock, test_db::Column::UpdatedAt, Duration::minutes(30))
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo'"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE (\"loco\".\"created_at\" BETWEEN \
+             '2024-03-01 12:00:00.000000' AND '2024-03-03 12:00:00.000000') AND \
+             (\"loco\".\"updated_at\" BETWEEN '2024-03-01 11:30:00.000000' AND '2024-03-01 \
+             12:00:00.000000')"
         );
     }
 
     #[test]
-    fn condition_contains() {
+    fn condition_within_saturates_out_of_range_durations() {
+        let clock = FixedClock(NaiveDateTime::MAX - Duration::days(1));
+        assert_eq!(
+            condition()
+                .within_next_at(&clock, test_db::Column::CreatedAt, Duration::days(2))
+                .build(),
+            Condition::all().add(test_db::Column::CreatedAt.between(clock.0, NaiveDateTime::MAX))
+        );
+
+        let clock = FixedClock(NaiveDateTime::MIN + Duration::days(1));
+        assert_eq!(
+            condition()
+                .within_past_at(&clock, test_db::Column::CreatedAt, Duration::days(2))
+                .build(),
+            Condition::all().add(test_db::Column::CreatedAt.between(NaiveDateTime::MIN, clock.0))
+        );
+    }
+
+    #[test]
+    fn condition_between_percentiles_clamps_fractions() {
+        let band = |low, high| {
+            condition()
+                .between_percentiles(DbBackend::Postgres, test_db::Column::Id, low, high)
+                .build()
+        };
+        assert_eq!(band(-0.1, 1.5), band(0.0, 1.0));
+        assert_eq!(band(0.75, 0.25), band(0.25, 0.75));
+        assert_eq!(band(f64::NAN, 0.5), Condition::all());
+        assert_ne!(band(0.25, 0.75), Condition::all());
+        assert_eq!(
+            condition()
+                .between_percentiles(DbBackend::MySql, test_db::Column::Id, 0.25, 0.75)
+                .build(),
+            Condition::all()
+        );
+    }
+
+    #[test]
+    fn condition_any_of_all_of() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().contains(test_db::Column::Name, "lo").build())
+            .filter(
+                condition()
+                    .any_of(|c| c.eq(test_db::Column::Id, 1).eq(test_db::Column::Id, 2))
+                    .eq(test_db::Column::Name, "loco")
+                    .any_of(|c| {
+                        c.is_null(test_db::Column::Name)
+                            .all_of(|c| c.gt(test_db::Column::Id, 3).lt(test_db::Column::Id, 5))
+                    })
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" LIKE '%lo%'"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE (\"loco\".\"id\" = 1 OR \"loco\".\"id\" = \
+             2) AND \"loco\".\"name\" = 'loco' AND (\"loco\".\"name\" IS NULL OR (\"loco\".\"id\" \
+             > 3 AND \"loco\".\"id\" < 5))"
         );
     }
 
     #[test]
-    fn condition_is_null() {
+    fn condition_ilike() {
+        let query = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(
+                condition()
+                    .ilike(DbBackend::MySql, test_db::Column::Name, "%Lo%")
+                    .not_ilike(DbBackend::MySql, test_db::Column::Name, "%Rs%")
+                    .build(),
+            );
+
+        assert_eq!(
+            query.build(DbBackend::MySql).to_string(),
+            "SELECT `loco`.`id` FROM `loco` WHERE LOWER(`loco`.`name`) LIKE LOWER('%Lo%') AND \
+             LOWER(`loco`.`name`) NOT LIKE LOWER('%Rs%')"
+        );
+    }
+
+    #[test]
+    fn condition_not() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().is_null(test_db::Column::Name).build())
+            .filter(not(|c| c.eq(test_db::Column::Id, 1)).build())
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" IS NULL"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE NOT \"loco\".\"id\" = 1"
         );
-    }
 
-    #[test]
-    fn condition_is_not_null() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().is_not_null(test_db::Column::Name).build())
+            .filter(
+                condition()
+                    .any_of(|c| {
+                        c.eq(test_db::Column::Name, "loco")
+                            .not(|c| c.gt(test_db::Column::Id, 3).lt(test_db::Column::Id, 5))
+                    })
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" IS NOT NULL"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" = 'loco' OR (NOT \
+             (\"loco\".\"id\" > 3 AND \"loco\".\"id\" < 5))"
         );
     }
 
     #[test]
-    fn condition_is_in() {
+    fn condition_like_escaped() {
+        assert_eq!(escape_like(r"50%_off\"), r"50\%\_off\\");
+
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().is_in(test_db::Column::Id, [1]).build())
-            .build(sea_orm::DatabaseBackend::Postgres)
+            .filter(contains_escaped(test_db::Column::Name, r"a\b").build())
+            .build(sea_orm::DatabaseBackend::Sqlite)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" IN (1)"
+            r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE '%a\\b%' ESCAPE '\'"#
+        );
+
+        let query_str = |condition: ConditionBuilder| {
+            test_db::Entity::find()
+                .select_only()
+                .column(test_db::Column::Id)
+                .filter(condition.build())
+                .build(sea_orm::DatabaseBackend::Sqlite)
+                .to_string()
+        };
+
+        assert_eq!(
+            query_str(contains_escaped(test_db::Column::Name, "50%")),
+            r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE '%50\%%' ESCAPE '\'"#
+        );
+        assert_eq!(
+            query_str(starts_with_escaped(test_db::Column::Name, "50%")),
+            r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE '50\%%' ESCAPE '\'"#
+        );
+        assert_eq!(
+            query_str(ends_with_escaped(test_db::Column::Name, "50%")),
+            r#"SELECT "loco"."id" FROM "loco" WHERE "loco"."name" LIKE '%50\%' ESCAPE '\'"#
+        );
+    }
+
+    #[tokio::test]
+    async fn condition_like_escaped_matches_literally() {
+        let db = seeded_connection(&["50% off", "500 off", "lo_co", "loco"]).await;
+
+        let names = |condition: ConditionBuilder| {
+            let db = &db;
+            async move {
+                test_db::Entity::find()
+                    .filter(condition.build())
+                    .all(db)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|note| note.name)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            names(contains(test_db::Column::Name, "50%")).await,
+            ["50% off", "500 off"]
+        );
+        assert_eq!(
+            names(contains_escaped(test_db::Column::Name, "50%")).await,
+            ["50% off"]
+        );
+        assert_eq!(
+            names(starts_with_escaped(test_db::Column::Name, "lo_")).await,
+            ["lo_co"]
+        );
+        assert_eq!(
+            names(ends_with_escaped(test_db::Column::Name, "_co")).await,
+            ["lo_co"]
         );
     }
 
     #[test]
-    fn condition_is_not_in() {
+    fn condition_add_if_add_some() {
+        let build = |name: Option<&str>, only_small: bool| {
+            test_db::Entity::find()
+                .select_only()
+                .column(test_db::Column::Id)
+                .filter(
+                    add_some(name, |c, name| c.eq(test_db::Column::Name, name))
+                        .add_if(only_small, |c| c.lt(test_db::Column::Id, 10))
+                        .build(),
+                )
+                .build(sea_orm::DatabaseBackend::Postgres)
+                .to_string()
+        };
+
+        assert_eq!(
+            build(Some("loco"), true),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" = 'loco' AND \
+             \"loco\".\"id\" < 10"
+        );
+        assert_eq!(
+            build(None, true),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" < 10"
+        );
+        assert_eq!(
+            build(Some("loco"), false),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" = 'loco'"
+        );
+        assert_eq!(
+            build(None, false),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE TRUE"
+        );
+    }
+
+    #[test]
+    fn condition_col_compare() {
         let query_str = test_db::Entity::find()
             .select_only()
             .column(test_db::Column::Id)
-            .filter(condition().is_not_in(test_db::Column::Id, [1]).build())
+            .filter(
+                col_lt(test_db::Column::CreatedAt, test_db::Column::UpdatedAt)
+                    .col_ne(test_db::Column::Id, test_db::Column::Name)
+                    .build(),
+            )
             .build(sea_orm::DatabaseBackend::Postgres)
             .to_string();
 
         assert_eq!(
             query_str,
-            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"id\" NOT IN (1)"
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"created_at\" < \
+             \"loco\".\"updated_at\" AND \"loco\".\"id\" <> \"loco\".\"name\""
+        );
+    }
+
+    #[test]
+    fn condition_col_gt_by() {
+        let query_str = |backend: DbBackend, offset: Duration| {
+            test_db::Entity::find()
+                .select_only()
+                .column(test_db::Column::Id)
+                .filter(
+                    col_gt_by(
+                        backend,
+                        test_db::Column::UpdatedAt,
+                        test_db::Column::CreatedAt,
+                        offset,
+                    )
+                    .build(),
+                )
+                .build(backend)
+                .to_string()
+        };
+
+        assert_eq!(
+            query_str(DbBackend::MySql, Duration::minutes(90)),
+            "SELECT `loco`.`id` FROM `loco` WHERE `loco`.`updated_at` > `loco`.`created_at` + \
+             (INTERVAL 5400 SECOND)"
+        );
+        assert_eq!(
+            query_str(DbBackend::Sqlite, Duration::seconds(-30)),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE julianday(\"loco\".\"updated_at\") > \
+             julianday(\"loco\".\"created_at\", '-30 seconds')"
+        );
+    }
+
+    #[test]
+    fn condition_build_into() {
+        let builder = || {
+            condition().eq(test_db::Column::Id, 1).any_of(|c| {
+                c.like(test_db::Column::Name, "%lo%")
+                    .is_null(test_db::Column::Name)
+            })
+        };
+
+        assert_eq!(builder().build(), builder().build_into());
+    }
+
+    #[test]
+    fn condition_search_ranked() {
+        let search = search_ranked(
+            DbBackend::Postgres,
+            [test_db::Column::Name, test_db::Column::Id],
+            "loco",
+        );
+        let query_str = search
+            .order(
+                test_db::Entity::find()
+                    .select_only()
+                    .column(test_db::Column::Id),
+            )
+            .filter(search.condition.build())
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE to_tsvector(concat_ws(' ', \
+             \"loco\".\"name\", \"loco\".\"id\")) @@ plainto_tsquery('loco') ORDER BY \
+             ts_rank(to_tsvector(concat_ws(' ', \"loco\".\"name\", \"loco\".\"id\")), \
+             plainto_tsquery('loco')) DESC"
+        );
+
+        let search = search_ranked(
+            DbBackend::MySql,
+            [test_db::Column::Name, test_db::Column::Id],
+            "Lo_co",
+        );
+        assert!(search.rank.is_none());
+        let query_str = search
+            .order(
+                test_db::Entity::find()
+                    .select_only()
+                    .column(test_db::Column::Id),
+            )
+            .filter(search.condition.build())
+            .build(DbBackend::MySql)
+            .to_string();
+
+        assert_eq!(
+            query_str,
+            r"SELECT `loco`.`id` FROM `loco` WHERE LOWER(`loco`.`name`) LIKE '%lo\\_co%' ESCAPE '\\' OR LOWER(`loco`.`id`) LIKE '%lo\\_co%' ESCAPE '\\'"
+        );
+    }
+
+    #[test]
+    fn condition_date_range_tz_binds_offset() {
+        let from = chrono::DateTime::parse_from_rfc3339("2024-03-10T01:30:00-05:00").unwrap();
+
+        let statement = test_db::Entity::find()
+            .filter(
+                date_range_tz(test_db::Column::CreatedAt)
+                    .from(&from)
+                    .build()
+                    .build(),
+            )
+            .build(DbBackend::Postgres);
+
+        assert!(matches!(
+            statement.values.unwrap().0.as_slice(),
+            [Value::ChronoDateTimeWithTimeZone(Some(value))] if **value == from
+        ));
+    }
+
+    #[test]
+    fn condition_json_eq() {
+        let query_str = |backend: DbBackend, path: &str| {
+            test_db::Entity::find()
+                .select_only()
+                .column(test_db::Column::Id)
+                .filter(json_eq(backend, test_db::Column::Name, path, "loco").build())
+                .build(backend)
+                .to_string()
+        };
+
+        assert_eq!(
+            query_str(DbBackend::Postgres, "name"),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE (\"loco\".\"name\" ->> 'name') = 'loco'"
+        );
+        assert_eq!(
+            query_str(DbBackend::Sqlite, "owner.name"),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE json_extract(\"loco\".\"name\", \
+             '$.owner.name') = 'loco'"
+        );
+    }
+
+    #[test]
+    fn condition_ip_in_subnet() {
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(
+                ip_in_subnet(test_db::Column::Name, parse_cidr("2001:db8::/32").unwrap()).build(),
+            )
+            .build(DbBackend::Postgres);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE \"loco\".\"name\" << (CAST($1 AS inet))"
+        );
+        assert_eq!(
+            statement.values.unwrap().0,
+            vec![Value::from("2001:db8::/32")]
+        );
+    }
+
+    #[test]
+    fn can_parse_cidr() {
+        assert_eq!(parse_cidr("10.0.0.0/8").unwrap().to_string(), "10.0.0.0/8");
+        for cidr in [
+            "10.0.0.0",
+            "10.0.0.0/33",
+            "::/129",
+            "10.0.0/8",
+            "10.0.0.0/x",
+        ] {
+            assert!(parse_cidr(cidr).is_err(), "{cidr}");
+        }
+    }
+
+    #[test]
+    fn condition_nullif_eq() {
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(nullif_eq(test_db::Column::Name, "", "loco").build())
+            .build(DbBackend::MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `loco`.`id` FROM `loco` WHERE NULLIF(`loco`.`name`, ?) = ?"
+        );
+        assert_eq!(
+            statement.values.unwrap().0,
+            vec![Value::from(""), Value::from("loco")]
+        );
+    }
+
+    #[test]
+    fn condition_json_field_as() {
+        let query_str = |condition: ConditionBuilder| {
+            test_db::Entity::find()
+                .select_only()
+                .column(test_db::Column::Id)
+                .filter(condition.build())
+                .build(DbBackend::Postgres)
+                .to_string()
+        };
+
+        assert_eq!(
+            query_str(json_field_as_gte(test_db::Column::Name, "score", 1.5)),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE ((\"loco\".\"name\" #>> \
+             '{score}')::double precision) >= 1.5"
+        );
+        assert_eq!(
+            query_str(json_field_as_lt(
+                test_db::Column::Name,
+                "stats.views",
+                100_i64
+            )),
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE ((\"loco\".\"name\" #>> \
+             '{stats,views}')::bigint) < 100"
+        );
+
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(json_field_as_lte(test_db::Column::Name, "stats.odd key", 5_i16).build())
+            .build(DbBackend::Postgres);
+        assert_eq!(
+            statement.sql,
+            "SELECT \"loco\".\"id\" FROM \"loco\" WHERE ((\"loco\".\"name\" #>> $1)::smallint) <= \
+             $2"
+        );
+        assert_eq!(
+            statement.values.unwrap().0,
+            vec![Value::from("{stats,\"odd key\"}"), Value::from(5_i16)]
+        );
+    }
+
+    #[test]
+    fn condition_normalized_eq() {
+        assert_eq!(normalize_text("\t Loco \n  RS  "), "loco rs");
+
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(
+                condition()
+                    .normalized_eq(DbBackend::MySql, test_db::Column::Name, " Loco  RS")
+                    .build(),
+            )
+            .build(DbBackend::MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `loco`.`id` FROM `loco` WHERE LOWER(TRIM(REGEXP_REPLACE(`loco`.`name`, \
+             '[[:space:]]+', ' '))) = ?"
+        );
+        assert_eq!(
+            statement.values,
+            Some(sea_orm::Values(vec!["loco rs".into()]))
+        );
+    }
+
+    #[test]
+    fn condition_array_binds_values() {
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(
+                condition()
+                    .array_contains(test_db::Column::Name, vec!["rust", "web"])
+                    .array_overlaps(test_db::Column::Id, vec![1])
+                    .build(),
+            )
+            .build(DbBackend::Postgres);
+
+        assert_eq!(
+            statement.sql,
+            r#"SELECT "loco"."id" FROM "loco" WHERE ("loco"."name" @> ARRAY[ $1, $2 ]) AND ("loco"."id" && ARRAY[ $3 ])"#
+        );
+        assert_eq!(
+            statement.values,
+            Some(sea_orm::Values(vec!["rust".into(), "web".into(), 1.into()]))
+        );
+    }
+
+    #[test]
+    fn condition_full_text() {
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(
+                condition()
+                    .full_text_in(DbBackend::MySql, "english", test_db::Column::Name, "Loco")
+                    .build(),
+            )
+            .build(DbBackend::MySql);
+
+        assert_eq!(
+            statement.sql,
+            "SELECT `loco`.`id` FROM `loco` WHERE LOWER(`loco`.`name`) LIKE ? ESCAPE '\\\\'"
+        );
+        assert_eq!(
+            statement.values,
+            Some(sea_orm::Values(vec!["%loco%".into()]))
+        );
+
+        let statement = test_db::Entity::find()
+            .select_only()
+            .column(test_db::Column::Id)
+            .filter(full_text(DbBackend::Postgres, test_db::Column::Name, "loco").build())
+            .build(DbBackend::Postgres);
+        assert_eq!(
+            statement.sql,
+            r#"SELECT "loco"."id" FROM "loco" WHERE to_tsvector("loco"."name") @@ plainto_tsquery($1)"#
         );
     }
 }
@@ -0,0 +1,249 @@
+use sea_orm::{
+    sea_query::{NullOrdering, SimpleExpr},
+    ColumnTrait, Order, QueryOrder,
+};
+
+use crate::{Error, Result};
+
+/// A list of sort columns, started with [`super::order`], to apply to a
+/// query with [`OrderBuilder::apply`].
+#[derive(Debug, Clone, Default)]
+pub struct OrderBuilder {
+    orders: Vec<(SimpleExpr, Order, Option<NullOrdering>)>,
+}
+
+impl OrderBuilder {
+    /// Parses a user supplied sort spec, such as `?sort=-created_at,name`: a
+    /// comma separated list of fields, descending when prefixed with `-`.
+    ///
+    /// Only the fields of `allowed` can be sorted by, each mapping the public
+    /// field name to its column, so requests cannot sort by internal columns.
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let allowed = [("name", test_db::Column::Name), ("created_at", test_db::Column::CreatedAt)];
+    /// let query_str = query::OrderBuilder::from_params("-created_at,name", &allowed)
+    ///     .unwrap()
+    ///     .apply(test_db::Entity::find().select_only().column(test_db::Column::Id))
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" ORDER BY \"loco\".\"created_at\" DESC, \"loco\".\"name\" ASC"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// When a field is not in `allowed`, as [`Error::BadRequest`].
+    pub fn from_params<T: ColumnTrait>(spec: &str, allowed: &[(&str, T)]) -> Result<Self> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .try_fold(Self::default(), |order, field| {
+                let (name, desc) = match field.strip_prefix('-') {
+                    Some(name) => (name, true),
+                    None => (field.strip_prefix('+').unwrap_or(field), false),
+                };
+                let Some((_, col)) = allowed.iter().find(|(allowed, _)| *allowed == name) else {
+                    return Err(Error::BadRequest(format!(
+                        "cannot sort by `{name}`, expected one of: {}",
+                        allowed
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )));
+                };
+                Ok(if desc {
+                    order.desc(*col)
+                } else {
+                    order.asc(*col)
+                })
+            })
+    }
+
+    /// Sort by the given column ascending
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = query::order()
+    ///     .asc(test_db::Column::Name)
+    ///     .desc(test_db::Column::CreatedAt)
+    ///     .apply(test_db::Entity::find().select_only().column(test_db::Column::Id))
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" ORDER BY \"loco\".\"name\" ASC, \"loco\".\"created_at\" DESC"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn asc<T: ColumnTrait>(self, col: T) -> Self {
+        self.push(col, Order::Asc, None)
+    }
+
+    /// Sort by the given column descending
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = query::order()
+    ///     .desc(test_db::Column::CreatedAt)
+    ///     .apply(test_db::Entity::find().select_only().column(test_db::Column::Id))
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" ORDER BY \"loco\".\"created_at\" DESC"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn desc<T: ColumnTrait>(self, col: T) -> Self {
+        self.push(col, Order::Desc, None)
+    }
+
+    /// Sort by the given column ascending, with `NULL`s after the values
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = query::order()
+    ///     .nulls_last(test_db::Column::Name)
+    ///     .apply(test_db::Entity::find().select_only().column(test_db::Column::Id))
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" ORDER BY \"loco\".\"name\" ASC NULLS LAST"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn nulls_last<T: ColumnTrait>(self, col: T) -> Self {
+        self.push(col, Order::Asc, Some(NullOrdering::Last))
+    }
+
+    /// Sort by the given column descending, with `NULL`s after the values
+    ///
+    /// # Examples
+    /// ```
+    /// use loco_rs::tests_cfg::db::test_db;
+    /// use sea_orm::{EntityTrait, QuerySelect, QueryTrait};
+    /// use loco_rs::prelude::*;
+    ///
+    /// let query_str = query::order()
+    ///     .desc_nulls_last(test_db::Column::CreatedAt)
+    ///     .apply(test_db::Entity::find().select_only().column(test_db::Column::Id))
+    ///     .build(sea_orm::DatabaseBackend::Postgres)
+    ///     .to_string();
+    ///
+    /// assert_eq!(
+    ///     query_str,
+    ///     "SELECT \"loco\".\"id\" FROM \"loco\" ORDER BY \"loco\".\"created_at\" DESC NULLS LAST"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn desc_nulls_last<T: ColumnTrait>(self, col: T) -> Self {
+        self.push(col, Order::Desc, Some(NullOrdering::Last))
+    }
+
+    fn push<T: ColumnTrait>(mut self, col: T, order: Order, nulls: Option<NullOrdering>) -> Self {
+        self.orders.push((col.into_expr().into(), order, nulls));
+        self
+    }
+
+    /// Whether no sort column was added.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Adds the sort columns to the query, after its existing ordering.
+    #[must_use]
+    pub fn apply<Q: QueryOrder>(self, query: Q) -> Q {
+        self.orders
+            .into_iter()
+            .fold(query, |query, (expr, order, nulls)| match nulls {
+                Some(nulls) => query.order_by_with_nulls(expr, order, nulls),
+                None => query.order_by(expr, order),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{EntityTrait, QuerySelect, QueryTrait};
+
+    use super::*;
+    use crate::tests_cfg::db::test_db;
+
+    const ALLOWED: [(&str, test_db::Column); 2] = [
+        ("name", test_db::Column::Name),
+        ("created_at", test_db::Column::CreatedAt),
+    ];
+
+    fn order_by(spec: &str) -> String {
+        let query_str = OrderBuilder::from_params(spec, &ALLOWED)
+            .unwrap()
+            .apply(
+                test_db::Entity::find()
+                    .select_only()
+                    .column(test_db::Column::Id),
+            )
+            .build(sea_orm::DatabaseBackend::Postgres)
+            .to_string();
+        query_str
+            .split_once(" ORDER BY ")
+            .map_or_else(String::new, |(_, order)| order.to_string())
+    }
+
+    #[test]
+    fn can_parse_sort_params() {
+        assert_eq!(order_by("name"), r#""loco"."name" ASC"#);
+        assert_eq!(order_by("+name"), r#""loco"."name" ASC"#);
+        assert_eq!(order_by("-created_at"), r#""loco"."created_at" DESC"#);
+        assert_eq!(order_by(""), "");
+        assert!(OrderBuilder::from_params(" , ", &ALLOWED)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn can_parse_mixed_sort_params() {
+        assert_eq!(
+            order_by("-created_at, name"),
+            r#""loco"."created_at" DESC, "loco"."name" ASC"#
+        );
+        assert_eq!(
+            order_by("name,-name"),
+            r#""loco"."name" ASC, "loco"."name" DESC"#
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_sort_field() {
+        let err = OrderBuilder::from_params("name,-id", &ALLOWED).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::BadRequest(message) if message == "cannot sort by `id`, expected one of: name, created_at"
+        ));
+    }
+}
@@ -0,0 +1,25 @@
+use sea_orm::{sea_query::SimpleExpr, Order, QueryOrder};
+
+use super::ConditionBuilder;
+
+/// A search filter together with its relevance ranking, returned by
+/// [`ConditionBuilder::search_ranked`].
+pub struct RankedSearch {
+    /// The builder with the search filter added.
+    pub condition: ConditionBuilder,
+    /// The relevance of a row, higher is better. `None` on backends without
+    /// full-text ranking.
+    pub rank: Option<SimpleExpr>,
+}
+
+impl RankedSearch {
+    /// Orders the query by relevance, best match first. Leaves the query
+    /// untouched when there is no ranking.
+    #[must_use]
+    pub fn order<Q: QueryOrder>(&self, query: Q) -> Q {
+        match &self.rank {
+            Some(rank) => query.order_by(rank.clone(), Order::Desc),
+            None => query,
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use sea_orm::{
+    sea_query::{Alias, Asterisk, Expr, Func, SimpleExpr},
+    ColumnTrait, Condition, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Select, TryGetable,
+};
+
+use crate::Result as LocoResult;
+
+/// Counts the rows of `select` matching `condition`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let published = query::exec::count(
+///     &ctx.db,
+///     notes::Entity::find(),
+///     Some(query::condition().is_not_null(notes::Column::PublishedAt).build()),
+/// )
+/// .await?;
+/// ```
+///
+/// # Errors
+///
+/// When the query fails.
+pub async fn count<E, C>(db: &C, select: Select<E>, condition: Option<Condition>) -> LocoResult<u64>
+where
+    E: EntityTrait,
+    C: ConnectionTrait,
+{
+    let count: Option<i64> = scalar(select, count_all(), condition)
+        .into_tuple()
+        .one(db)
+        .await?;
+    Ok(count.map_or(0, |count| u64::try_from(count).unwrap_or_default()))
+}
+
+/// Sums `col` over the rows of `select` matching `condition`, `None` when no
+/// row matches.
+///
+/// `T` is the type of the sum, which can be wider than the column: on
+/// Postgres, the sum of an `integer` column is a `bigint` (`i64`) and the sum
+/// of a `bigint` column a `numeric`.
+///
+/// # Errors
+///
+/// When the query fails, or the sum cannot be decoded as `T`.
+pub async fn sum<T, E, C>(
+    db: &C,
+    select: Select<E>,
+    col: E::Column,
+    condition: Option<Condition>,
+) -> LocoResult<Option<T>>
+where
+    T: TryGetable,
+    E: EntityTrait,
+    C: ConnectionTrait,
+{
+    let sum: Option<Option<T>> = scalar(select, Func::sum(col.into_expr()).into(), condition)
+        .into_tuple()
+        .one(db)
+        .await?;
+    Ok(sum.flatten())
+}
+
+/// Averages `col` over the rows of `select` matching `condition`, `None`
+/// when no row matches.
+///
+/// # Errors
+///
+/// When the query fails.
+pub async fn avg<E, C>(
+    db: &C,
+    select: Select<E>,
+    col: E::Column,
+    condition: Option<Condition>,
+) -> LocoResult<Option<f64>>
+where
+    E: EntityTrait,
+    C: ConnectionTrait,
+{
+    let avg: Option<Option<f64>> =
+        scalar(select, avg_expr(db.get_database_backend(), col), condition)
+            .into_tuple()
+            .one(db)
+            .await?;
+    Ok(avg.flatten())
+}
+
+/// Counts the rows of `select` matching `condition` by value of `col`, as
+/// `(value, count)` pairs ordered by value.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let by_status: Vec<(String, i64)> =
+///     query::exec::group_count(&ctx.db, orders::Entity::find(), orders::Column::Status, None)
+///         .await?;
+/// ```
+///
+/// # Errors
+///
+/// When the query fails, or a value cannot be decoded as `K`.
+pub async fn group_count<K, E, C>(
+    db: &C,
+    select: Select<E>,
+    col: E::Column,
+    condition: Option<Condition>,
+) -> LocoResult<Vec<(K, i64)>>
+where
+    K: TryGetable,
+    E: EntityTrait,
+    C: ConnectionTrait,
+{
+    Ok(grouped(select, col, condition).into_tuple().all(db).await?)
+}
+
+fn count_all() -> SimpleExpr {
+    Func::count(Expr::col(Asterisk)).into()
+}
+
+/// `AVG` is a `numeric` on Postgres and a `DECIMAL` on MySQL, cast to a
+/// double to decode it as a `f64`.
+fn avg_expr<T: ColumnTrait>(backend: DbBackend, col: T) -> SimpleExpr {
+    let avg = Expr::expr(Func::avg(col.into_expr()));
+    match backend {
+        DbBackend::Postgres => avg.cast_as(Alias::new("double precision")),
+        DbBackend::MySql => avg.cast_as(Alias::new("double")),
+        DbBackend::Sqlite => avg.into(),
+    }
+}
+
+fn filtered<E: EntityTrait>(select: Select<E>, condition: Option<Condition>) -> Select<E> {
+    match condition {
+        Some(condition) => select.filter(condition),
+        None => select,
+    }
+}
+
+fn scalar<E: EntityTrait>(
+    select: Select<E>,
+    expr: SimpleExpr,
+    condition: Option<Condition>,
+) -> Select<E> {
+    filtered(select, condition)
+        .select_only()
+        .column_as(expr, "value")
+}
+
+fn grouped<E: EntityTrait>(
+    select: Select<E>,
+    col: E::Column,
+    condition: Option<Condition>,
+) -> Select<E> {
+    filtered(select, condition)
+        .select_only()
+        .column_as(col, "key")
+        .column_as(count_all(), "count")
+        .group_by(col)
+        .order_by_asc(col)
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DatabaseConnection, QueryTrait};
+
+    use super::*;
+    use crate::{
+        model::query,
+        tests_cfg::db::{seeded_connection, test_db},
+    };
+
+    async fn setup() -> DatabaseConnection {
+        seeded_connection(&["rust", "rust", "web"]).await
+    }
+
+    fn rust() -> Option<Condition> {
+        Some(query::condition().eq(test_db::Column::Name, "rust").build())
+    }
+
+    #[test]
+    fn can_build_aggregates() {
+        assert_eq!(
+            scalar(test_db::Entity::find(), count_all(), rust())
+                .build(DbBackend::Sqlite)
+                .to_string(),
+            r#"SELECT COUNT(*) AS "value" FROM "loco" WHERE "loco"."name" = 'rust'"#
+        );
+        assert_eq!(
+            scalar(
+                test_db::Entity::find(),
+                avg_expr(DbBackend::Postgres, test_db::Column::Id),
+                None
+            )
+            .build(DbBackend::Postgres)
+            .to_string(),
+            r#"SELECT CAST(AVG("loco"."id") AS double precision) AS "value" FROM "loco""#
+        );
+        assert_eq!(
+            grouped(test_db::Entity::find(), test_db::Column::Name, None)
+                .build(DbBackend::Sqlite)
+                .to_string(),
+            r#"SELECT "loco"."name" AS "key", COUNT(*) AS "count" FROM "loco" GROUP BY "loco"."name" ORDER BY "loco"."name" ASC"#
+        );
+    }
+
+    #[tokio::test]
+    async fn can_aggregate() {
+        let db = setup().await;
+        let find = test_db::Entity::find;
+
+        assert_eq!(count(&db, find(), None).await.unwrap(), 3);
+        assert_eq!(count(&db, find(), rust()).await.unwrap(), 2);
+        assert_eq!(
+            sum::<i64, _, _>(&db, find(), test_db::Column::Id, None)
+                .await
+                .unwrap(),
+            Some(6)
+        );
+        assert_eq!(
+            avg(&db, find(), test_db::Column::Id, rust()).await.unwrap(),
+            Some(1.5)
+        );
+        assert_eq!(
+            group_count::<String, _, _>(&db, find(), test_db::Column::Name, None)
+                .await
+                .unwrap(),
+            [("rust".to_string(), 2), ("web".to_string(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn aggregates_of_no_rows() {
+        let db = setup().await;
+        let none = Some(query::condition().eq(test_db::Column::Name, "go").build());
+        let find = test_db::Entity::find;
+
+        assert_eq!(count(&db, find(), none.clone()).await.unwrap(), 0);
+        assert_eq!(
+            sum::<i64, _, _>(&db, find(), test_db::Column::Id, none.clone())
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            avg(&db, find(), test_db::Column::Id, none.clone())
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(
+            group_count::<String, _, _>(&db, find(), test_db::Column::Name, none)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
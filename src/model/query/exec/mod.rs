@@ -0,0 +1,328 @@
+//! Executing queries beyond the plain `sea_orm` calls, such as batched
+//! inserts, upserts, soft deletes, retried transactions and aggregates.
+
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelBehavior, ActiveModelTrait, Condition, ConnectionTrait,
+    EntityTrait, Insert, IntoActiveModel, QueryFilter,
+};
+
+use crate::{
+    model::{SoftDelete, SoftDeleteActiveModel},
+    Result as LocoResult,
+};
+
+mod aggregate;
+mod transaction;
+
+pub use aggregate::{avg, count, group_count, sum};
+pub use transaction::transaction_with_retry;
+
+/// Inserts `models` with multi-row `INSERT` statements of at most
+/// `chunk_size` rows each, to stay under the bind parameter limit of the
+/// database, and returns the number of inserted rows.
+///
+/// All models should set the same columns, as each statement lists the
+/// columns of its rows. The chunks are inserted one after the other, run it
+/// in a transaction to insert all rows or none.
+///
+/// # Examples
+///
+/// ```
+/// use loco_rs::tests_cfg::db;
+/// use sea_orm::Set;
+/// use loco_rs::prelude::*;
+///
+/// async fn example() {
+///     let db = db::dummy_connection().await;
+///     let notes = ["a", "b", "c"].map(|name| db::test_db::ActiveModel {
+///         name: Set(name.to_string()),
+///         ..Default::default()
+///     });
+///     let inserted = query::exec::insert_many(&db, notes.to_vec(), 1000).await;
+/// }
+/// ```
+///
+/// # Errors
+///
+/// When an insert fails, after the previous chunks were inserted.
+pub async fn insert_many<A, C>(db: &C, models: Vec<A>, chunk_size: usize) -> LocoResult<u64>
+where
+    A: ActiveModelTrait + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    let mut inserted = 0;
+    for chunk in chunks(models, chunk_size) {
+        inserted += A::Entity::insert_many(chunk)
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(inserted)
+}
+
+/// What [`upsert`] does with a row conflicting with an existing row.
+#[derive(Debug, Clone)]
+pub enum OnConflictDo<C> {
+    /// Update these columns of the existing row to the inserted values. No
+    /// columns updates nothing, the same as [`OnConflictDo::Nothing`].
+    Update(Vec<C>),
+    /// Keep the existing row as is.
+    Nothing,
+}
+
+/// Inserts `model`, or when it conflicts with an existing row on
+/// `conflict_cols` (a primary key or unique index), updates that row or
+/// leaves it unchanged. Returns the number of affected rows.
+///
+/// This runs a single `INSERT ... ON CONFLICT (...) DO UPDATE SET ...` (or
+/// `DO NOTHING`) statement, `INSERT ... ON DUPLICATE KEY UPDATE ...` on
+/// MySQL, so unlike selecting the row first it cannot race with a concurrent
+/// insert. MySQL reports 2 affected rows when a row was updated.
+///
+/// # Examples
+///
+/// ```
+/// use loco_rs::tests_cfg::db;
+/// use sea_orm::Set;
+/// use loco_rs::prelude::*;
+///
+/// async fn example() {
+///     let db = db::dummy_connection().await;
+///     let note = db::test_db::ActiveModel {
+///         id: Set(1),
+///         name: Set("loco".to_string()),
+///         ..Default::default()
+///     };
+///     let affected = query::exec::upsert(
+///         &db,
+///         note,
+///         &[db::test_db::Column::Id],
+///         query::exec::OnConflictDo::Update(vec![db::test_db::Column::Name]),
+///     )
+///     .await;
+/// }
+/// ```
+///
+/// # Errors
+///
+/// When the statement fails, such as when `conflict_cols` is not covered by
+/// a unique index.
+pub async fn upsert<A, C>(
+    db: &C,
+    model: A,
+    conflict_cols: &[<A::Entity as EntityTrait>::Column],
+    on_conflict: OnConflictDo<<A::Entity as EntityTrait>::Column>,
+) -> LocoResult<u64>
+where
+    A: ActiveModelTrait + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    Ok(upsert_statement(model, conflict_cols, on_conflict)
+        .exec_without_returning(db)
+        .await?)
+}
+
+fn upsert_statement<A>(
+    model: A,
+    conflict_cols: &[<A::Entity as EntityTrait>::Column],
+    on_conflict: OnConflictDo<<A::Entity as EntityTrait>::Column>,
+) -> Insert<A>
+where
+    A: ActiveModelTrait,
+{
+    let mut clause = OnConflict::columns(conflict_cols.iter().copied());
+    match on_conflict {
+        // `DO UPDATE SET` with nothing to set is invalid SQL
+        OnConflictDo::Update(cols) if !cols.is_empty() => clause.update_columns(cols),
+        // MySQL has no `DO NOTHING`, it sets the conflict columns to themselves
+        OnConflictDo::Update(_) | OnConflictDo::Nothing => {
+            clause.do_nothing_on(conflict_cols.iter().copied())
+        }
+    };
+    A::Entity::insert(model).on_conflict(clause)
+}
+
+/// Loads the rows matching `condition`, skipping soft-deleted rows. Use
+/// [`SoftDelete::with_trashed`] to load them too.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let notes = query::exec::find_all::<notes::Entity, _>(
+///     &ctx.db,
+///     query::condition().eq(notes::Column::Title, "loco").build(),
+/// )
+/// .await?;
+/// ```
+///
+/// # Errors
+///
+/// When the query fails.
+pub async fn find_all<E, C>(db: &C, condition: Condition) -> LocoResult<Vec<E::Model>>
+where
+    E: SoftDelete,
+    C: ConnectionTrait,
+{
+    Ok(E::find_not_deleted().filter(condition).all(db).await?)
+}
+
+/// Soft deletes `model`, setting its `deleted_at` column to now instead of
+/// deleting the row. See [`SoftDeleteActiveModel::soft_delete`].
+///
+/// # Errors
+///
+/// When the update fails.
+pub async fn soft_delete<A, C>(db: &C, model: A) -> LocoResult<<A::Entity as EntityTrait>::Model>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Send,
+    A::Entity: SoftDelete,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    Ok(model.soft_delete(db).await?)
+}
+
+/// Splits `items` into chunks of `size` items, the last one holding the
+/// rest.
+fn chunks<T>(items: Vec<T>, size: usize) -> impl Iterator<Item = Vec<T>> {
+    let size = size.max(1);
+    let mut items = items.into_iter().peekable();
+    std::iter::from_fn(move || {
+        items.peek()?;
+        Some(items.by_ref().take(size).collect())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DbBackend, EntityTrait, PaginatorTrait, QueryTrait, Set};
+
+    use super::*;
+    use crate::tests_cfg::db::{seeded_connection, test_db};
+
+    fn note(id: i32, name: &str) -> test_db::ActiveModel {
+        test_db::ActiveModel {
+            id: Set(id),
+            ..test_db::active_model(name)
+        }
+    }
+
+    #[test]
+    fn can_split_chunks() {
+        let sizes = |len: usize, size: usize| {
+            chunks((0..len).collect(), size)
+                .map(|chunk| chunk.len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sizes(2500, 1000), [1000, 1000, 500]);
+        assert_eq!(sizes(2000, 1000), [1000, 1000]);
+        assert_eq!(sizes(3, 0), [1, 1, 1]);
+        assert!(sizes(0, 1000).is_empty());
+        assert_eq!(
+            chunks((0..5).collect(), 2).collect::<Vec<_>>(),
+            [vec![0, 1], vec![2, 3], vec![4]]
+        );
+    }
+
+    #[tokio::test]
+    async fn can_insert_many() {
+        let db = seeded_connection(&[]).await;
+
+        let notes = (0..2500)
+            .map(|i| test_db::active_model(&format!("note {i}")))
+            .collect();
+        assert_eq!(insert_many(&db, notes, 1000).await.unwrap(), 2500);
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 2500);
+
+        assert_eq!(
+            insert_many(&db, Vec::<test_db::ActiveModel>::new(), 1000)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    fn upsert_sql(on_conflict: OnConflictDo<test_db::Column>, backend: DbBackend) -> String {
+        let note = test_db::ActiveModel {
+            id: Set(1),
+            name: Set("loco".to_string()),
+            ..Default::default()
+        };
+        upsert_statement(note, &[test_db::Column::Id], on_conflict)
+            .build(backend)
+            .to_string()
+    }
+
+    #[test]
+    fn can_build_upsert_per_backend() {
+        let update = || OnConflictDo::Update(vec![test_db::Column::Name]);
+        assert_eq!(
+            upsert_sql(update(), DbBackend::Postgres),
+            r#"INSERT INTO "loco" ("id", "name") VALUES (1, 'loco') ON CONFLICT ("id") DO UPDATE SET "name" = "excluded"."name""#
+        );
+        assert_eq!(
+            upsert_sql(update(), DbBackend::Sqlite),
+            r#"INSERT INTO "loco" ("id", "name") VALUES (1, 'loco') ON CONFLICT ("id") DO UPDATE SET "name" = "excluded"."name""#
+        );
+        assert_eq!(
+            upsert_sql(update(), DbBackend::MySql),
+            "INSERT INTO `loco` (`id`, `name`) VALUES (1, 'loco') ON DUPLICATE KEY UPDATE `name` \
+             = VALUES(`name`)"
+        );
+    }
+
+    #[test]
+    fn can_build_upsert_do_nothing_per_backend() {
+        assert_eq!(
+            upsert_sql(OnConflictDo::Nothing, DbBackend::Postgres),
+            r#"INSERT INTO "loco" ("id", "name") VALUES (1, 'loco') ON CONFLICT ("id") DO NOTHING"#
+        );
+        assert_eq!(
+            upsert_sql(OnConflictDo::Nothing, DbBackend::MySql),
+            "INSERT INTO `loco` (`id`, `name`) VALUES (1, 'loco') ON DUPLICATE KEY UPDATE `id` = \
+             `id`"
+        );
+    }
+
+    #[test]
+    fn upsert_updating_no_columns_does_nothing() {
+        for backend in [DbBackend::Postgres, DbBackend::MySql, DbBackend::Sqlite] {
+            assert_eq!(
+                upsert_sql(OnConflictDo::Update(vec![]), backend),
+                upsert_sql(OnConflictDo::Nothing, backend)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn can_upsert() {
+        let db = seeded_connection(&[]).await;
+        let on_conflict = || OnConflictDo::Update(vec![test_db::Column::Name]);
+
+        let affected = upsert(&db, note(1, "loco"), &[test_db::Column::Id], on_conflict())
+            .await
+            .unwrap();
+        assert_eq!(affected, 1);
+        upsert(
+            &db,
+            note(1, "updated"),
+            &[test_db::Column::Id],
+            on_conflict(),
+        )
+        .await
+        .unwrap();
+        upsert(
+            &db,
+            note(1, "ignored"),
+            &[test_db::Column::Id],
+            OnConflictDo::Nothing,
+        )
+        .await
+        .unwrap();
+
+        let notes = test_db::Entity::find().all(&db).await.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].name, "updated");
+    }
+}
@@ -0,0 +1,229 @@
+use std::{future::Future, pin::Pin, time::Duration};
+
+use sea_orm::{sqlx, DatabaseTransaction, DbErr, RuntimeErr, TransactionTrait};
+
+use crate::{model::ModelError, Error, Result as LocoResult};
+
+/// The delay before the first retry, doubled on every further retry.
+const BASE_BACKOFF: Duration = Duration::from_millis(10);
+
+/// The longest delay between two retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Runs `f` in a transaction and commits it, running it again in a new
+/// transaction, up to `max_retries` times, when the database aborts it
+/// because of a concurrent transaction: a serialization failure or deadlock
+/// (`40001` and `40P01` on Postgres, deadlocks on MySQL) or a busy SQLite
+/// database. The retries wait an exponentially growing delay, starting at
+/// 10ms, so concurrent transactions can finish.
+///
+/// Any other error rolls the transaction back and is returned as is, so `f`
+/// must be safe to run again, with no side effects outside the transaction.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let note = query::exec::transaction_with_retry(&ctx.db, 3, |txn| {
+///     Box::pin(async move {
+///         let note = notes::Entity::find_by_id_or_404(txn, id).await?;
+///         let mut note = note.into_active_model();
+///         note.views = Set(note.views.unwrap() + 1);
+///         Ok(note.update(txn).await?)
+///     })
+/// })
+/// .await?;
+/// ```
+///
+/// # Errors
+///
+/// The error of `f` or of the commit, once it is not retryable or the retries
+/// are exhausted.
+pub async fn transaction_with_retry<C, F, T>(db: &C, max_retries: u32, f: F) -> LocoResult<T>
+where
+    C: TransactionTrait,
+    F: for<'c> Fn(
+            &'c DatabaseTransaction,
+        ) -> Pin<Box<dyn Future<Output = LocoResult<T>> + Send + 'c>>
+        + Send
+        + Sync,
+    T: Send,
+{
+    let mut attempt = 0;
+    loop {
+        let txn = db.begin().await?;
+        let err = match f(&txn).await {
+            Ok(value) => match txn.commit().await {
+                Ok(()) => return Ok(value),
+                Err(err) => Error::from(err),
+            },
+            Err(err) => {
+                txn.rollback().await?;
+                err
+            }
+        };
+        if attempt >= max_retries || !is_retryable(&err) {
+            return Err(err);
+        }
+
+        let backoff = BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempt));
+        attempt += 1;
+        tracing::debug!(attempt, error = %err, "retrying transaction");
+        tokio::time::sleep(backoff.min(MAX_BACKOFF)).await;
+    }
+}
+
+/// Whether the database aborted the transaction because of a concurrent one,
+/// so running it again can succeed.
+fn is_retryable(err: &Error) -> bool {
+    let err = match err {
+        Error::WithBacktrace { inner, .. } => return is_retryable(inner),
+        Error::DB(err) | Error::Model(ModelError::DbErr(err)) => err,
+        _ => return false,
+    };
+    let (DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(err)))
+    | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(err)))) = err
+    else {
+        return false;
+    };
+    err.code().is_some_and(|code| {
+        match code.as_ref() {
+            // serialization failure and deadlock, also the SQLSTATE of MySQL
+            // deadlocks
+            "40001" | "40P01" => true,
+            // SQLITE_BUSY and SQLITE_LOCKED, with their extended codes, which
+            // unlike a SQLSTATE are shorter than 5 digits
+            code if code.len() < 5 => code
+                .parse::<u32>()
+                .is_ok_and(|code| matches!(code & 0xff, 5 | 6)),
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        borrow::Cow,
+        error::Error as StdError,
+        fmt,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, PaginatorTrait};
+
+    use super::*;
+    use crate::tests_cfg::db::{seeded_connection, test_db};
+
+    #[derive(Debug)]
+    struct DatabaseError(&'static str);
+
+    impl fmt::Display for DatabaseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "database error {}", self.0)
+        }
+    }
+
+    impl StdError for DatabaseError {}
+
+    impl sqlx::error::DatabaseError for DatabaseError {
+        fn message(&self) -> &str {
+            "could not serialize access"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(self.0.into())
+        }
+
+        fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    fn db_error(code: &'static str) -> Error {
+        Error::DB(DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(
+            Box::new(DatabaseError(code)),
+        ))))
+    }
+
+    /// Inserts a row, then fails with `code` for the first `failures` calls.
+    async fn run(
+        db: &DatabaseConnection,
+        max_retries: u32,
+        failures: u32,
+        code: &'static str,
+    ) -> (LocoResult<i32>, u32) {
+        let calls = AtomicU32::new(0);
+        let res = transaction_with_retry(db, max_retries, |txn| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                let note = test_db::active_model(&format!("attempt {call}"))
+                    .insert(txn)
+                    .await?;
+                if call < failures {
+                    return Err(db_error(code));
+                }
+                Ok(note.id)
+            })
+        })
+        .await;
+        (res, calls.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn retries_serialization_failures_then_commits() {
+        let db = seeded_connection(&[]).await;
+
+        let (res, calls) = run(&db, 3, 2, "40001").await;
+        assert!(res.is_ok());
+        assert_eq!(calls, 3);
+
+        // the failed attempts were rolled back
+        let notes = test_db::Entity::find().all(&db).await.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].name, "attempt 2");
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let db = seeded_connection(&[]).await;
+
+        let (res, calls) = run(&db, 2, 5, "40P01").await;
+        assert!(is_retryable(&res.unwrap_err()));
+        assert_eq!(calls, 3);
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_errors() {
+        let db = seeded_connection(&[]).await;
+
+        let (res, calls) = run(&db, 3, 1, "23505").await;
+        assert!(res.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(test_db::Entity::find().count(&db).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn detects_retryable_errors() {
+        assert!(is_retryable(&db_error("40001")));
+        assert!(is_retryable(&db_error("40P01")));
+        assert!(is_retryable(&db_error("5")));
+        assert!(is_retryable(&db_error("517")));
+        assert!(!is_retryable(&db_error("23505")));
+        assert!(!is_retryable(&db_error("22021")));
+        assert!(!is_retryable(&db_error("2067")));
+        assert!(!is_retryable(&Error::NotFound));
+    }
+}
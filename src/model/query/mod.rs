@@ -1,5 +1,8 @@
 mod dsl;
+pub mod exec;
 mod paginate;
+mod renamed_column;
 
 pub use dsl::*;
 pub use paginate::*;
+pub use renamed_column::RenamedColumn;
@@ -0,0 +1,269 @@
+use sea_orm::{
+    prelude::*, sea_query::Order, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{default_page_size, deserialize_pagination_filter, MAX_LIMIT};
+use crate::{controller::cursor, Result as LocoResult};
+
+/// Structure representing the cursor pagination query parameters, to get
+/// from the query parameters like [`super::PaginationQuery`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CursorQuery {
+    /// A `next_cursor` or `prev_cursor` of a previous page, the first page
+    /// when unset.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(
+        default = "default_page_size",
+        deserialize_with = "deserialize_pagination_filter"
+    )]
+    pub page_size: u64,
+}
+
+impl Default for CursorQuery {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            page_size: default_page_size(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CursorPage<T> {
+    pub page: Vec<T>,
+    /// The cursor of the rows after this page, `None` on the last page.
+    pub next_cursor: Option<String>,
+    /// The cursor of the rows before this page, `None` on the first page.
+    pub prev_cursor: Option<String>,
+}
+
+/// The sort key of a row, with the side of it a cursor pages to.
+#[derive(Serialize, Deserialize)]
+struct Position<K> {
+    key: K,
+    before: bool,
+}
+
+/// Paginates a query by cursor (keyset) rather than by offset, which stays
+/// fast on large tables and neither skips nor repeats rows when rows are
+/// inserted between two pages.
+///
+/// Rows are ordered by `column`, which must be unique such as the primary
+/// key, ascending or descending, and `key` reads its value from a row. Cursors
+/// are signed with `secret`, see [`crate::controller::cursor`].
+///
+/// # Examples
+///
+/// ```
+/// use loco_rs::tests_cfg::db;
+/// use sea_orm::{sea_query::Order, EntityTrait};
+/// use loco_rs::prelude::*;
+///
+/// async fn example() {
+///     let db = db::dummy_connection().await;
+///     let res = query::paginate_cursor(
+///         &db,
+///         db::test_db::Entity::find(),
+///         db::test_db::Column::Id,
+///         Order::Asc,
+///         |note| note.id,
+///         &query::CursorQuery::default(),
+///         "secret",
+///     )
+///     .await;
+/// }
+/// ````
+///
+/// # Errors
+///
+/// Returns a `LocoResult` indicating any errors that occur during
+/// pagination, and a bad request for a tampered, expired or malformed
+/// cursor.
+pub async fn paginate_cursor<E, K>(
+    db: &DatabaseConnection,
+    entity: Select<E>,
+    column: E::Column,
+    order: Order,
+    key: impl Fn(&E::Model) -> K + Send,
+    cursor_query: &CursorQuery,
+    secret: &str,
+) -> LocoResult<CursorPage<E::Model>>
+where
+    E: EntityTrait,
+    K: Serialize + DeserializeOwned + Into<Value> + Send,
+{
+    let position = cursor_query
+        .cursor
+        .as_deref()
+        .filter(|cursor| !cursor.is_empty())
+        .map(|cursor| cursor::decode::<Position<K>>(cursor, secret))
+        .transpose()?;
+    let before = position.as_ref().is_some_and(|position| position.before);
+    let from_cursor = position.is_some();
+
+    // pages before a cursor are fetched in the reverse order, then restored
+    let ascending = matches!(order, Order::Asc) != before;
+    let entity = match position {
+        Some(Position { key, .. }) if ascending => entity.filter(column.gt(key)),
+        Some(Position { key, .. }) => entity.filter(column.lt(key)),
+        None => entity,
+    };
+
+    let size = cursor_query.page_size.clamp(1, MAX_LIMIT);
+    let mut page = entity
+        .order_by(column, if ascending { Order::Asc } else { Order::Desc })
+        .limit(size.saturating_add(1).min(MAX_LIMIT))
+        .all(db)
+        .await?;
+    let has_more = page.len() as u64 > size;
+    page.truncate(usize::try_from(size).unwrap_or(usize::MAX));
+    if before {
+        page.reverse();
+    }
+
+    let encode = |row: Option<&E::Model>, before: bool| {
+        row.map(|row| {
+            cursor::encode(
+                &Position {
+                    key: key(row),
+                    before,
+                },
+                secret,
+                cursor::DEFAULT_TTL,
+            )
+        })
+        .transpose()
+    };
+    let (has_next, has_prev) = if before {
+        (true, has_more)
+    } else {
+        (has_more, from_cursor)
+    };
+    Ok(CursorPage {
+        next_cursor: encode(page.last().filter(|_| has_next), false)?,
+        prev_cursor: encode(page.first().filter(|_| has_prev), true)?,
+        page,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::ActiveModelTrait;
+
+    use super::*;
+    use crate::{
+        tests_cfg::db::{seeded_connection, test_db},
+        Error,
+    };
+
+    const SECRET: &str = "secret";
+
+    async fn setup() -> DatabaseConnection {
+        seeded_connection(&["a", "b", "c", "d", "e"]).await
+    }
+
+    async fn page(
+        db: &DatabaseConnection,
+        order: Order,
+        cursor: Option<&String>,
+    ) -> LocoResult<(Vec<String>, CursorPage<test_db::Model>)> {
+        let res = paginate_cursor(
+            db,
+            test_db::Entity::find(),
+            test_db::Column::Id,
+            order,
+            |note| note.id,
+            &CursorQuery {
+                cursor: cursor.cloned(),
+                page_size: 2,
+            },
+            SECRET,
+        )
+        .await?;
+        Ok((res.page.iter().map(|m| m.name.clone()).collect(), res))
+    }
+
+    #[tokio::test]
+    async fn iterates_stably_across_inserts() {
+        let db = setup().await;
+
+        let (names, first) = page(&db, Order::Asc, None).await.unwrap();
+        assert_eq!(names, ["a", "b"]);
+        assert!(first.prev_cursor.is_none());
+
+        test_db::active_model("f").insert(&db).await.unwrap();
+        let (names, second) = page(&db, Order::Asc, first.next_cursor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(names, ["c", "d"]);
+
+        let (names, last) = page(&db, Order::Asc, second.next_cursor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(names, ["e", "f"]);
+        assert!(last.next_cursor.is_none());
+
+        let (names, back) = page(&db, Order::Asc, last.prev_cursor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(names, ["c", "d"]);
+        let (names, back) = page(&db, Order::Asc, back.prev_cursor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(names, ["a", "b"]);
+        assert!(back.prev_cursor.is_none());
+        assert!(back.next_cursor.is_some());
+    }
+
+    #[tokio::test]
+    async fn can_paginate_descending() {
+        let db = setup().await;
+
+        let (names, first) = page(&db, Order::Desc, None).await.unwrap();
+        assert_eq!(names, ["e", "d"]);
+        let (names, next) = page(&db, Order::Desc, first.next_cursor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(names, ["c", "b"]);
+        let (names, _) = page(&db, Order::Desc, next.prev_cursor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(names, ["e", "d"]);
+    }
+
+    #[tokio::test]
+    async fn can_request_the_largest_page_size() {
+        let db = setup().await;
+        let res = paginate_cursor(
+            &db,
+            test_db::Entity::find(),
+            test_db::Column::Id,
+            Order::Asc,
+            |note| note.id,
+            &CursorQuery {
+                cursor: None,
+                page_size: u64::MAX,
+            },
+            SECRET,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.page.len(), 5);
+        assert!(res.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_tampered_cursor() {
+        let db = setup().await;
+        let (_, first) = page(&db, Order::Asc, None).await.unwrap();
+        let tampered = format!("x{}", first.next_cursor.unwrap());
+
+        assert!(matches!(
+            page(&db, Order::Asc, Some(&tampered)).await,
+            Err(Error::CustomError(..))
+        ));
+    }
+}
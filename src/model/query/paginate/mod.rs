@@ -1,6 +1,13 @@
-use sea_orm::{prelude::*, Condition, DatabaseConnection, EntityTrait, QueryFilter, SelectorTrait};
+use sea_orm::{
+    prelude::*, Condition, DatabaseConnection, DbBackend, EntityTrait, Iterable, Paginator,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait, SelectorTrait, Statement,
+};
 use serde::{Deserialize, Serialize};
 
+mod cursor;
+
+pub use cursor::{paginate_cursor, CursorPage, CursorQuery};
+
 /// Set the default pagination page size.
 const fn default_page_size() -> u64 {
     25
@@ -11,6 +18,14 @@ const fn default_page() -> u64 {
     1
 }
 
+/// Set the default maximum pagination page size.
+const fn default_max_page_size() -> Option<u64> {
+    Some(100)
+}
+
+/// The largest `LIMIT` every backend can bind, as a signed 64-bit integer.
+const MAX_LIMIT: u64 = u64::MAX >> 1;
+
 /// Structure representing the pagination query parameters.
 /// This struct allows to get the struct parameters from the query parameters.
 ///
@@ -42,6 +57,43 @@ pub struct PaginationQuery {
         deserialize_with = "deserialize_pagination_filter"
     )]
     pub page: u64,
+    /// How to count the total pages, set by the app rather than taken from
+    /// the query parameters.
+    #[serde(skip)]
+    pub count_strategy: CountStrategy,
+    /// The largest `page_size` a client can request, set by the app rather
+    /// than taken from the query parameters. 100 by default, and `None` lifts
+    /// the cap.
+    #[serde(skip, default = "default_max_page_size")]
+    pub max_page_size: Option<u64>,
+    /// The environment of the app, to have [`paginate`] return the SQL of its
+    /// query, with its condition, as [`PageResponse::debug_sql`] to
+    /// troubleshoot filters. Set by the app rather than taken from the query
+    /// parameters, and ignored in [`Environment::Production`] so production
+    /// responses never expose the query structure.
+    #[serde(skip)]
+    pub debug: Option<Environment>,
+}
+
+/// How [`paginate`] counts the rows to get the total pages.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CountStrategy {
+    /// Run a `COUNT(*)` query.
+    #[default]
+    Exact,
+    /// Use a row estimate of the database, which is cheap but approximate:
+    /// the table statistics (`pg_class.reltuples`) for an unfiltered query on
+    /// Postgres, the query planner estimate (`EXPLAIN`) otherwise, as the
+    /// table statistics would ignore the filters. Falls back to `Exact` where
+    /// there is no estimate, such as on SQLite, on a table never analyzed or
+    /// with [`fetch_page`].
+    Estimated,
+    /// Skip counting: `total_pages` is left `0` and only `has_next` tells
+    /// whether there are more pages. [`paginate`] fetches one row past the
+    /// page to find it out in the same query, while [`fetch_page`], which
+    /// cannot extend the query of its selector, probes the next page with a
+    /// second one when the page is full.
+    None,
 }
 
 impl PaginationQuery {
@@ -52,6 +104,36 @@ impl PaginationQuery {
             ..Default::default()
         }
     }
+
+    /// Returns the query with the values pagination applies: `page_size`
+    /// clamped between 1 and `max_page_size`, and `page` starting at 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loco_rs::prelude::*;
+    ///
+    /// let pagination_query = query::PaginationQuery {
+    ///     page_size: 100_000,
+    ///     page: 0,
+    ///     ..Default::default()
+    /// }
+    /// .normalize();
+    /// assert_eq!((pagination_query.page, pagination_query.page_size), (1, 100));
+    /// ```
+    #[must_use]
+    pub fn normalize(&self) -> Self {
+        let max_page_size = self.max_page_size.map(|max| max.max(1));
+        Self {
+            page_size: self
+                .page_size
+                .clamp(1, max_page_size.unwrap_or(MAX_LIMIT).min(MAX_LIMIT)),
+            page: self.page.max(1),
+            count_strategy: self.count_strategy,
+            max_page_size,
+            debug: self.debug.clone(),
+        }
+    }
 }
 
 /// Default implementation for `PaginationQuery`.
@@ -60,30 +142,134 @@ impl Default for PaginationQuery {
         Self {
             page_size: default_page_size(),
             page: default_page(),
+            count_strategy: CountStrategy::default(),
+            max_page_size: default_max_page_size(),
+            debug: None,
         }
     }
 }
 
 /// Deserialize pagination filter from string to u64 following a bug in
-/// `serde_urlencoded`.
+/// `serde_urlencoded`. Negative numbers are read as `0`, which
+/// [`PaginationQuery::normalize`] raises to the first page.
 fn deserialize_pagination_filter<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     let s: String = Deserialize::deserialize(deserializer)?;
-    s.parse().map_err(serde::de::Error::custom)
+    s.parse()
+        .or_else(|err| match s.parse::<i64>() {
+            Ok(negative) if negative < 0 => Ok(0),
+            _ => Err(err),
+        })
+        .map_err(serde::de::Error::custom)
+}
+
+/// The URLs of the first, previous, next and last pages, for JSON:API-style
+/// `links` or an RFC 5988 `Link` header.
+///
+/// # Example
+///
+/// ```
+/// use loco_rs::prelude::*;
+///
+/// let links = query::PaginationLinks::new("/notes", &query::PaginationQuery::page(2), 3);
+/// assert_eq!(links.next.as_deref(), Some("/notes?page=3&page_size=25"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PaginationLinks {
+    pub first: String,
+    /// Omitted on the first page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+    /// Omitted on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    pub last: String,
+}
+
+impl PaginationLinks {
+    /// Builds the links of the page of `pagination_query` out of
+    /// `total_pages`, appending the `page` and `page_size` query parameters
+    /// to `base`.
+    #[must_use]
+    pub fn new(base: &str, pagination_query: &PaginationQuery, total_pages: u64) -> Self {
+        let pagination_query = pagination_query.normalize();
+        let separator = if base.contains('?') { '&' } else { '?' };
+        let url = |page: u64| {
+            format!(
+                "{base}{separator}page={page}&page_size={}",
+                pagination_query.page_size
+            )
+        };
+        let page = pagination_query.page;
+        let last = total_pages.max(1);
+
+        Self {
+            first: url(1),
+            prev: (page > 1).then(|| url((page - 1).min(last))),
+            next: (page < last).then(|| url(page + 1)),
+            last: url(last),
+        }
+    }
+
+    /// Formats the links as an RFC 5988 `Link` header value.
+    #[must_use]
+    pub fn link_header(&self) -> String {
+        [
+            Some((&self.first, "first")),
+            self.prev.as_ref().map(|prev| (prev, "prev")),
+            self.next.as_ref().map(|next| (next, "next")),
+            Some((&self.last, "last")),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(url, rel)| format!("<{url}>; rel=\"{rel}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// What to do when the requested page is past the last one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// Return an empty page.
+    #[default]
+    EmptyPage,
+    /// Return the last page instead.
+    ClampToLast,
+    /// Fail with [`crate::Error::NotFound`].
+    Error,
 }
 
 #[derive(Debug)]
 pub struct PageResponse<T> {
     pub page: Vec<T>,
     pub total_pages: u64,
+    /// The page that was returned, which differs from the requested one when
+    /// it was below 1, or clamped with [`OutOfRange::ClampToLast`].
+    pub current_page: u64,
+    /// The page size that was applied, which differs from the requested one
+    /// when it was clamped by [`PaginationQuery::normalize`].
+    pub page_size: u64,
+    /// The policy that was applied, when the requested page was past the last
+    /// one.
+    pub out_of_range: Option<OutOfRange>,
+    /// Whether there is a page after this one.
+    pub has_next: bool,
+    /// The SQL of the query, with [`PaginationQuery::debug`] set outside of
+    /// production.
+    pub debug_sql: Option<String>,
 }
 
-use crate::Result as LocoResult;
+use crate::{environment::Environment, Error, Result as LocoResult};
 
 /// Paginate function for fetching paginated data from the database.
 ///
+/// Pages are only stable when the rows have a total order, so a query without
+/// any ordering is ordered by its primary key, with a warning. The pagination
+/// query is [normalized](PaginationQuery::normalize) first.
+///
 /// # Examples
 ///
 /// Without conditions
@@ -97,6 +283,7 @@ use crate::Result as LocoResult;
 ///     let pagination_query = query::PaginationQuery {
 ///         page_size: 100,
 ///         page: 1,
+///         ..Default::default()
 ///     };
 ///     
 ///     let res = query::paginate(&db, db::test_db::Entity::find(), None, &pagination_query).await;
@@ -113,6 +300,7 @@ use crate::Result as LocoResult;
 ///     let pagination_query = query::PaginationQuery {
 ///         page_size: 100,
 ///         page: 1,
+///         ..Default::default()
 ///     };
 ///     let condition = query::condition().contains(db::test_db::Column::Name, "loco").build();
 ///     let res = query::paginate(&db, db::test_db::Entity::find(), Some(condition), &pagination_query).await;
@@ -129,6 +317,7 @@ use crate::Result as LocoResult;
 ///     let pagination_query = query::PaginationQuery {
 ///         page_size: 100,
 ///         page: 1,
+///         ..Default::default()
 ///     };
 ///     
 ///     let condition = query::condition().contains(db::test_db::Column::Name, "loco").build();
@@ -151,24 +340,193 @@ where
     E: EntityTrait,
     <E as EntityTrait>::Model: Sync,
 {
-    let page = if pagination_query.page <= 1 {
-        0
-    } else {
-        pagination_query.page - 1
-    };
+    paginate_with(
+        db,
+        entity,
+        condition,
+        pagination_query,
+        OutOfRange::EmptyPage,
+    )
+    .await
+}
+
+/// Like [`paginate`], with a policy for pages past the last one.
+///
+/// # Examples
+///
+/// ```
+/// use loco_rs::tests_cfg::db;
+/// use sea_orm::EntityTrait;
+/// use loco_rs::prelude::*;
+///
+/// async fn example() {
+///     let db = db::dummy_connection().await;
+///     let pagination_query = query::PaginationQuery::page(9999);
+///     let res = query::paginate_with(
+///         &db,
+///         db::test_db::Entity::find(),
+///         None,
+///         &pagination_query,
+///         query::OutOfRange::ClampToLast,
+///     )
+///     .await;
+/// }
+/// ````
+///
+/// # Errors
+///
+/// Returns a `LocoResult` indicating any errors that occur during
+/// pagination, and [`Error::NotFound`] for a page past the last one with
+/// [`OutOfRange::Error`].
+pub async fn paginate_with<E>(
+    db: &DatabaseConnection,
+    entity: Select<E>,
+    condition: Option<Condition>,
+    pagination_query: &PaginationQuery,
+    out_of_range: OutOfRange,
+) -> LocoResult<PageResponse<E::Model>>
+where
+    E: EntityTrait,
+    <E as EntityTrait>::Model: Sync,
+{
     let entity = if let Some(condition) = condition {
         entity.filter(condition)
     } else {
         entity
     };
+    let entity = order_by_primary_key_if_unordered(entity);
 
+    let estimated_rows = if pagination_query.count_strategy == CountStrategy::Estimated {
+        estimate_rows(db, &entity).await
+    } else {
+        None
+    };
+    let debug_sql = matches!(&pagination_query.debug, Some(env) if *env != Environment::Production)
+        .then(|| entity.build(db.get_database_backend()).to_string());
+    let pagination_query = pagination_query.normalize();
+    let lookahead = if pagination_query.count_strategy == CountStrategy::None {
+        let page_size = pagination_query.page_size;
+        Some(
+            entity
+                .clone()
+                .offset((pagination_query.page - 1).saturating_mul(page_size))
+                .limit(page_size.saturating_add(1).min(MAX_LIMIT))
+                .all(db)
+                .await?,
+        )
+    } else {
+        None
+    };
     let query = entity.paginate(db, pagination_query.page_size);
-    let total_pages = query.num_pages().await?;
-    let page: Vec<<E as EntityTrait>::Model> = query.fetch_page(page).await?;
+    let mut res = fetch(
+        &query,
+        &pagination_query,
+        out_of_range,
+        estimated_rows,
+        lookahead,
+    )
+    .await?;
+    res.debug_sql = debug_sql;
+    Ok(res)
+}
+
+/// Returns the database estimate of the rows a query returns.
+async fn estimate_rows<E: EntityTrait>(db: &DatabaseConnection, entity: &Select<E>) -> Option<u64> {
+    let backend = db.get_database_backend();
+    if backend == DbBackend::Postgres && is_unfiltered(entity) {
+        return estimate_table_rows::<E>(db).await;
+    }
+    let statement = entity.build(backend);
+    let explain = Statement::from_sql_and_values(
+        backend,
+        format!("EXPLAIN {}", statement.sql),
+        statement.values.map(|values| values.0).unwrap_or_default(),
+    );
+
+    let rows = match backend {
+        DbBackend::Postgres => {
+            // the first plan line ends with `(cost=.. rows=<estimate> width=..)`
+            let plan: String = db
+                .query_one(explain)
+                .await
+                .ok()??
+                .try_get_by_index(0)
+                .ok()?;
+            plan.split("rows=")
+                .nth(1)?
+                .split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse()
+                .ok()
+        }
+        DbBackend::MySql => db
+            .query_one(explain)
+            .await
+            .ok()??
+            .try_get::<Option<u64>>("", "rows")
+            .ok()?,
+        DbBackend::Sqlite => None,
+    };
+    if rows.is_none() {
+        tracing::debug!(
+            table = E::default().table_name(),
+            "no row estimate, counting rows"
+        );
+    }
+    rows
+}
+
+/// Returns the `pg_class.reltuples` estimate of the rows of the table, which
+/// is `-1` until the table is analyzed.
+async fn estimate_table_rows<E: EntityTrait>(db: &DatabaseConnection) -> Option<u64> {
+    let quote = |ident: &str| format!("\"{}\"", ident.replace('"', "\"\""));
+    let table = E::default();
+    let name = table.schema_name().map_or_else(
+        || quote(table.table_name()),
+        |schema| format!("{}.{}", quote(schema), quote(table.table_name())),
+    );
+    let rows: i64 = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT reltuples::bigint FROM pg_class WHERE oid = to_regclass($1)",
+            [name.into()],
+        ))
+        .await
+        .ok()??
+        .try_get_by_index(0)
+        .ok()?;
+    let rows = u64::try_from(rows).ok();
+    if rows.is_none() {
+        tracing::debug!(
+            table = table.table_name(),
+            "table never analyzed, counting rows"
+        );
+    }
+    rows
+}
+
+/// Whether the query selects all the rows of its entity, in any order.
+fn is_unfiltered<E: EntityTrait>(entity: &Select<E>) -> bool {
+    let mut query = entity.as_query().clone();
+    query.clear_order_by();
+    let mut all = E::find().into_query();
+    all.clear_order_by();
+    query == all
+}
 
-    let paginated_response = PageResponse { page, total_pages };
+/// Orders the query by the primary key when it has no ordering at all.
+fn order_by_primary_key_if_unordered<E: EntityTrait>(entity: Select<E>) -> Select<E> {
+    let mut unordered = entity.as_query().clone();
+    unordered.clear_order_by();
+    if &unordered != entity.as_query() {
+        return entity;
+    }
 
-    Ok(paginated_response)
+    tracing::warn!(
+        table = E::default().table_name(),
+        "paginating a query without ordering, ordering by primary key"
+    );
+    E::PrimaryKey::iter().fold(entity, |entity, key| entity.order_by_asc(key.into_column()))
 }
 
 /// Fetching a page from a selector.
@@ -186,6 +544,7 @@ where
 ///     let pagination_query = query::PaginationQuery {
 ///         page_size: 100,
 ///         page: 1,
+///         ..Default::default()
 ///     };
 ///     let res = query::fetch_page(&db, db::test_db::Entity::find(), &query::PaginationQuery::page(2)).await;
 /// }
@@ -204,15 +563,437 @@ where
     C: ConnectionTrait + Sync,
     S: PaginatorTrait<'db, C> + Send,
 {
-    let page = if pagination_query.page <= 1 {
-        0
-    } else {
-        pagination_query.page - 1
-    };
+    fetch_page_with(db, selector, pagination_query, OutOfRange::EmptyPage).await
+}
 
+/// Like [`fetch_page`], with a policy for pages past the last one.
+///
+/// # Errors
+///
+/// Returns a `LocoResult` indicating any errors that occur during the fetch,
+/// and [`Error::NotFound`] for a page past the last one with
+/// [`OutOfRange::Error`].
+pub async fn fetch_page_with<'db, C, S>(
+    db: &'db C,
+    selector: S,
+    pagination_query: &PaginationQuery,
+    out_of_range: OutOfRange,
+) -> LocoResult<PageResponse<<<S as PaginatorTrait<'db, C>>::Selector as SelectorTrait>::Item>>
+where
+    C: ConnectionTrait + Sync,
+    S: PaginatorTrait<'db, C> + Send,
+{
+    let pagination_query = pagination_query.normalize();
     let query = selector.paginate(db, pagination_query.page_size);
-    let total_pages = query.num_pages().await?;
-    let page = query.fetch_page(page).await?;
+    fetch(&query, &pagination_query, out_of_range, None, None).await
+}
+
+/// Fetches the page of `pagination_query`. With [`CountStrategy::None`],
+/// `lookahead` holds the rows of the page and the first row of the next one,
+/// if they were already fetched.
+async fn fetch<'db, C, S>(
+    query: &Paginator<'db, C, S>,
+    pagination_query: &PaginationQuery,
+    out_of_range: OutOfRange,
+    estimated_rows: Option<u64>,
+    lookahead: Option<Vec<S::Item>>,
+) -> LocoResult<PageResponse<S::Item>>
+where
+    C: ConnectionTrait,
+    S: SelectorTrait + 'db,
+{
+    let requested = pagination_query.page;
+    let page_size = pagination_query.page_size;
+    let total_pages = match (pagination_query.count_strategy, estimated_rows) {
+        (CountStrategy::None, _) => {
+            let (page, next) = match lookahead {
+                Some(mut page) => {
+                    let next = page.len() as u64 > page_size;
+                    page.truncate(usize::try_from(page_size).unwrap_or(usize::MAX));
+                    (page, Some(next))
+                }
+                None => (query.fetch_page(requested - 1).await?, None),
+            };
+            // past the last page, only clamping or failing needs the count
+            if !page.is_empty() || requested == 1 || out_of_range == OutOfRange::EmptyPage {
+                let has_next = match next {
+                    Some(next) => next,
+                    None => {
+                        page.len() as u64 >= page_size
+                            && !query.fetch_page(requested).await?.is_empty()
+                    }
+                };
+                return Ok(PageResponse {
+                    out_of_range: (page.is_empty() && requested > 1).then_some(out_of_range),
+                    page,
+                    total_pages: 0,
+                    current_page: requested,
+                    page_size,
+                    has_next,
+                    debug_sql: None,
+                });
+            }
+            query.num_pages().await?
+        }
+        (CountStrategy::Estimated, Some(rows)) => {
+            // rounds up without overflowing, as `u64::div_ceil` is past the MSRV
+            rows / page_size + u64::from(rows % page_size != 0)
+        }
+        _ => query.num_pages().await?,
+    };
+    // an empty result still has a (empty) first page
+    let last_page = total_pages.max(1);
+
+    if requested <= last_page {
+        return Ok(PageResponse {
+            page: query.fetch_page(requested - 1).await?,
+            total_pages,
+            current_page: requested,
+            page_size,
+            out_of_range: None,
+            has_next: requested < last_page,
+            debug_sql: None,
+        });
+    }
+
+    match out_of_range {
+        OutOfRange::EmptyPage => Ok(PageResponse {
+            page: vec![],
+            total_pages,
+            current_page: requested,
+            page_size,
+            out_of_range: Some(out_of_range),
+            has_next: false,
+            debug_sql: None,
+        }),
+        OutOfRange::ClampToLast => Ok(PageResponse {
+            page: query.fetch_page(last_page - 1).await?,
+            total_pages,
+            current_page: last_page,
+            page_size,
+            out_of_range: Some(out_of_range),
+            has_next: false,
+            debug_sql: None,
+        }),
+        OutOfRange::Error => Err(Error::NotFound),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::tests_cfg::db::{seeded_connection, test_db};
+
+    async fn setup() -> DatabaseConnection {
+        seeded_connection(&["a", "b", "c"]).await
+    }
+
+    async fn page(out_of_range: OutOfRange) -> LocoResult<PageResponse<test_db::Model>> {
+        let pagination_query = PaginationQuery {
+            page_size: 2,
+            page: 9999,
+            ..Default::default()
+        };
+        paginate_with(
+            &setup().await,
+            test_db::Entity::find(),
+            None,
+            &pagination_query,
+            out_of_range,
+        )
+        .await
+    }
+
+    #[test]
+    fn unordered_query_is_ordered_by_primary_key() {
+        let query_str = order_by_primary_key_if_unordered(test_db::Entity::find())
+            .build(sea_orm::DatabaseBackend::Sqlite)
+            .to_string();
+        assert!(query_str.ends_with(r#"FROM "loco" ORDER BY "loco"."id" ASC"#));
+
+        let query_str = order_by_primary_key_if_unordered(
+            test_db::Entity::find().order_by_desc(test_db::Column::Name),
+        )
+        .build(sea_orm::DatabaseBackend::Sqlite)
+        .to_string();
+        assert!(query_str.ends_with(r#"FROM "loco" ORDER BY "loco"."name" DESC"#));
+    }
+
+    #[test]
+    fn pagination_links() {
+        let query = |page| PaginationQuery {
+            page_size: 10,
+            page,
+            ..Default::default()
+        };
+
+        let links = PaginationLinks::new("/notes", &query(3), 5);
+        assert_eq!(links.first, "/notes?page=1&page_size=10");
+        assert_eq!(links.prev.as_deref(), Some("/notes?page=2&page_size=10"));
+        assert_eq!(links.next.as_deref(), Some("/notes?page=4&page_size=10"));
+        assert_eq!(links.last, "/notes?page=5&page_size=10");
+
+        let links = PaginationLinks::new("/notes?title=loco", &query(1), 5);
+        assert_eq!(links.prev, None);
+        assert_eq!(
+            links.next.as_deref(),
+            Some("/notes?title=loco&page=2&page_size=10")
+        );
+
+        let links = PaginationLinks::new("/notes", &query(5), 5);
+        assert_eq!(links.next, None);
+        assert_eq!(
+            links.link_header(),
+            "</notes?page=1&page_size=10>; rel=\"first\", </notes?page=4&page_size=10>; \
+             rel=\"prev\", </notes?page=5&page_size=10>; rel=\"last\""
+        );
+
+        let links = PaginationLinks::new("/notes", &query(1), 0);
+        assert_eq!((links.prev, links.next), (None, None));
+        assert_eq!(links.last, "/notes?page=1&page_size=10");
+    }
+
+    #[tokio::test]
+    async fn out_of_range_empty_page() {
+        let res = page(OutOfRange::EmptyPage).await.unwrap();
+
+        assert!(res.page.is_empty());
+        assert_eq!(res.total_pages, 2);
+        assert_eq!(res.current_page, 9999);
+        assert_eq!(res.out_of_range, Some(OutOfRange::EmptyPage));
+    }
+
+    #[tokio::test]
+    async fn out_of_range_clamp_to_last() {
+        let res = page(OutOfRange::ClampToLast).await.unwrap();
+
+        assert_eq!(
+            res.page.into_iter().map(|m| m.name).collect::<Vec<_>>(),
+            ["c"]
+        );
+        assert_eq!(res.current_page, 2);
+        assert_eq!(res.out_of_range, Some(OutOfRange::ClampToLast));
+    }
+
+    #[tokio::test]
+    async fn out_of_range_error() {
+        assert!(matches!(
+            page(OutOfRange::Error).await,
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn in_range_page() {
+        let db = setup().await;
+        let res = paginate_with(
+            &db,
+            test_db::Entity::find(),
+            None,
+            &PaginationQuery {
+                page_size: 2,
+                page: 1,
+                ..Default::default()
+            },
+            OutOfRange::Error,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(res.page.len(), 2);
+        assert_eq!(res.current_page, 1);
+        assert_eq!(res.out_of_range, None);
+    }
+
+    async fn page_from_params(page: &str, page_size: &str) -> PageResponse<test_db::Model> {
+        let pagination_query: PaginationQuery =
+            serde_json::from_value(serde_json::json!({ "page": page, "page_size": page_size }))
+                .unwrap();
+        paginate(
+            &setup().await,
+            test_db::Entity::find(),
+            None,
+            &pagination_query,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn page_zero_is_first_page() {
+        let res = page_from_params("0", "2").await;
+
+        assert_eq!(
+            res.page.into_iter().map(|m| m.name).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(res.current_page, 1);
+        assert!(res.has_next);
+    }
+
+    #[tokio::test]
+    async fn negative_page_is_first_page() {
+        let res = page_from_params("-3", "2").await;
+
+        assert_eq!(res.page.len(), 2);
+        assert_eq!(res.current_page, 1);
+        assert_eq!(res.out_of_range, None);
+    }
+
+    #[tokio::test]
+    async fn page_size_is_clamped_to_max() {
+        let res = page_from_params("1", "100000").await;
 
-    Ok(PageResponse { page, total_pages })
+        assert_eq!(res.page.len(), 3);
+        assert_eq!(res.page_size, 100);
+        assert_eq!(res.total_pages, 1);
+
+        let res = paginate(
+            &setup().await,
+            test_db::Entity::find(),
+            None,
+            &PaginationQuery {
+                page_size: 100_000,
+                max_page_size: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.page.len(), 2);
+        assert_eq!(res.page_size, 2);
+        assert_eq!(res.total_pages, 2);
+
+        let res = paginate(
+            &setup().await,
+            test_db::Entity::find(),
+            None,
+            &PaginationQuery {
+                page_size: u64::MAX,
+                max_page_size: None,
+                count_strategy: CountStrategy::None,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.page.len(), 3);
+        assert!(!res.has_next);
+
+        assert!(
+            serde_json::from_value::<PaginationQuery>(serde_json::json!({ "page": "one" }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn only_estimates_table_rows_of_unfiltered_queries() {
+        assert!(is_unfiltered(&test_db::Entity::find()));
+        assert!(is_unfiltered(
+            &test_db::Entity::find().order_by_desc(test_db::Column::Name)
+        ));
+        assert!(!is_unfiltered(
+            &test_db::Entity::find().filter(test_db::Column::Name.eq("a"))
+        ));
+    }
+
+    async fn page_counted_by(
+        count_strategy: CountStrategy,
+        page: u64,
+    ) -> (PageResponse<test_db::Model>, Vec<String>) {
+        let mut db = setup().await;
+        let statements = Arc::new(Mutex::new(Vec::new()));
+        let recorded = statements.clone();
+        db.set_metric_callback(move |info| {
+            recorded.lock().unwrap().push(info.statement.sql.clone());
+        });
+
+        let res = paginate(
+            &db,
+            test_db::Entity::find(),
+            None,
+            &PaginationQuery {
+                page_size: 2,
+                page,
+                count_strategy,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let statements = statements.lock().unwrap().clone();
+        (res, statements)
+    }
+
+    #[tokio::test]
+    async fn count_strategy_none_skips_count() {
+        let (res, statements) = page_counted_by(CountStrategy::None, 1).await;
+        assert_eq!(res.page.len(), 2);
+        assert_eq!(res.total_pages, 0);
+        assert!(res.has_next);
+        assert_eq!(statements.len(), 1, "{statements:?}");
+        assert!(!statements.iter().any(|sql| sql.contains("COUNT")));
+
+        let (res, statements) = page_counted_by(CountStrategy::None, 2).await;
+        assert_eq!(
+            res.page.into_iter().map(|m| m.name).collect::<Vec<_>>(),
+            ["c"]
+        );
+        assert!(!res.has_next);
+        assert_eq!(statements.len(), 1, "{statements:?}");
+        assert!(!statements.iter().any(|sql| sql.contains("COUNT")));
+
+        let (res, _) = page_counted_by(CountStrategy::None, 3).await;
+        assert!(res.page.is_empty());
+        assert!(!res.has_next);
+        assert_eq!(res.out_of_range, Some(OutOfRange::EmptyPage));
+    }
+
+    #[tokio::test]
+    async fn count_strategies_count_pages() {
+        let (res, statements) = page_counted_by(CountStrategy::Exact, 1).await;
+        assert_eq!(res.total_pages, 2);
+        assert!(res.has_next);
+        assert!(statements.iter().any(|sql| sql.contains("COUNT")));
+
+        // there is no estimate on SQLite, so rows are counted
+        let (res, _) = page_counted_by(CountStrategy::Estimated, 2).await;
+        assert_eq!(res.total_pages, 2);
+        assert!(!res.has_next);
+    }
+
+    #[tokio::test]
+    async fn debug_sql_is_opt_in() {
+        let db = setup().await;
+        let debug_sql = |debug| {
+            let db = &db;
+            async move {
+                paginate(
+                    db,
+                    test_db::Entity::find(),
+                    Some(
+                        crate::model::query::condition()
+                            .eq(test_db::Column::Name, "a")
+                            .build(),
+                    ),
+                    &PaginationQuery {
+                        debug,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap()
+                .debug_sql
+            }
+        };
+
+        assert_eq!(debug_sql(None).await, None);
+        assert_eq!(debug_sql(Some(Environment::Production)).await, None);
+        for env in [Environment::Development, Environment::Test] {
+            let sql = debug_sql(Some(env)).await.unwrap();
+            assert!(sql.contains(r#"WHERE "loco"."name" = 'a'"#), "{sql}");
+        }
+    }
 }
@@ -0,0 +1,266 @@
+//! Columns in the middle of a rename.
+//!
+//! A zero-downtime rename happens in phases: the new column is added and
+//! backfilled, the app switches over, and the old column is dropped later.
+//! Instances of the app roll forward at different times, so during the
+//! deploy a query can run against either schema. [`RenamedColumn`] probes the
+//! table for which of the two names exists and hands out the right one.
+//!
+//! The old column is read for as long as it exists, since the new one may
+//! not be backfilled yet. Once it is, deploy the app with the column marked
+//! [`RenamedColumn::backfilled`] so it reads the new one, and only then drop
+//! the old column.
+//!
+//! ```rust
+//! use loco_rs::model::query::RenamedColumn;
+//! use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, sea_query::Expr};
+//! use loco_rs::tests_cfg::db::test_db;
+//!
+//! static TITLE: RenamedColumn = RenamedColumn::new("loco", "name", "title").backfilled();
+//!
+//! async fn find_loco(db: &DatabaseConnection) -> loco_rs::model::ModelResult<Vec<test_db::Model>> {
+//!     let title = TITLE.expr(db).await?;
+//!     Ok(test_db::Entity::find().filter(title.eq("loco")).all(db).await?)
+//! }
+//! ```
+
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use sea_orm::{
+    sea_query::{Alias, Expr},
+    ConnectionTrait, DbBackend, DbErr, Statement,
+};
+
+use crate::model::ModelResult;
+
+/// A column being renamed from `old` to `new`.
+///
+/// The old name is used while it exists, unless the new column is marked
+/// [`RenamedColumn::backfilled`]. The new name, once resolved, is cached for
+/// the lifetime of the value, while the old one is probed again every
+/// [`RenamedColumn::recheck`] in case the column was dropped. Keep the value
+/// in a `static`, one per database.
+#[derive(Debug)]
+pub struct RenamedColumn {
+    table: &'static str,
+    old: &'static str,
+    new: &'static str,
+    backfilled: bool,
+    recheck: Duration,
+    resolved: RwLock<Option<(&'static str, Instant)>>,
+}
+
+impl RenamedColumn {
+    #[must_use]
+    pub const fn new(table: &'static str, old: &'static str, new: &'static str) -> Self {
+        Self {
+            table,
+            old,
+            new,
+            backfilled: false,
+            recheck: Duration::from_secs(60),
+            resolved: RwLock::new(None),
+        }
+    }
+
+    /// Marks the new column as backfilled, so it is used as soon as it
+    /// exists.
+    #[must_use]
+    pub const fn backfilled(mut self) -> Self {
+        self.backfilled = true;
+        self
+    }
+
+    /// Sets how long the old name is used before probing the table again,
+    /// one minute by default.
+    #[must_use]
+    pub const fn recheck(mut self, every: Duration) -> Self {
+        self.recheck = every;
+        self
+    }
+
+    /// Returns the name the column currently has in the database.
+    ///
+    /// # Errors
+    /// When the probe query fails, or neither column exists.
+    pub async fn resolve<C: ConnectionTrait>(&self, db: &C) -> ModelResult<&'static str> {
+        let cached = self.resolved.read().ok().and_then(|resolved| *resolved);
+        if let Some((name, at)) = cached {
+            // the new column stays, the old one goes away at the end
+            if name == self.new || at.elapsed() < self.recheck {
+                return Ok(name);
+            }
+        }
+        let name = self.probe(db).await?;
+        if let Ok(mut resolved) = self.resolved.write() {
+            *resolved = Some((name, Instant::now()));
+        }
+        Ok(name)
+    }
+
+    /// Returns the column, qualified with its table, as an expression.
+    ///
+    /// # Errors
+    /// See [`RenamedColumn::resolve`].
+    pub async fn expr<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Expr> {
+        let name = self.resolve(db).await?;
+        Ok(Expr::col((Alias::new(self.table), Alias::new(name))))
+    }
+
+    async fn probe<C: ConnectionTrait>(&self, db: &C) -> ModelResult<&'static str> {
+        let backend = db.get_database_backend();
+        let sql = match backend {
+            DbBackend::Postgres => {
+                "SELECT column_name::text AS name FROM information_schema.columns WHERE \
+                 table_schema = current_schema() AND table_name = $1 AND column_name IN ($2, $3)"
+            }
+            DbBackend::MySql => {
+                "SELECT column_name AS name FROM information_schema.columns WHERE table_schema = \
+                 DATABASE() AND table_name = ? AND column_name IN (?, ?)"
+            }
+            DbBackend::Sqlite => "SELECT name FROM pragma_table_info(?) WHERE name IN (?, ?)",
+        };
+        let rows = db
+            .query_all(Statement::from_sql_and_values(
+                backend,
+                sql,
+                [self.table.into(), self.old.into(), self.new.into()],
+            ))
+            .await?;
+        let mut names = vec![];
+        for row in rows {
+            names.push(row.try_get::<String>("", "name")?);
+        }
+
+        let has_old = names.iter().any(|name| name == self.old);
+        let has_new = names.iter().any(|name| name == self.new);
+        if has_new && (self.backfilled || !has_old) {
+            Ok(self.new)
+        } else if has_old {
+            Ok(self.old)
+        } else {
+            Err(DbErr::Custom(format!(
+                "neither `{}` nor `{}` exists on `{}`",
+                self.old, self.new, self.table
+            ))
+            .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{Database, DatabaseConnection, EntityTrait, QueryFilter, QueryTrait};
+
+    use super::*;
+    use crate::tests_cfg::db::test_db;
+
+    async fn db(columns: &str) -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared(&format!(
+            "CREATE TABLE loco (id INTEGER PRIMARY KEY, {columns})"
+        ))
+        .await
+        .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn uses_old_column_before_rename() {
+        let column = RenamedColumn::new("loco", "name", "title");
+        assert_eq!(
+            column.resolve(&db("name TEXT").await).await.unwrap(),
+            "name"
+        );
+    }
+
+    #[tokio::test]
+    async fn uses_old_column_until_backfilled() {
+        let column = RenamedColumn::new("loco", "name", "title");
+        assert_eq!(
+            column
+                .resolve(&db("name TEXT, title TEXT").await)
+                .await
+                .unwrap(),
+            "name"
+        );
+
+        let column = RenamedColumn::new("loco", "name", "title").backfilled();
+        assert_eq!(
+            column
+                .resolve(&db("name TEXT, title TEXT").await)
+                .await
+                .unwrap(),
+            "title"
+        );
+
+        let column = RenamedColumn::new("loco", "name", "title").backfilled();
+        assert_eq!(
+            column.resolve(&db("name TEXT").await).await.unwrap(),
+            "name"
+        );
+    }
+
+    #[tokio::test]
+    async fn uses_new_column_after_rename() {
+        let column = RenamedColumn::new("loco", "name", "title");
+        let db = db("title TEXT").await;
+        assert_eq!(column.resolve(&db).await.unwrap(), "title");
+
+        let query = test_db::Entity::find()
+            .filter(column.expr(&db).await.unwrap().eq("loco"))
+            .build(DbBackend::Sqlite)
+            .to_string();
+        assert!(query.ends_with(r#"WHERE "loco"."title" = 'loco'"#));
+    }
+
+    #[tokio::test]
+    async fn caches_probe() {
+        let column = RenamedColumn::new("loco", "name", "title");
+        assert_eq!(
+            column.resolve(&db("name TEXT").await).await.unwrap(),
+            "name"
+        );
+        assert_eq!(
+            column.resolve(&db("title TEXT").await).await.unwrap(),
+            "name"
+        );
+
+        let column = RenamedColumn::new("loco", "name", "title")
+            .backfilled()
+            .recheck(Duration::ZERO);
+        assert_eq!(
+            column.resolve(&db("title TEXT").await).await.unwrap(),
+            "title"
+        );
+        assert_eq!(
+            column.resolve(&db("name TEXT").await).await.unwrap(),
+            "title"
+        );
+    }
+
+    #[tokio::test]
+    async fn probes_old_column_again() {
+        let column = RenamedColumn::new("loco", "name", "title").recheck(Duration::ZERO);
+        assert_eq!(
+            column
+                .resolve(&db("name TEXT, title TEXT").await)
+                .await
+                .unwrap(),
+            "name"
+        );
+        assert_eq!(
+            column.resolve(&db("title TEXT").await).await.unwrap(),
+            "title"
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_without_either_column() {
+        let column = RenamedColumn::new("loco", "name", "title");
+        assert!(column.resolve(&db("other TEXT").await).await.is_err());
+    }
+}
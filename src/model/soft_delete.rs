@@ -0,0 +1,291 @@
+//! # Soft Delete
+//!
+//! Marks rows as deleted by setting a `deleted_at` timestamp instead of
+//! removing them, so they can later be restored.
+//!
+//! Implement [`SoftDelete`] on an entity to point at its `deleted_at`
+//! column; its active models then get `soft_delete`, `restore` and
+//! `force_delete` through [`SoftDeleteActiveModel`].
+//!
+//! Query with [`SoftDelete::find_not_deleted`] or
+//! [`crate::model::query::exec::find_all`] to skip soft-deleted rows, and
+//! with [`SoftDelete::with_trashed`] to include them.
+//!
+//! ```rust,ignore
+//! impl SoftDelete for notes::Entity {
+//!     fn deleted_at_column() -> Self::Column {
+//!         notes::Column::DeletedAt
+//!     }
+//! }
+//!
+//! let note = notes::Entity::find_not_deleted().one(&ctx.db).await?;
+//! note.into_active_model().soft_delete(&ctx.db).await?;
+//! ```
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    sea_query::ColumnType, ActiveModelBehavior, ActiveModelTrait, ColumnTrait, ConnectionTrait,
+    DeleteResult, EntityTrait, IntoActiveModel, QueryFilter, Select, Value,
+};
+
+use super::ModelResult;
+
+/// An entity whose rows are soft deleted through a nullable timestamp column.
+pub trait SoftDelete: EntityTrait {
+    /// The column holding the time a row was soft deleted, `NULL` while the
+    /// row is live.
+    fn deleted_at_column() -> Self::Column;
+
+    /// Like [`EntityTrait::find`], excluding soft-deleted rows.
+    #[must_use]
+    fn find_not_deleted() -> Select<Self> {
+        Self::find().filter(Self::deleted_at_column().is_null())
+    }
+
+    /// Selects only the soft-deleted rows.
+    #[must_use]
+    fn find_deleted() -> Select<Self> {
+        Self::find().filter(Self::deleted_at_column().is_not_null())
+    }
+
+    /// Selects all rows, soft-deleted or not. The same as
+    /// [`EntityTrait::find`], spelled out where including soft-deleted rows
+    /// is deliberate.
+    #[must_use]
+    fn with_trashed() -> Select<Self> {
+        Self::find()
+    }
+}
+
+/// Soft delete operations on the active model of a [`SoftDelete`] entity.
+#[async_trait]
+pub trait SoftDeleteActiveModel: ActiveModelTrait + ActiveModelBehavior + Send
+where
+    Self::Entity: SoftDelete,
+    <Self::Entity as EntityTrait>::Model: IntoActiveModel<Self>,
+{
+    /// Sets the `deleted_at` column to now.
+    ///
+    /// # Errors
+    /// When the update fails.
+    async fn soft_delete<C>(mut self, db: &C) -> ModelResult<<Self::Entity as EntityTrait>::Model>
+    where
+        C: ConnectionTrait,
+    {
+        let col = Self::Entity::deleted_at_column();
+        self.set(col, deleted_at_now(&col));
+        Ok(self.update(db).await?)
+    }
+
+    /// Clears the `deleted_at` column, bringing the row back into
+    /// [`SoftDelete::find_not_deleted`] queries.
+    ///
+    /// # Errors
+    /// When the update fails.
+    async fn restore<C>(mut self, db: &C) -> ModelResult<<Self::Entity as EntityTrait>::Model>
+    where
+        C: ConnectionTrait,
+    {
+        let col = Self::Entity::deleted_at_column();
+        let null = deleted_at_now(&col).as_null();
+        self.set(col, null);
+        Ok(self.update(db).await?)
+    }
+
+    /// Permanently removes the row.
+    ///
+    /// # Errors
+    /// When the delete fails.
+    async fn force_delete<C>(self, db: &C) -> ModelResult<DeleteResult>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(self.delete(db).await?)
+    }
+}
+
+impl<A> SoftDeleteActiveModel for A
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Send,
+    A::Entity: SoftDelete,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+}
+
+/// The current time, typed to match the `deleted_at` column definition.
+fn deleted_at_now<C: ColumnTrait>(col: &C) -> Value {
+    let now = Utc::now();
+    match col.def().get_column_type() {
+        ColumnType::TimestampWithTimeZone => {
+            Value::ChronoDateTimeWithTimeZone(Some(Box::new(now.fixed_offset())))
+        }
+        _ => Value::ChronoDateTime(Some(Box::new(now.naive_utc()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{entity::prelude::*, ConnectionTrait, Schema, Set};
+
+    use super::*;
+    use crate::{
+        model::query::{self, exec},
+        tests_cfg,
+    };
+
+    mod notes {
+        use sea_orm::entity::prelude::*;
+
+        #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+        #[sea_orm(table_name = "notes")]
+        pub struct Model {
+            #[sea_orm(primary_key)]
+            pub id: i32,
+            pub title: String,
+            pub deleted_at: Option<DateTime>,
+        }
+
+        #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+
+        impl super::SoftDelete for Entity {
+            fn deleted_at_column() -> Self::Column {
+                Column::DeletedAt
+            }
+        }
+    }
+
+    async fn setup() -> (DatabaseConnection, notes::Model) {
+        let db = tests_cfg::db::dummy_connection().await;
+        let backend = db.get_database_backend();
+        db.execute(backend.build(&Schema::new(backend).create_table_from_entity(notes::Entity)))
+            .await
+            .unwrap();
+
+        let note = notes::ActiveModel {
+            title: Set("loco".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        (db, note)
+    }
+
+    #[tokio::test]
+    async fn soft_deleted_rows_are_excluded() {
+        let (db, note) = setup().await;
+
+        let deleted = note.into_active_model().soft_delete(&db).await.unwrap();
+        assert!(deleted.deleted_at.is_some());
+
+        assert!(notes::Entity::find_not_deleted()
+            .all(&db)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(
+            exec::find_all::<notes::Entity, _>(&db, query::condition().build())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            notes::Entity::find_deleted().all(&db).await.unwrap(),
+            vec![deleted]
+        );
+    }
+
+    #[tokio::test]
+    async fn can_restore() {
+        let (db, note) = setup().await;
+
+        let deleted = note.into_active_model().soft_delete(&db).await.unwrap();
+        let restored = deleted.into_active_model().restore(&db).await.unwrap();
+        assert!(restored.deleted_at.is_none());
+
+        assert_eq!(
+            notes::Entity::find_not_deleted().all(&db).await.unwrap(),
+            vec![restored.clone()]
+        );
+        assert_eq!(
+            exec::find_all::<notes::Entity, _>(&db, query::condition().build())
+                .await
+                .unwrap(),
+            vec![restored]
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_skips_trashed_rows() {
+        let (db, note) = setup().await;
+        let live = notes::ActiveModel {
+            title: Set("live".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let deleted = exec::soft_delete(&db, note.into_active_model())
+            .await
+            .unwrap();
+        assert!(deleted.deleted_at.is_some());
+
+        assert_eq!(
+            exec::find_all::<notes::Entity, _>(&db, query::condition().build())
+                .await
+                .unwrap(),
+            vec![live.clone()]
+        );
+        assert_eq!(
+            exec::find_all::<notes::Entity, _>(
+                &db,
+                query::condition().like(notes::Column::Title, "%").build()
+            )
+            .await
+            .unwrap(),
+            vec![live.clone()]
+        );
+        assert_eq!(
+            query::paginate(
+                &db,
+                notes::Entity::find_not_deleted(),
+                None,
+                &query::PaginationQuery::default(),
+            )
+            .await
+            .unwrap()
+            .page,
+            vec![live.clone()]
+        );
+        assert_eq!(
+            notes::Entity::find()
+                .filter(
+                    query::condition()
+                        .not_deleted(notes::Column::DeletedAt)
+                        .build()
+                )
+                .all(&db)
+                .await
+                .unwrap(),
+            vec![live.clone()]
+        );
+        assert_eq!(
+            notes::Entity::with_trashed().all(&db).await.unwrap(),
+            vec![deleted, live]
+        );
+    }
+
+    #[tokio::test]
+    async fn can_force_delete() {
+        let (db, note) = setup().await;
+
+        let res = note.into_active_model().force_delete(&db).await.unwrap();
+        assert_eq!(res.rows_affected, 1);
+        assert!(notes::Entity::find().all(&db).await.unwrap().is_empty());
+    }
+}
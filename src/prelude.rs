@@ -22,7 +22,9 @@ pub use serde_json::json as data;
 #[cfg(all(feature = "auth_jwt", feature = "with-db"))]
 pub use crate::controller::middleware::auth;
 #[cfg(feature = "with-db")]
-pub use crate::model::{query, Authenticable, ModelError, ModelResult};
+pub use crate::model::{
+    query, Authenticable, FindOr404, ModelError, ModelResult, SoftDelete, SoftDeleteActiveModel,
+};
 pub use crate::{
     app::{AppContext, Initializer},
     controller::{
@@ -32,8 +34,8 @@ pub use crate::{
             remote_ip::RemoteIP,
         },
         not_found, unauthorized,
-        views::{engines::TeraView, ViewEngine, ViewRenderer},
-        Json, Routes,
+        views::{engines::TeraView, ViewEngine, ViewRenderer, WithLayout},
+        Json, MatchedRoute, RouteTable, Routes, ValidJson,
     },
     errors::Error,
     mailer,
@@ -16,6 +16,8 @@ use crate::{
     Result,
 };
 
+pub mod replay;
+
 // Lazy-static constants for data cleanup patterns
 lazy_static! {
     /// Constants for cleaning up user model data, replacing certain patterns with placeholders.
@@ -158,6 +160,8 @@ pub async fn seed<H: Hooks>(db: &DatabaseConnection) -> Result<()> {
 #[allow(clippy::future_not_send)]
 /// Initiates a test request with a provided callback.
 ///
+/// When `LOCO_RECORD_REQUESTS` is set, the requests are recorded to that file
+/// for [`replay`].
 ///
 /// # Panics
 /// When could not initialize the test request.this errors can be when could not
@@ -199,7 +203,12 @@ where
         .default_content_type("application/json")
         .build();
 
-    let server = TestServer::new_with_config(boot.router.unwrap(), config).unwrap();
+    let mut router = boot.router.unwrap();
+    if let Ok(path) = std::env::var(replay::RECORD_ENV) {
+        router = replay::record(router, path);
+    }
+
+    let server = TestServer::new_with_config(router, config).unwrap();
 
     callback(server, boot.app_context.clone()).await;
 }
@@ -0,0 +1,369 @@
+//! # Request recording and replay
+//!
+//! Golden tests for an API: record the requests a test suite makes together
+//! with the responses the app gave, then replay them later against the
+//! current app and report every response that changed.
+//!
+//! Recording is opt-in. Set `LOCO_RECORD_REQUESTS` to a file path and every
+//! request made through [`crate::testing::request`] is appended to it, or
+//! wrap a router with [`record`] directly. Each exchange is stored as one JSON
+//! line.
+//!
+//! Fields that differ on every run, such as generated ids or timestamps, are
+//! ignored when comparing JSON bodies by listing their names.
+//!
+//! ```rust,ignore
+//! use loco_rs::testing::replay;
+//! use myapp::app::App;
+//!
+//! #[tokio::test]
+//! async fn responses_match_recording() {
+//!     let mismatches = replay::replay_app::<App>("tests/golden/notes.jsonl", &["id", "created_at"])
+//!         .await
+//!         .unwrap();
+//!     assert!(mismatches.is_empty(), "{mismatches:#?}");
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::Request,
+    http::{request::Parts, Method},
+    middleware::{self, Next},
+    response::Response,
+    Router as AxumRouter,
+};
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
+
+use crate::{app::Hooks, testing::boot_test, Error, Result};
+
+/// Environment variable holding the file [`crate::testing::request`] records
+/// to.
+pub const RECORD_ENV: &str = "LOCO_RECORD_REQUESTS";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub uri: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// A request and the response the app gave to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// A replayed request whose response differs from the recorded one.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    pub request: RecordedRequest,
+    pub expected: RecordedResponse,
+    pub actual: RecordedResponse,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: expected {} {}, got {} {}",
+            self.request.method,
+            self.request.uri,
+            self.expected.status,
+            self.expected.body,
+            self.actual.status,
+            self.actual.body
+        )
+    }
+}
+
+async fn read_body(body: Body) -> Result<Bytes> {
+    to_bytes(body, usize::MAX)
+        .await
+        .map_err(|err| Error::Message(err.to_string()))
+}
+
+fn recorded_request(parts: &Parts, body: &[u8]) -> RecordedRequest {
+    RecordedRequest {
+        method: parts.method.to_string(),
+        uri: parts.uri.to_string(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+#[derive(Clone)]
+struct Recorder {
+    path: Arc<PathBuf>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl Recorder {
+    async fn record(self, request: Request, next: Next) -> Result<Response> {
+        let (parts, body) = request.into_parts();
+        let body = read_body(body).await?;
+        let recorded = recorded_request(&parts, &body);
+
+        let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+        let (parts, body) = response.into_parts();
+        let body = read_body(body).await?;
+
+        let exchange = Exchange {
+            request: recorded,
+            response: RecordedResponse {
+                status: parts.status.as_u16(),
+                body: String::from_utf8_lossy(&body).into_owned(),
+            },
+        };
+        let line = serde_json::to_string(&exchange)?;
+        {
+            let _guard = self
+                .lock
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path.as_path())?;
+            writeln!(file, "{line}")?;
+        }
+
+        Ok(Response::from_parts(parts, Body::from(body)))
+    }
+}
+
+/// Wraps the router so every request and its response are appended to the
+/// file at `path`.
+pub fn record(router: AxumRouter, path: impl Into<PathBuf>) -> AxumRouter {
+    let recorder = Recorder {
+        path: Arc::new(path.into()),
+        lock: Arc::new(Mutex::new(())),
+    };
+    router.layer(middleware::from_fn(move |request, next| {
+        recorder.clone().record(request, next)
+    }))
+}
+
+/// Reads the exchanges recorded in the file at `path`.
+///
+/// # Errors
+/// When the file cannot be read or holds an invalid exchange.
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Exchange>> {
+    fs::read_to_string(path.as_ref())?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Removes the `volatile` fields, at any depth, from a JSON body.
+fn without_volatile(value: &mut serde_json::Value, volatile: &[&str]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| !volatile.contains(&key.as_str()));
+            for value in map.values_mut() {
+                without_volatile(value, volatile);
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                without_volatile(value, volatile);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn same_response(
+    expected: &RecordedResponse,
+    actual: &RecordedResponse,
+    volatile: &[&str],
+) -> bool {
+    if expected.status != actual.status {
+        return false;
+    }
+    match (
+        serde_json::from_str::<serde_json::Value>(&expected.body),
+        serde_json::from_str::<serde_json::Value>(&actual.body),
+    ) {
+        (Ok(mut expected), Ok(mut actual)) => {
+            without_volatile(&mut expected, volatile);
+            without_volatile(&mut actual, volatile);
+            expected == actual
+        }
+        _ => expected.body == actual.body,
+    }
+}
+
+async fn send(router: AxumRouter, recorded: &RecordedRequest) -> Result<RecordedResponse> {
+    let mut request = Request::builder()
+        .method(
+            Method::from_bytes(recorded.method.as_bytes())
+                .map_err(|err| Error::Message(err.to_string()))?,
+        )
+        .uri(&recorded.uri);
+    for (name, value) in &recorded.headers {
+        request = request.header(name, value);
+    }
+    let request = request
+        .body(Body::from(recorded.body.clone()))
+        .map_err(|err| Error::Message(err.to_string()))?;
+
+    let response = router
+        .oneshot(request)
+        .await
+        .map_err(|err| Error::Message(err.to_string()))?;
+    let status = response.status().as_u16();
+    let body = read_body(response.into_body()).await?;
+    Ok(RecordedResponse {
+        status,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Re-issues the requests recorded in the file at `path` against the router,
+/// in order, and returns the ones whose response changed.
+///
+/// Statuses are compared, and bodies too: JSON bodies without their
+/// `volatile` fields, other bodies as they are.
+///
+/// # Errors
+/// When the recording cannot be loaded or a request cannot be sent.
+pub async fn replay(
+    router: AxumRouter,
+    path: impl AsRef<Path>,
+    volatile: &[&str],
+) -> Result<Vec<Mismatch>> {
+    let mut mismatches = vec![];
+    for exchange in load(path)? {
+        let actual = send(router.clone(), &exchange.request).await?;
+        if !same_response(&exchange.response, &actual, volatile) {
+            mismatches.push(Mismatch {
+                request: exchange.request,
+                expected: exchange.response,
+                actual,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Boots the app for testing and replays the recording at `path` against it,
+/// see [`replay`].
+///
+/// # Errors
+/// When the app cannot boot or the recording cannot be replayed.
+pub async fn replay_app<H: Hooks>(
+    path: impl AsRef<Path>,
+    volatile: &[&str],
+) -> Result<Vec<Mismatch>> {
+    let boot = boot_test::<H>().await?;
+    let router = boot
+        .router
+        .ok_or_else(|| Error::Message("app booted without a router".to_string()))?;
+    replay(router, path, volatile).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use axum::{
+        extract::{Path as UrlPath, State},
+        routing::{get, post},
+        Json,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    fn app(suffix: &'static str) -> AxumRouter {
+        let counter = Arc::new(AtomicU32::new(0));
+        AxumRouter::new()
+            .route(
+                "/notes",
+                post(
+                    move |State(counter): State<Arc<AtomicU32>>,
+                          Json(note): Json<serde_json::Value>| async move {
+                        Json(json!({
+                            "id": counter.fetch_add(1, Ordering::SeqCst) + 100,
+                            "created_at": uuid::Uuid::new_v4().to_string(),
+                            "title": format!("{}{suffix}", note["title"].as_str().unwrap_or_default()),
+                        }))
+                    },
+                ),
+            )
+            .route(
+                "/notes/:id",
+                get(|UrlPath(id): UrlPath<u32>| async move { format!("note {id}") }),
+            )
+            .with_state(counter)
+    }
+
+    async fn call(router: AxumRouter, method: &str, uri: &str, body: &str) {
+        router
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn can_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("loco-replay-{}.jsonl", uuid::Uuid::new_v4()));
+
+        let recording = record(app(""), &path);
+        call(recording.clone(), "POST", "/notes", r#"{"title":"loco"}"#).await;
+        call(recording, "GET", "/notes/1", "").await;
+
+        let exchanges = load(&path).unwrap();
+        assert_eq!(exchanges.len(), 2);
+        assert_eq!(exchanges[0].request.method, "POST");
+        assert_eq!(exchanges[0].request.body, r#"{"title":"loco"}"#);
+        assert_eq!(exchanges[1].response.body, "note 1");
+
+        // ids and timestamps differ between runs
+        assert_eq!(replay(app(""), &path, &[]).await.unwrap().len(), 1);
+        assert!(replay(app(""), &path, &["id", "created_at"])
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mismatches = replay(app("!"), &path, &["id", "created_at"])
+            .await
+            .unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].request.uri, "/notes");
+        assert!(mismatches[0].actual.body.contains("loco!"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
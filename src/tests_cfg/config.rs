@@ -21,6 +21,7 @@ pub fn test_config() -> Config {
             port: 3000,
             host: "localhost".to_string(),
             ident: None,
+            secret_key: None,
             middlewares: middleware::Config::default(),
         },
         #[cfg(feature = "with-db")]
@@ -27,10 +27,31 @@ pub async fn dummy_connection() -> sea_orm::DatabaseConnection {
     sea_orm::Database::connect(opt).await.unwrap()
 }
 
+/// Creating a dummy db connection holding the [`test_db`] table, with a row
+/// for each of the given names
+///
+/// # Panics
+/// When the table cannot be created or a row cannot be inserted
+pub async fn seeded_connection(names: &[&str]) -> sea_orm::DatabaseConnection {
+    use sea_orm::{ActiveModelTrait, ConnectionTrait};
+
+    let db = dummy_connection().await;
+    let backend = db.get_database_backend();
+    db.execute(
+        backend.build(&sea_orm::Schema::new(backend).create_table_from_entity(test_db::Entity)),
+    )
+    .await
+    .unwrap();
+    for name in names {
+        test_db::active_model(name).insert(&db).await.unwrap();
+    }
+    db
+}
+
 pub mod test_db {
     use std::fmt;
 
-    use sea_orm::entity::prelude::*;
+    use sea_orm::{entity::prelude::*, Set};
 
     #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
     #[sea_orm(table_name = "loco")]
@@ -40,6 +61,7 @@ pub mod test_db {
         pub name: String,
         pub created_at: DateTime,
         pub updated_at: DateTime,
+        pub released_on: Option<Date>,
     }
 
     #[derive(Debug)]
@@ -68,6 +90,17 @@ pub mod test_db {
     pub enum Relation {}
 
     impl ActiveModelBehavior for ActiveModel {}
+
+    /// A new row named `name`, with default timestamps
+    #[must_use]
+    pub fn active_model(name: &str) -> ActiveModel {
+        ActiveModel {
+            name: Set(name.to_string()),
+            created_at: Set(DateTime::default()),
+            updated_at: Set(DateTime::default()),
+            ..Default::default()
+        }
+    }
 }
 
 pub struct Migrator;
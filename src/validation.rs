@@ -38,10 +38,12 @@
 //! }
 //! ```
 
+use std::collections::BTreeMap;
+
 #[cfg(feature = "with-db")]
 use sea_orm::DbErr;
 use serde::{Deserialize, Serialize};
-use validator::{Validate, ValidationError, ValidationErrors};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 
 #[derive(Debug, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
@@ -84,24 +86,7 @@ impl From<ModelValidationErrors> for DbErr {
 #[cfg(feature = "with-db")]
 #[must_use]
 pub fn into_db_error(errors: &ModelValidationErrors) -> sea_orm::DbErr {
-    use std::collections::BTreeMap;
-
-    let errors = &errors.0;
-    let error_data: BTreeMap<String, Vec<ModelValidationMessage>> = errors
-        .field_errors()
-        .iter()
-        .map(|(field, field_errors)| {
-            let errors = field_errors
-                .iter()
-                .map(|err| ModelValidationMessage {
-                    code: err.code.to_string(),
-                    message: err.message.as_ref().map(std::string::ToString::to_string),
-                })
-                .collect();
-            ((*field).to_string(), errors)
-        })
-        .collect();
-    let json_errors = serde_json::to_value(error_data);
+    let json_errors = serde_json::to_value(field_errors(&errors.0));
     match json_errors {
         Ok(errors_json) => sea_orm::DbErr::Custom(errors_json.to_string()),
         Err(err) => sea_orm::DbErr::Custom(format!(
@@ -110,6 +95,48 @@ pub fn into_db_error(errors: &ModelValidationErrors) -> sea_orm::DbErr {
     }
 }
 
+/// The messages of the field errors, by field name. The fields of nested
+/// structs are named by their path, such as `author.name`, and the fields of
+/// list items by their index, such as `tags[0].name`.
+#[must_use]
+pub fn field_errors(errors: &ValidationErrors) -> BTreeMap<String, Vec<ModelValidationMessage>> {
+    let mut fields = BTreeMap::new();
+    collect_field_errors(errors, "", &mut fields);
+    fields
+}
+
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    parent: &str,
+    fields: &mut BTreeMap<String, Vec<ModelValidationMessage>>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = if parent.is_empty() {
+            (*field).to_string()
+        } else {
+            format!("{parent}.{field}")
+        };
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                let errors = field_errors
+                    .iter()
+                    .map(|err| ModelValidationMessage {
+                        code: err.code.to_string(),
+                        message: err.message.as_ref().map(std::string::ToString::to_string),
+                    })
+                    .collect();
+                fields.insert(path, errors);
+            }
+            ValidationErrorsKind::Struct(errors) => collect_field_errors(errors, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(errors, &format!("{path}[{index}]"), fields);
+                }
+            }
+        }
+    }
+}
+
 /// Implement `Validatable` for `ActiveModel` when you want it to have a
 /// `validate()` function.
 pub trait Validatable {
@@ -140,6 +167,53 @@ mod tests {
         pub name: String,
     }
 
+    #[derive(Debug, Validate)]
+    pub struct Author {
+        #[validate(length(min = 1))]
+        pub name: String,
+        #[validate]
+        pub tags: Vec<TestValidator>,
+    }
+
+    #[derive(Debug, Validate)]
+    pub struct Post {
+        #[validate(range(min = 1))]
+        pub rating: u8,
+        #[validate]
+        pub author: Author,
+    }
+
+    #[test]
+    fn field_errors_include_nested_and_list_errors() {
+        let post = Post {
+            rating: 0,
+            author: Author {
+                name: String::new(),
+                tags: vec![
+                    TestValidator {
+                        name: "rust".to_string(),
+                    },
+                    TestValidator {
+                        name: "rs".to_string(),
+                    },
+                ],
+            },
+        };
+
+        let errors = field_errors(&post.validate().unwrap_err());
+        assert_eq!(
+            errors
+                .iter()
+                .map(|(field, errors)| (field.as_str(), errors[0].code.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("author.name", "length"),
+                ("author.tags[1].name", "length"),
+                ("rating", "range"),
+            ]
+        );
+    }
+
     #[rstest]
     #[case("test@example.com", true)]
     #[case("invalid-email", false)]
@@ -0,0 +1,58 @@
+use axum::http::StatusCode;
+use loco_rs::{controller::multipart::MultipartForm, prelude::*, tests_cfg};
+use serial_test::serial;
+
+use crate::infra_cfg;
+
+#[tokio::test]
+#[serial]
+async fn can_upload_text_field_and_file() {
+    async fn action(form: MultipartForm) -> Result<Response> {
+        let file = form.file("attachment").expect("file part");
+        format::json(serde_json::json!({
+            "title": form.text("title"),
+            "file_name": file.file_name,
+            "content_type": file.content_type,
+            "content": String::from_utf8_lossy(&file.bytes),
+        }))
+    }
+
+    let ctx: AppContext = tests_cfg::app::get_app_context().await;
+    let handle = infra_cfg::server::start_with_route(ctx, "/", post(action)).await;
+
+    let body = [
+        "--boundary",
+        "Content-Disposition: form-data; name=\"title\"",
+        "",
+        "loco",
+        "--boundary",
+        "Content-Disposition: form-data; name=\"attachment\"; filename=\"notes.txt\"",
+        "Content-Type: text/plain",
+        "",
+        "hello loco",
+        "--boundary--",
+        "",
+    ]
+    .join("\r\n");
+    let res = reqwest::Client::new()
+        .post(infra_cfg::server::get_base_url())
+        .header("content-type", "multipart/form-data; boundary=boundary")
+        .body(body)
+        .send()
+        .await
+        .expect("valid response");
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&res.text().await.expect("body"))
+            .expect("json body"),
+        serde_json::json!({
+            "title": "loco",
+            "file_name": "notes.txt",
+            "content_type": "text/plain",
+            "content": "hello loco",
+        })
+    );
+
+    handle.abort();
+}
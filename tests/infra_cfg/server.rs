@@ -65,6 +65,7 @@ pub async fn start_from_ctx(ctx: AppContext) -> tokio::task::JoinHandle<()> {
         app_context: ctx,
         router: Some(app_router),
         processor: None,
+        initializers: vec![],
     };
 
     start_from_boot(boot).await
@@ -85,6 +86,7 @@ pub async fn start_with_route(
         app_context: ctx,
         router: Some(app_router),
         processor: None,
+        initializers: vec![],
     };
     start_from_boot(boot).await
 }